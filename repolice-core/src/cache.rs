@@ -0,0 +1,130 @@
+use crate::reader::{RepoInfo, ScanOptions};
+use crate::system::now;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+/// Last-known status of clean repos, reused while nothing in their `.git` directory changed.
+///
+/// Entries are checked against the modification times of the index, HEAD, the checked out branch,
+/// the upstream, the stash, the tags and the markers of merges and rebases, and against the options of the scan.
+/// A tracked file edited in place changes none of these, the reader compares the work tree with the index
+/// before it reuses an entry. Entries also expire after a maximum age.
+/// Repos with changes are never cached, they are read again on every scan anyway to show what changed.
+pub struct Cache {
+    entries: Mutex<BTreeMap<String, Entry>>,
+    max_age: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    stamp: Stamp,
+    /// seconds since the unix epoch
+    saved: i64,
+    repos: Vec<RepoInfo>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Stamp {
+    options: String,
+    /// nanoseconds since the unix epoch per file below the repo, none for missing files
+    mtimes: Vec<(String, Option<u128>)>,
+}
+
+impl Cache {
+    /// Empty cache whose entries are used for at most `max_age`
+    pub fn new(max_age: Duration) -> Cache {
+        Cache { entries: Mutex::new(BTreeMap::new()), max_age }
+    }
+
+    /// Reads a cache saved earlier, a missing or unreadable file gives an empty cache
+    pub fn load(path: &Path, max_age: Duration) -> Cache {
+        let entries = fs::read_to_string(path).ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Cache { entries: Mutex::new(entries), max_age }
+    }
+
+    /// Writes every entry that hasn't expired yet
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| !self.expired(entry));
+        let contents = serde_json::to_string(&*entries).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// The entries read from the repo at `path` when nothing changed since they were cached
+    pub(crate) fn get(&self, path: &str, options: &ScanOptions) -> Option<Vec<RepoInfo>> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(path).filter(|entry| !self.expired(entry))?;
+        (Self::stamp(path, &entry.repos, options)? == entry.stamp).then(|| entry.repos.clone())
+    }
+
+    pub(crate) fn put(&self, path: &str, options: &ScanOptions, repos: &[RepoInfo]) {
+        if repos.is_empty() || repos.iter().any(|repo| repo.has_changes()) {
+            self.entries.lock().unwrap().remove(path);
+            return;
+        }
+        let Some(stamp) = Self::stamp(path, repos, options) else {
+            return;
+        };
+        let entry = Entry { stamp, saved: now(), repos: repos.to_vec() };
+        self.entries.lock().unwrap().insert(path.to_string(), entry);
+    }
+
+    fn expired(&self, entry: &Entry) -> bool {
+        now() - entry.saved > self.max_age.as_secs() as i64
+    }
+
+    /// What an entry depends on. None when the repo can't be cached, e.g. for linked worktrees
+    /// whose `.git` is a file, repos that have linked worktrees, or scans that talk to remotes.
+    fn stamp(path: &str, repos: &[RepoInfo], options: &ScanOptions) -> Option<Stamp> {
+        if options.fetch || options.verify_remote {
+            return None;
+        }
+        let git_dir = Path::new(path).join(".git");
+        // edits in linked worktrees touch nothing below the repo
+        if !git_dir.is_dir() || repos.iter().any(|repo| !repo.worktrees.is_empty()) {
+            return None;
+        }
+        // config holds the remotes and upstreams
+        let mut files: Vec<PathBuf> = ["index", "HEAD", "config", "packed-refs", "FETCH_HEAD", "refs/stash", "MERGE_HEAD",
+            "CHERRY_PICK_HEAD", "REVERT_HEAD", "BISECT_LOG", "rebase-merge", "rebase-apply", "refs/tags", "worktrees"].iter()
+            .map(|file| git_dir.join(file))
+            .collect();
+        if let Ok(head) = fs::read_to_string(git_dir.join("HEAD")) && let Some(branch) = head.trim().strip_prefix("ref: ") {
+            files.push(git_dir.join(branch));
+        }
+        if let Some(upstream) = repos.first().and_then(|repo| repo.upstream.as_ref()) {
+            files.push(git_dir.join("refs/remotes").join(&upstream.name));
+        }
+        // new untracked files and manifests in the root
+        files.push(PathBuf::from(path));
+        files.push(Path::new(path).join(".gitmodules"));
+
+        let mtimes = files.iter()
+            .map(|file| {
+                let mtime = file.metadata().and_then(|m| m.modified()).ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|since| since.as_nanos());
+                (file.strip_prefix(path).unwrap_or(file).to_string_lossy().to_string(), mtime)
+            })
+            .collect();
+        Some(Stamp { options: Self::options_key(path, options), mtimes })
+    }
+
+    /// The options that change what is read from a repo, filters are applied after the cache
+    fn options_key(path: &str, options: &ScanOptions) -> String {
+        let flags = [options.verbose, options.all_untracked, options.deps, options.stashes, options.size, options.ignored, options.mine];
+        let flags: String = flags.iter().map(|flag| if *flag { '1' } else { '0' }).collect();
+        let hygiene = options.hygiene.as_ref().map_or("-".to_string(), |patterns| patterns.join(","));
+        format!("{} {} {}", flags, options.subpaths_for(path).join(","), hygiene)
+    }
+}
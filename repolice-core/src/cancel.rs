@@ -0,0 +1,66 @@
+use std::io;
+use std::process::{Command, Output};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Stops a scan or a bulk operation early, shared by its clones.
+///
+/// Cancelling starts no new work: a scan reads no further repos and the git commands a repo is waiting on,
+/// like fetches, are killed. Whatever was read or done before stays valid.
+#[derive(Clone, Default, Debug)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// a child is cancelled along with its parent, but not the other way round
+    parent: Option<Box<CancellationToken>>,
+}
+
+impl CancellationToken {
+    /// A token that isn't cancelled yet
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancels the token and every clone of it, for good
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`CancellationToken::cancel`] was called on this token or a clone of it
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
+    }
+
+    /// A token that can be cancelled on its own and is cancelled whenever this one is
+    pub fn child(&self) -> CancellationToken {
+        CancellationToken { cancelled: Arc::default(), parent: Some(Box::new(self.clone())) }
+    }
+
+    /// The flag [`CancellationToken::cancel`] sets, for gix operations that stop once it is set.
+    /// It isn't set when only a parent is cancelled.
+    pub(crate) fn flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    /// Runs `command` to completion like `Command::output`, but kills it once the token is cancelled.
+    /// None when it was killed. Only for commands with little output, a full pipe isn't drained while waiting.
+    pub(crate) fn output(&self, command: &mut Command) -> Option<io::Result<Output>> {
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => return Some(Err(e)),
+        };
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => return Some(child.wait_with_output()),
+                Ok(None) if self.is_cancelled() => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(20)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Why a repo couldn't be read. Only that repo is left out of a scan, the others are read as usual.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoliceError {
+    /// the repo couldn't be opened, e.g. for missing permissions or a corrupt `.git`
+    Open {
+        /// path of the repo
+        path: String,
+        /// what git reported
+        reason: String,
+    },
+    /// the repo opened, but its status couldn't be read, e.g. for a corrupt index
+    Status {
+        /// path of the repo
+        path: String,
+        /// what git reported
+        reason: String,
+    },
+    /// reading the repo crashed
+    Panic {
+        /// path of the repo
+        path: String,
+        /// the panic message
+        reason: String,
+    },
+    /// reading the repo took longer than the scan's timeout, e.g. on a stale network mount
+    Timeout {
+        /// path of the repo
+        path: String,
+        /// the timeout in seconds
+        secs: u64,
+    },
+    /// a repo on another machine couldn't be read, or the machine couldn't be reached at all
+    Remote {
+        /// `host:path` of the repo, or of the scanned directory when the machine couldn't be reached
+        path: String,
+        /// what went wrong there, as reported by the other machine or ssh
+        reason: String,
+    },
+}
+
+impl RepoliceError {
+    /// Path of the repo that couldn't be read
+    pub fn path(&self) -> &str {
+        match self {
+            RepoliceError::Open { path, .. } | RepoliceError::Status { path, .. } | RepoliceError::Panic { path, .. }
+                | RepoliceError::Timeout { path, .. } | RepoliceError::Remote { path, .. } => path,
+        }
+    }
+
+    /// What went wrong, without the path
+    pub fn reason(&self) -> String {
+        match self {
+            RepoliceError::Open { reason, .. } => format!("cannot open: {}", reason),
+            RepoliceError::Status { reason, .. } => format!("cannot read status: {}", reason),
+            RepoliceError::Panic { reason, .. } => format!("crashed while reading: {}", reason),
+            RepoliceError::Timeout { secs, .. } => format!("timed out after {}s", secs),
+            RepoliceError::Remote { reason, .. } => reason.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RepoliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.reason())
+    }
+}
+
+impl std::error::Error for RepoliceError {}
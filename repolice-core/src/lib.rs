@@ -0,0 +1,57 @@
+//! Multi-repo git status, the scanning half of [repolice](https://github.com/daffyzk/repolice).
+//!
+//! Finds every git repository below a directory and reads its status natively through gix,
+//! without shelling out to `git`.
+//!
+//! ```no_run
+//! use repolice_core::{scan, ScanOptions};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn run() {
+//! let mut repos = scan("/home/me/git", ScanOptions::default());
+//! while let Some(repo) = repos.next().await {
+//!     match repo {
+//!         Ok(repo) => println!("{} [{}]: {} changes", repo.name, repo.branch, repo.total_changes()),
+//!         Err(e) => eprintln!("{}", e),
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`Reader::collect_repos`] does the same without an async runtime and returns the repos sorted.
+//!
+//! [`RepoInfo`] and everything it contains implement serde's `Serialize` and `Deserialize`. This is the model
+//! behind `repolice --format json`, so its output can be read back as a `Vec<RepoInfo>`.
+#![warn(missing_docs)]
+
+mod cache;
+mod cancel;
+mod error;
+mod progress;
+mod reader;
+mod snapshot;
+mod system;
+
+use std::path::PathBuf;
+use tokio_stream::Stream;
+
+pub use cache::Cache;
+pub use cancel::CancellationToken;
+pub use error::RepoliceError;
+pub use progress::{ScanProgress, SkippedDir};
+pub use snapshot::Snapshots;
+pub use system::{host_name, now};
+pub use reader::{Attention, Branch, CommitSummary, DepSync, ExtraRepo, FetchLimits, FileTracker, Hygiene, IgnoredUsage, Language, LatestTag, Level, Limits, NameStyle, Naming, Provider, Reader, ReflogEntry, relative_age, RemoteCheck, RepoFilter, RepoInfo, RepoState, SCAN_THREAD, ScanOptions, SortOrder, SubmoduleStatus, Thresholds, Totals, Upstream, WorktreeStatus};
+
+/// Scans every repo below `path`, yielding each one as soon as its status is read.
+///
+/// Repos arrive in no particular order, a repo that can't be read yields an error instead and the scan goes on.
+/// Must be called from within a tokio runtime.
+pub fn scan(path: impl Into<PathBuf>, options: ScanOptions) -> impl Stream<Item = Result<RepoInfo, RepoliceError>> {
+    Reader::stream_repos(vec![path.into()], options)
+}
+
+/// Like [`scan`], for every repo below any of `paths`. A repo below several of them is yielded once.
+pub fn scan_all(paths: impl IntoIterator<Item = impl Into<PathBuf>>, options: ScanOptions) -> impl Stream<Item = Result<RepoInfo, RepoliceError>> {
+    Reader::stream_repos(paths.into_iter().map(Into::into).collect(), options)
+}
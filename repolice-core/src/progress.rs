@@ -0,0 +1,86 @@
+use crate::error::RepoliceError;
+use crate::reader::RepoInfo;
+
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How far a scan got, shared by its clones so another thread can show it while the scan runs.
+///
+/// Counts repos, not entries: a repo with sub-paths counts once, and as dirty when any of them has changes.
+#[derive(Clone, Default, Debug)]
+pub struct ScanProgress {
+    found: Arc<AtomicUsize>,
+    read: Arc<AtomicUsize>,
+    dirty: Arc<AtomicUsize>,
+    failed: Arc<AtomicUsize>,
+    skipped: Arc<Mutex<Vec<SkippedDir>>>,
+}
+
+/// A directory the search for repos couldn't list, repos below it weren't found
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkippedDir {
+    /// the directory
+    pub path: String,
+    /// why it couldn't be listed, e.g. "permission denied"
+    pub reason: String,
+}
+
+impl ScanProgress {
+    /// Progress of a scan that hasn't started yet
+    pub fn new() -> ScanProgress {
+        ScanProgress::default()
+    }
+
+    /// Repos the scan is going to read, zero until they were all found
+    pub fn found(&self) -> usize {
+        self.found.load(Ordering::Relaxed)
+    }
+
+    /// Repos read so far, including the ones that couldn't be read
+    pub fn read(&self) -> usize {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// Repos read so far that have changes
+    pub fn dirty(&self) -> usize {
+        self.dirty.load(Ordering::Relaxed)
+    }
+
+    /// Repos so far that couldn't be read
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Directories the last search for repos had to skip, in the order it came across them
+    pub fn skipped(&self) -> Vec<SkippedDir> {
+        self.skipped.lock().unwrap().clone()
+    }
+
+    pub(crate) fn clear_skipped(&self) {
+        self.skipped.lock().unwrap().clear();
+    }
+
+    pub(crate) fn skip(&self, path: String, reason: String) {
+        let mut skipped = self.skipped.lock().unwrap();
+        if !skipped.iter().any(|dir| dir.path == path) {
+            skipped.push(SkippedDir { path, reason });
+        }
+    }
+
+    pub(crate) fn start(&self, found: usize) {
+        self.found.fetch_add(found, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record(&self, infos: &Result<Vec<RepoInfo>, RepoliceError>) {
+        match infos {
+            Ok(infos) if infos.iter().any(|info| info.has_changes()) => {
+                self.dirty.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(_) => {}
+            Err(_) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.read.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,2781 @@
+use std::any::Any;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Stdio, Command};
+use std::sync::{Arc, Condvar, Mutex, Once, PoisonError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use to_vec::ToVec;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use gix::bstr::{BStr, BString};
+use gix::commit::describe::SelectRef;
+use serde::{Deserialize, Serialize};
+use crate::cache::Cache;
+use crate::cancel::CancellationToken;
+use crate::error::RepoliceError;
+use crate::progress::ScanProgress;
+use crate::snapshot::{Snapshot, Snapshots};
+use gix::dir::entry::{Kind as DirKind, Status as DirStatus};
+use gix::dir::walk::EmissionMode;
+use gix::index::entry::{Mode, Stage};
+use gix::status::{index_worktree, UntrackedFiles};
+use gix::status::plumbing::index_as_worktree::{Change as IndexChange, EntryStatus};
+
+/// One category of changed files in a repo
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct FileTracker {
+    /// label of the category, "Modified" in verbose scans and "M" otherwise
+    pub status: String,
+    /// number of files in the category
+    pub amount: usize,
+    /// files whose change is in the index, fully or partly, i.e. ready to commit
+    pub staged: usize,
+    /// files with changes that are not added to the index yet
+    pub unstaged: usize,
+    /// the files themselves, only collected in verbose scans
+    pub files: Option<Vec<String>>,
+    /// the staged subset of `files`, only collected in verbose scans
+    pub staged_files: Option<Vec<String>>,
+    /// the unstaged subset of `files`, only collected in verbose scans
+    pub unstaged_files: Option<Vec<String>>,
+}
+
+impl FileTracker {
+    fn new(status: &str, files: Vec<String>, staged: Vec<String>, unstaged: Vec<String>, verbose: bool) -> Self {
+        Self {
+           status: status.to_string(), 
+           amount: files.len(),
+           staged: staged.len(),
+           unstaged: unstaged.len(),
+           files: verbose.then_some(files),
+           staged_files: verbose.then_some(staged),
+           unstaged_files: verbose.then_some(unstaged),
+        }
+    }
+
+    /// Whether `file` has staged changes, unstaged ones, or both.
+    /// Only answers for verbose scans, `None` otherwise.
+    pub fn staging(&self, file: &str) -> Option<(bool, bool)> {
+        let staged = self.staged_files.as_ref()?.iter().any(|f| f == file);
+        let unstaged = self.unstaged_files.as_ref()?.iter().any(|f| f == file);
+        Some((staged, unstaged))
+    }
+}
+
+/// The commit HEAD points at
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommitSummary {
+    /// abbreviated commit hash
+    pub short_id: String,
+    /// first line of the message
+    pub subject: String,
+    /// author name
+    pub author: String,
+    /// seconds since the unix epoch
+    pub time: i64,
+}
+
+impl CommitSummary {
+    /// Relative age like "5m ago" or "3d ago"
+    pub fn age(&self) -> String {
+        relative_age(self.time)
+    }
+}
+
+/// The most recent tag reachable from HEAD, like `git describe --tags` finds it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LatestTag {
+    /// tag name without `refs/tags/`
+    pub name: String,
+    /// commits on HEAD since the tag, the unreleased ones
+    pub commits_since: u32,
+    /// an annotated tag, as releases are usually tagged, rather than a lightweight one
+    pub annotated: bool,
+}
+
+impl LatestTag {
+    /// "v1.2.0" when HEAD is tagged, "v1.2.0+3" with 3 commits since
+    pub fn describe(&self) -> String {
+        match self.commits_since {
+            0 => self.name.clone(),
+            commits => format!("{}+{}", self.name, commits),
+        }
+    }
+}
+
+/// One movement of HEAD, as recorded in its reflog
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    /// abbreviated commit HEAD moved to
+    pub short_id: String,
+    /// what moved it, e.g. "checkout: moving from main to topic"
+    pub message: String,
+    /// seconds since the unix epoch
+    pub time: i64,
+}
+
+impl ReflogEntry {
+    /// Relative age like "5m ago" or "3d ago"
+    pub fn age(&self) -> String {
+        relative_age(self.time)
+    }
+}
+
+/// How long ago `time`, seconds since the unix epoch, was: "just now" or like "3d ago"
+pub fn relative_age(time: i64) -> String {
+    match secs_since(time) {
+        s if s < 60 => "just now".to_string(),
+        s => format!("{} ago", short_duration(s)),
+    }
+}
+
+/// Seconds from `time` until now, 0 for times in the future
+fn secs_since(time: i64) -> i64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(time);
+    (now - time).max(0)
+}
+
+/// Duration in its largest whole unit, like "45s", "5m" or "3d"
+fn short_duration(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 60 * 60 * 24 => format!("{}h", s / (60 * 60)),
+        s if s < 60 * 60 * 24 * 30 => format!("{}d", s / (60 * 60 * 24)),
+        s if s < 60 * 60 * 24 * 365 => format!("{}mo", s / (60 * 60 * 24 * 30)),
+        s => format!("{}y", s / (60 * 60 * 24 * 365)),
+    }
+}
+
+/// Project type detected from the manifest files in a repo's root
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Cargo.toml
+    Rust,
+    /// package.json
+    Node,
+    /// pyproject.toml
+    Python,
+    /// go.mod
+    Go,
+}
+
+impl Language {
+    const MANIFESTS: [(&'static str, Language); 4] = [
+        ("Cargo.toml", Language::Rust),
+        ("package.json", Language::Node),
+        ("pyproject.toml", Language::Python),
+        ("go.mod", Language::Go),
+    ];
+
+    /// Languages whose manifest sits in the root of `path`
+    pub fn detect(path: &str) -> Vec<Language> {
+        Self::MANIFESTS.iter()
+            .filter(|(manifest, _)| Path::new(path).join(manifest).is_file())
+            .map(|(_, language)| *language)
+            .collect()
+    }
+
+    /// Parses a language name or common alias like "rs" or "ts"
+    pub fn parse(name: &str) -> Option<Language> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" | "cargo" => Some(Language::Rust),
+            "node" | "js" | "javascript" | "typescript" | "ts" => Some(Language::Node),
+            "python" | "py" => Some(Language::Python),
+            "go" | "golang" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    /// Two letter abbreviation used in listings
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Language::Rust => "rs",
+            Language::Node => "js",
+            Language::Python => "py",
+            Language::Go => "go",
+        }
+    }
+
+    /// Full lowercase name, as accepted by [`Language::parse`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Node => "node",
+            Language::Python => "python",
+            Language::Go => "go",
+        }
+    }
+}
+
+/// Order of collected repos, parsed from `--sort`
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum SortOrder {
+    /// conflicted repos first, then those with changes on a protected branch, then the most changed ones,
+    /// clean repos last and alphabetically
+    #[default]
+    Changes,
+    /// alphabetically by name
+    Name,
+    /// alphabetically by branch, then by name
+    Branch,
+    /// most recent last commit first
+    Age,
+    /// most commits ahead of or behind the upstream first
+    Upstream,
+    /// repos whose oldest uncommitted change is the oldest first, clean repos last
+    Dirty,
+}
+
+impl SortOrder {
+    /// Every order, in the order `next` cycles through them
+    pub const ALL: [SortOrder; 6] = [SortOrder::Changes, SortOrder::Name, SortOrder::Branch, SortOrder::Age, SortOrder::Upstream, SortOrder::Dirty];
+
+    /// Parses an order's name, "ahead" and "behind" mean upstream
+    pub fn parse(name: &str) -> Result<SortOrder, String> {
+        match name.to_lowercase().as_str() {
+            "changes" => Ok(SortOrder::Changes),
+            "name" => Ok(SortOrder::Name),
+            "branch" => Ok(SortOrder::Branch),
+            "age" => Ok(SortOrder::Age),
+            "upstream" | "ahead" | "behind" => Ok(SortOrder::Upstream),
+            "dirty" | "stale" => Ok(SortOrder::Dirty),
+            _ => Err(format!("unknown sort order '{}', expected one of changes, name, branch, age, upstream, dirty", name)),
+        }
+    }
+
+    /// Name as accepted by `parse`
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Changes => "changes",
+            SortOrder::Name => "name",
+            SortOrder::Branch => "branch",
+            SortOrder::Age => "age",
+            SortOrder::Upstream => "upstream",
+            SortOrder::Dirty => "dirty",
+        }
+    }
+
+    /// The order after this one, wrapping around
+    pub fn next(&self) -> SortOrder {
+        let index = Self::ALL.iter().position(|order| order == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Sorts `repos` in this order after the pinned ones, ties are broken by name
+    pub fn sort(&self, repos: &mut [RepoInfo]) {
+        let divergence = |repo: &RepoInfo| repo.upstream.as_ref().map_or(0, |u| u.ahead + u.behind);
+        let time = |repo: &RepoInfo| repo.last_commit.as_ref().map(|c| c.time);
+        repos.sort_by(|a, b| {
+            let order = match self {
+                // conflicted repos always go first, then those with changes on a protected branch
+                SortOrder::Changes => b.has_conflicts().cmp(&a.has_conflicts())
+                    .then_with(|| b.edits_protected_branch().cmp(&a.edits_protected_branch()))
+                    .then_with(|| match (a.has_changes(), b.has_changes()) {
+                        (true, false) => std::cmp::Ordering::Less,                      // repos with changes come first
+                        (false, true) => std::cmp::Ordering::Greater,                   // clean repos come last
+                        (true, true) => b.total_changes().cmp(&a.total_changes()),      // sort by most changes first
+                        (false, false) => std::cmp::Ordering::Equal,                    // clean repos sorted alphabetically
+                    }),
+                SortOrder::Name => std::cmp::Ordering::Equal,
+                SortOrder::Branch => a.branch.cmp(&b.branch),
+                // unborn branches have no commit and go last
+                SortOrder::Age => time(b).cmp(&time(a)),
+                SortOrder::Upstream => divergence(b).cmp(&divergence(a)),
+                // dirty repos without a file left to date, like those that only deleted files, come after the dated ones
+                SortOrder::Dirty => b.has_changes().cmp(&a.has_changes())
+                    .then_with(|| a.dirty_since.is_none().cmp(&b.dirty_since.is_none()))
+                    .then_with(|| a.dirty_since.cmp(&b.dirty_since)),
+            };
+            b.pinned.cmp(&a.pinned).then(order).then_with(|| a.name.cmp(&b.name))
+        });
+    }
+}
+
+/// Narrows which repos a scan reports, parsed from `--filter KEY=VALUE`
+#[derive(Clone, Debug)]
+pub enum RepoFilter {
+    /// `lang=rust`, repos with the language's manifest in their root
+    Lang(Language),
+    /// `name=work/api`, the repo with this name as shown in listings, see [`Naming`]
+    Name(String),
+    /// `state=dirty`, repos with uncommitted changes
+    Dirty,
+    /// `state=clean`, repos without uncommitted changes
+    Clean,
+}
+
+impl RepoFilter {
+    /// Parses a `KEY=VALUE` filter, the error describes what was expected
+    pub fn parse(filter: &str) -> Result<RepoFilter, String> {
+        let (key, value) = filter.split_once('=').ok_or(format!("expected KEY=VALUE, got '{}'", filter))?;
+        match key {
+            "lang" => Language::parse(value)
+                .map(RepoFilter::Lang)
+                .ok_or(format!("unknown language '{}', expected one of rust, node, python, go", value)),
+            "name" => Ok(RepoFilter::Name(value.to_string())),
+            "state" => match value {
+                "dirty" => Ok(RepoFilter::Dirty),
+                "clean" => Ok(RepoFilter::Clean),
+                _ => Err(format!("unknown state '{}', expected dirty or clean", value)),
+            },
+            _ => Err(format!("unknown filter '{}', expected lang, name or state", key)),
+        }
+    }
+
+    /// Whether `repo` passes the filter
+    pub fn matches(&self, repo: &RepoInfo) -> bool {
+        match self {
+            RepoFilter::Lang(language) => repo.languages.contains(language),
+            RepoFilter::Name(name) => repo.name == *name,
+            RepoFilter::Dirty => repo.has_changes(),
+            RepoFilter::Clean => !repo.has_changes(),
+        }
+    }
+
+    /// Whether the filter goes by the repo's changes rather than by what the repo is
+    pub fn is_state(&self) -> bool {
+        matches!(self, RepoFilter::Dirty | RepoFilter::Clean)
+    }
+}
+
+/// Whether a Rust repo's Cargo.lock keeps up with its manifests
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepSync {
+    /// no manifest or lockfile changes, or only manifest changes in a repo without a lockfile
+    InSync,
+    /// Cargo.lock has uncommitted changes
+    LockDirty,
+    /// a Cargo.toml changed but Cargo.lock didn't follow
+    ManifestWithoutLock,
+}
+
+impl DepSync {
+    /// Libraries often don't commit a lockfile, `has_lock` keeps those from being flagged
+    fn from_changes(files: &[&String], has_lock: bool) -> DepSync {
+        // renames and copies are "old -> new", the destination counts
+        let changed = |name: &str| files.iter().any(|f| f.rsplit(" -> ").next().unwrap_or(f).trim_end_matches('"').ends_with(name));
+        match (changed("Cargo.toml"), changed("Cargo.lock")) {
+            (_, true) => DepSync::LockDirty,
+            (true, false) if has_lock => DepSync::ManifestWithoutLock,
+            _ => DepSync::InSync,
+        }
+    }
+
+    /// Short human readable description
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DepSync::InSync => "deps in sync",
+            DepSync::LockDirty => "Cargo.lock dirty",
+            DepSync::ManifestWithoutLock => "Cargo.toml changed without Cargo.lock",
+        }
+    }
+}
+
+/// The branch the checked out branch tracks, as of the last fetch
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Upstream {
+    /// short name of the remote-tracking branch, e.g. "origin/main"
+    pub name: String,
+    /// local commits the upstream doesn't have
+    pub ahead: usize,
+    /// upstream commits the local branch doesn't have
+    pub behind: usize,
+}
+
+/// A local branch of a repo and where it stands against the remotes
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Branch {
+    /// short name, e.g. "topic"
+    pub name: String,
+    /// whether the branch is checked out
+    pub current: bool,
+    /// none when the branch tracks nothing or its remote-tracking branch is gone
+    pub upstream: Option<Upstream>,
+    /// tracks a remote branch that no longer exists, like `[gone]` in `git branch -vv`. Usually deleted
+    /// after it was merged, so the local branch can go too.
+    pub gone: bool,
+    /// commits of the branch that no remote-tracking branch contains
+    pub unpushed: usize,
+    /// seconds since the unix epoch of the commit the branch points at
+    pub time: i64,
+}
+
+impl Branch {
+    /// Whether the branch has no counterpart on a remote, so its commits only live in this clone
+    pub fn is_local_only(&self) -> bool {
+        self.upstream.is_none()
+    }
+
+    /// Relative age of the branch's last commit, like "5m ago" or "3d ago"
+    pub fn age(&self) -> String {
+        relative_age(self.time)
+    }
+}
+
+/// Whether `origin` still answers, checked with `git ls-remote`
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteCheck {
+    /// the remote listed its HEAD
+    Reachable,
+    /// the remote is gone, renamed or refused us, with git's reason
+    Unreachable(String),
+}
+
+/// Whether the repo is on a branch as usual, or stuck in the middle of something
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoState {
+    /// a branch is checked out and nothing is in progress
+    Normal,
+    /// HEAD points at a commit instead of a branch
+    Detached,
+    /// a merge stopped, usually on conflicts
+    Merging,
+    /// a rebase stopped, interactive or not
+    Rebasing,
+    /// a cherry-pick stopped
+    CherryPicking,
+    /// a revert stopped
+    Reverting,
+    /// a bisect hasn't been reset yet
+    Bisecting,
+    /// `git am` stopped
+    ApplyingMailbox,
+}
+
+impl RepoState {
+    fn detect(repo: &gix::Repository, detached: bool) -> RepoState {
+        use gix::state::InProgress;
+        match repo.state() {
+            Some(InProgress::Merge) => RepoState::Merging,
+            Some(InProgress::Rebase | InProgress::RebaseInteractive | InProgress::ApplyMailboxRebase) => RepoState::Rebasing,
+            Some(InProgress::CherryPick | InProgress::CherryPickSequence) => RepoState::CherryPicking,
+            Some(InProgress::Revert | InProgress::RevertSequence) => RepoState::Reverting,
+            Some(InProgress::Bisect) => RepoState::Bisecting,
+            Some(InProgress::ApplyMailbox) => RepoState::ApplyingMailbox,
+            None if detached => RepoState::Detached,
+            None => RepoState::Normal,
+        }
+    }
+
+    /// Whether an operation waits to be continued or aborted, a detached HEAD alone doesn't count
+    pub fn in_progress(&self) -> bool {
+        !matches!(self, RepoState::Normal | RepoState::Detached)
+    }
+
+    /// Warning shown next to the branch, empty for the normal state
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RepoState::Normal => "",
+            RepoState::Detached => "detached HEAD",
+            RepoState::Merging => "merge in progress",
+            RepoState::Rebasing => "rebase in progress",
+            RepoState::CherryPicking => "cherry-pick in progress",
+            RepoState::Reverting => "revert in progress",
+            RepoState::Bisecting => "bisect in progress",
+            RepoState::ApplyingMailbox => "am in progress",
+        }
+    }
+}
+
+/// Where a repo's `origin` is hosted, guessed from its URL
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// github.com or a GitHub Enterprise host with "github" in its name
+    GitHub,
+    /// gitlab.com or a self-hosted GitLab with "gitlab" in its name
+    GitLab,
+    /// bitbucket.org or a Bitbucket server with "bitbucket" in its name
+    Bitbucket,
+    /// any other host, or a local path
+    Other,
+}
+
+impl Provider {
+    /// Guesses the provider from the host of a remote URL, scp-like `git@host:path` URLs included
+    pub fn from_url(url: &str) -> Provider {
+        let host = Self::host(url).unwrap_or_default();
+        if host.contains("github") {
+            Provider::GitHub
+        } else if host.contains("gitlab") {
+            Provider::GitLab
+        } else if host.contains("bitbucket") {
+            Provider::Bitbucket
+        } else {
+            Provider::Other
+        }
+    }
+
+    /// Lowercase host of a remote URL, scp-like `git@host:path` URLs included. None for local paths.
+    pub fn host(url: &str) -> Option<String> {
+        let (scheme, rest) = match url.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme), rest),
+            // scp-like syntax needs a colon before the first slash, `C:\` and `C:/` are drive letters
+            None if Path::new(url).has_root() || url.get(1..3).is_some_and(|s| s == ":\\" || s == ":/") => return None,
+            None if url.find(':').is_some_and(|colon| url.find('/').is_none_or(|slash| colon < slash)) => (None, url),
+            None => return None,
+        };
+        if scheme == Some("file") {
+            return None;
+        }
+        let authority = rest.split(['/', ':']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default().to_lowercase();
+        (!host.is_empty()).then_some(host)
+    }
+
+    /// User or group owning the repo behind a remote URL, the path segment before the repo's name.
+    /// For nested groups that is the innermost one. None for local paths.
+    pub fn owner(url: &str) -> Option<String> {
+        Self::host(url)?;
+        let path = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?.1,
+            None => url.split_once(':')?.1,
+        };
+        let mut segments = path.trim_end_matches('/').rsplit('/');
+        segments.next()?;
+        segments.next().filter(|owner| !owner.is_empty()).map(String::from)
+    }
+
+    /// Short tag used in listings
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Provider::GitHub => "gh",
+            Provider::GitLab => "gl",
+            Provider::Bitbucket => "bb",
+            Provider::Other => "git",
+        }
+    }
+}
+
+/// Status of a single repo, or of a registered sub-path of one
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RepoInfo {
+    /// directory name, "repo/sub-path" for sub-path entries
+    pub name: String,
+    /// path of the repo as found by the scan
+    pub path: String,
+    /// work tree of the repo the entry belongs to, `path` itself except for sub-path entries
+    #[serde(default)]
+    pub root: String,
+    /// checked out branch, the branch being rebased during a rebase, "HEAD" when detached
+    pub branch: String,
+    /// `branch` matches one of [`ScanOptions::protected_branches`], where changes should go through a pull request
+    #[serde(default)]
+    pub protected_branch: bool,
+    /// merge, rebase or similar in progress, or a detached HEAD
+    pub repo_state: RepoState,
+    /// unmerged paths
+    pub conflicted_files: FileTracker,
+    /// untracked files and directories
+    pub new_files: FileTracker,
+    /// files staged for the first time
+    pub added_files: FileTracker,
+    /// modified files, see `staged` and `unstaged` for how far they are added
+    pub modified_files: FileTracker,
+    /// files that became a symlink or a submodule, or stopped being one
+    #[serde(default)]
+    pub typechanged_files: FileTracker,
+    /// files removed from the index, like with `git rm`
+    pub deleted_files: FileTracker,
+    /// tracked files gone from the work tree whose deletion isn't staged
+    #[serde(default)]
+    pub missing_files: FileTracker,
+    /// staged renames as "old -> new"
+    pub renamed_files: FileTracker,
+    /// staged copies as "old -> new", only with `status.renames` or `diff.renames` set to "copies" like git.
+    /// The copied file has to be changed too, as git doesn't look at unchanged files for copies.
+    pub copied_files: FileTracker,
+    /// modification time of the least recently changed file that still exists, in seconds since the unix epoch.
+    /// None for clean repos and those that only deleted files.
+    #[serde(default)]
+    pub dirty_since: Option<i64>,
+    /// the commit HEAD points at, none for unborn branches
+    pub last_commit: Option<CommitSummary>,
+    /// most recent tag reachable from HEAD, none when there is none
+    #[serde(default)]
+    pub latest_tag: Option<LatestTag>,
+    /// project types detected from the repo's root
+    pub languages: Vec<Language>,
+    /// only computed for Rust repos when dependency checks are enabled
+    pub dep_sync: Option<DepSync>,
+    /// fetch URL of `origin`, none when the repo has no such remote
+    pub remote_url: Option<String>,
+    /// no remote is configured at all, so nothing of the repo exists anywhere else
+    #[serde(default)]
+    pub local_only: bool,
+    /// cloned with `--depth` or the like, so history stops somewhere and counts of commits ahead and behind may be off
+    #[serde(default)]
+    pub shallow: bool,
+    /// cloned with `--filter`, objects left out are fetched from the promisor remote when needed
+    #[serde(default)]
+    pub partial: bool,
+    /// hosting provider derived from `remote_url`
+    pub provider: Option<Provider>,
+    /// result of contacting `origin`, only checked when remote checks are enabled and the repo has an origin
+    pub remote_check: Option<RemoteCheck>,
+    /// tracking branch of the checked out branch, none when detached or not tracking anything
+    pub upstream: Option<Upstream>,
+    /// `origin/HEAD`, the default branch of origin, and how far the checked out branch is from it. None on the
+    /// default branch itself, when detached or when origin/HEAD isn't known, see `git remote set-head origin --auto`.
+    #[serde(default)]
+    pub default_branch: Option<Upstream>,
+    /// number of stash entries, only counted when stash checks are enabled
+    pub stashes: Option<usize>,
+    /// bytes used by the `.git` directory, only measured when size checks are enabled
+    pub size: Option<u64>,
+    /// ignored files in the work tree, like build output, only measured when disk usage checks are enabled
+    #[serde(default)]
+    pub ignored: Option<IgnoredUsage>,
+    /// files the repo should probably ignore and doesn't, only checked when hygiene checks are enabled
+    #[serde(default)]
+    pub hygiene: Option<Hygiene>,
+    /// submodules registered in `.gitmodules`, empty for sub-path entries
+    #[serde(default)]
+    pub submodules: Vec<SubmoduleStatus>,
+    /// linked worktrees added with `git worktree add`, the main checkout is the repo itself. Empty for sub-path entries.
+    #[serde(default)]
+    pub worktrees: Vec<WorktreeStatus>,
+    /// work tree of the repo this one sits inside of without being registered there as a submodule, which is
+    /// usually an accident. None for top-level repos and submodules.
+    #[serde(default)]
+    pub nested_in: Option<String>,
+    /// repos inside the work tree that aren't submodules, relative to it. They are left out of `new_files`
+    /// since the scan lists them as repos of their own.
+    #[serde(default)]
+    pub nested_repos: Vec<String>,
+    /// local branches tracking a remote branch that no longer exists, see [`Branch::gone`]
+    #[serde(default)]
+    pub gone_branches: Vec<String>,
+    /// milliseconds reading the repo took, fetching and remote checks included. Sub-path entries share their repo's.
+    #[serde(default)]
+    pub read_millis: u64,
+    /// the configured [`ScanOptions::thresholds`] the repo goes over, none when it stays within them
+    #[serde(default)]
+    pub attention: Option<Attention>,
+    /// one of [`ScanOptions::pinned`], listed first and never filtered out
+    #[serde(default)]
+    pub pinned: bool,
+    /// whose work the repo holds, only checked with [`ScanOptions::mine`]: true when an unpushed commit is by
+    /// `user.email` or a changed file is owned by the user running the scan, false when all of it is someone else's.
+    /// None when there is nothing to go by, e.g. a clean repo or only deleted files.
+    #[serde(default)]
+    pub mine: Option<bool>,
+    /// machine the repo is on when it was read there rather than here, none for local repos.
+    /// Its `path` and `root` are then `host:path`.
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+/// Disk space taken by a repo's ignored files, e.g. `target/` or `node_modules/`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IgnoredUsage {
+    /// bytes of all ignored files together
+    pub total: u64,
+    /// the biggest ignored paths relative to the repo with their bytes, largest first.
+    /// Directories that are ignored as a whole end in `/`.
+    pub largest: Vec<(String, u64)>,
+}
+
+impl IgnoredUsage {
+    /// How many of the biggest ignored paths are kept
+    const LARGEST: usize = 5;
+}
+
+/// Ignore rules that don't do what they should
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hygiene {
+    /// tracked files that match the repo's ignore rules, `core.excludesFile` included.
+    /// Ignoring only applies to untracked files, these stay tracked until `git rm --cached`.
+    pub tracked_ignored: Vec<String>,
+    /// untracked files that no rule ignores but match one of the patterns of [`ScanOptions::hygiene`], e.g. `*.log` or `.env`
+    pub unignored: Vec<String>,
+}
+
+impl Hygiene {
+    /// Number of files flagged
+    pub fn warnings(&self) -> usize {
+        self.tracked_ignored.len() + self.unignored.len()
+    }
+
+    /// e.g. "2 tracked but ignored, 1 not ignored"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.tracked_ignored.is_empty() {
+            parts.push(format!("{} tracked but ignored", self.tracked_ignored.len()));
+        }
+        if !self.unignored.is_empty() {
+            parts.push(format!("{} not ignored", self.unignored.len()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Limits past which a repo needs attention, parsed from the config. Unset limits never trip.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Limits {
+    /// changed files, like [`RepoInfo::total_changes`]
+    pub changes: Option<usize>,
+    /// commits the upstream has that the branch doesn't
+    pub behind: Option<usize>,
+    /// commits the branch has that the upstream doesn't
+    pub ahead: Option<usize>,
+    /// days since the oldest uncommitted change, see [`RepoInfo::dirty_since`]
+    pub dirty_days: Option<usize>,
+}
+
+impl Limits {
+    /// What `repo` goes over, like "45 changes", empty when it stays within every limit
+    fn exceeded(&self, repo: &RepoInfo) -> Vec<String> {
+        let upstream = repo.upstream.as_ref();
+        let dirty_days = repo.dirty_since.map(|time| secs_since(time) as usize / 86400);
+        let counts = [
+            (self.changes, Some(repo.total_changes()), "change", "changes"),
+            (self.behind, upstream.map(|u| u.behind), "behind", "behind"),
+            (self.ahead, upstream.map(|u| u.ahead), "ahead", "ahead"),
+            (self.dirty_days, dirty_days, "day dirty", "days dirty"),
+        ];
+        counts.into_iter()
+            .filter_map(|(limit, count, one, many)| Some((limit?, count?, one, many)))
+            .filter(|(limit, count, _, _)| count > limit)
+            .map(|(_, count, one, many)| format!("{} {}", count, if count == 1 { one } else { many }))
+            .collect()
+    }
+}
+
+/// What "needs attention" means, a repo going over a limit is flagged at that level
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Thresholds {
+    /// worth a look
+    pub warn: Limits,
+    /// needs dealing with
+    pub critical: Limits,
+}
+
+impl Thresholds {
+    /// The highest level `repo` reaches and which of its limits it goes over, none when it stays within all of them
+    pub fn check(&self, repo: &RepoInfo) -> Option<Attention> {
+        [(Level::Critical, &self.critical), (Level::Warn, &self.warn)].into_iter()
+            .map(|(level, limits)| Attention { level, exceeded: limits.exceeded(repo) })
+            .find(|attention| !attention.exceeded.is_empty())
+    }
+
+    /// Sets one limit from `level.limit=count`, like `warn.changes=20` or `critical.dirty_days=30`
+    pub fn set(&mut self, setting: &str) -> Result<(), String> {
+        let invalid = || format!("expected LEVEL.LIMIT=N like 'warn.changes=20', got '{}'", setting);
+        let (key, count) = setting.split_once('=').ok_or_else(invalid)?;
+        let (level, limit) = key.split_once('.').ok_or_else(invalid)?;
+        let limits = match level {
+            "warn" => &mut self.warn,
+            "critical" => &mut self.critical,
+            _ => return Err(format!("unknown level '{}', expected warn or critical", level)),
+        };
+        let limit = match limit {
+            "changes" => &mut limits.changes,
+            "behind" => &mut limits.behind,
+            "ahead" => &mut limits.ahead,
+            "dirty_days" => &mut limits.dirty_days,
+            _ => return Err(format!("unknown limit '{}', expected changes, behind, ahead or dirty_days", limit)),
+        };
+        *limit = Some(count.parse().map_err(|_| invalid())?);
+        Ok(())
+    }
+
+    /// Every limit that is set, in the form [`Thresholds::set`] takes
+    pub fn settings(&self) -> Vec<String> {
+        [("warn", &self.warn), ("critical", &self.critical)].into_iter()
+            .flat_map(|(level, limits)| {
+                [("changes", limits.changes), ("behind", limits.behind), ("ahead", limits.ahead), ("dirty_days", limits.dirty_days)]
+                    .into_iter()
+                    .filter_map(move |(limit, count)| Some(format!("{}.{}={}", level, limit, count?)))
+            })
+            .collect()
+    }
+}
+
+/// How urgent a repo that went over its thresholds is
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// over a `warn` limit
+    Warn,
+    /// over a `critical` limit
+    Critical,
+}
+
+/// A repo over some of the configured [`Thresholds`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Attention {
+    /// the highest level reached
+    pub level: Level,
+    /// what the repo goes over at that level, like "45 changes" or "60 behind"
+    pub exceeded: Vec<String>,
+}
+
+impl Attention {
+    /// e.g. "critical: 120 changes, 60 behind"
+    pub fn describe(&self) -> String {
+        let level = match self.level {
+            Level::Warn => "warn",
+            Level::Critical => "critical",
+        };
+        format!("{}: {}", level, self.exceeded.join(", "))
+    }
+}
+
+/// A submodule of a repo and how it differs from what the repo recorded for it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SubmoduleStatus {
+    /// path relative to the repo
+    pub path: String,
+    /// never cloned or checked out, `git submodule update --init` was not run
+    pub uninitialized: bool,
+    /// checked out at another commit than the one the repo's index records
+    pub out_of_sync: bool,
+    /// has changes or untracked files of its own
+    pub dirty: bool,
+}
+
+impl SubmoduleStatus {
+    /// Whether the submodule holds work the repo's own status doesn't show
+    pub fn needs_attention(&self) -> bool {
+        self.out_of_sync || self.dirty
+    }
+
+    /// Short state like "out of sync, dirty", "clean" when nothing differs
+    pub fn describe(&self) -> String {
+        if self.uninitialized {
+            return "not initialized".to_string();
+        }
+        match (self.out_of_sync, self.dirty) {
+            (true, true) => "out of sync, dirty",
+            (true, false) => "out of sync",
+            (false, true) => "dirty",
+            (false, false) => "clean",
+        }.to_string()
+    }
+}
+
+/// A linked worktree of a repo, checked out elsewhere and read along with the repo
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WorktreeStatus {
+    /// where it is checked out
+    pub path: String,
+    /// checked out branch, "HEAD" when detached
+    pub branch: String,
+    /// changed files, like [`RepoInfo::total_changes`]
+    pub changes: usize,
+    /// its directory is gone, `git worktree prune` would remove it
+    pub missing: bool,
+}
+
+impl WorktreeStatus {
+    /// Whether the worktree holds uncommitted work
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0
+    }
+
+    /// Short state like "feature, 3 changes", "missing" when its directory is gone
+    pub fn describe(&self) -> String {
+        match (self.missing, self.changes) {
+            (true, _) => format!("{}, missing", self.branch),
+            (false, 0) => format!("{}, clean", self.branch),
+            (false, 1) => format!("{}, 1 change", self.branch),
+            (false, changes) => format!("{}, {} changes", self.branch, changes),
+        }
+    }
+}
+
+impl RepoInfo {
+    /// Whether the entry is a registered sub-path of a repo rather than the repo itself
+    pub fn is_subpath(&self) -> bool {
+        self.path != self.root
+    }
+
+    /// Whether anything at all differs from HEAD
+    pub fn has_changes(&self) -> bool {
+        self.total_changes() > 0
+    }
+
+    /// How long the oldest uncommitted change has been sitting, like "12d"
+    pub fn dirty_for(&self) -> Option<String> {
+        self.dirty_since.map(|time| short_duration(secs_since(time)))
+    }
+
+    /// Whether someone is editing a protected branch directly
+    pub fn edits_protected_branch(&self) -> bool {
+        self.protected_branch && self.has_changes()
+    }
+
+    /// Whether a merge, rebase or similar left unmerged paths
+    pub fn has_conflicts(&self) -> bool {
+        self.conflicted_files.amount > 0
+    }
+
+    /// Whether the local branch has commits the upstream doesn't
+    pub fn is_ahead(&self) -> bool {
+        self.upstream.as_ref().is_some_and(|u| u.ahead > 0)
+    }
+
+    /// Whether the upstream has commits the local branch doesn't
+    pub fn is_behind(&self) -> bool {
+        self.upstream.as_ref().is_some_and(|u| u.behind > 0)
+    }
+
+    /// Number of changed files over all categories
+    pub fn total_changes(&self) -> usize {
+        self.trackers().iter().map(|t| t.amount).sum()
+    }
+
+    /// Number of files with changes in the index, i.e. what a commit would contain
+    pub fn staged_changes(&self) -> usize {
+        self.trackers().iter().map(|t| t.staged).sum()
+    }
+
+    /// Number of files with changes that are not added yet, untracked and conflicted files included
+    pub fn unstaged_changes(&self) -> usize {
+        self.trackers().iter().map(|t| t.unstaged).sum()
+    }
+
+    /// Number of submodules that are dirty or checked out at another commit than recorded
+    pub fn submodules_dirty(&self) -> usize {
+        self.submodules.iter().filter(|s| s.needs_attention()).count()
+    }
+
+    /// Number of linked worktrees with uncommitted work
+    pub fn worktrees_dirty(&self) -> usize {
+        self.worktrees.iter().filter(|w| w.is_dirty()).count()
+    }
+
+    /// All file trackers, in display priority order
+    pub fn trackers(&self) -> [&FileTracker; 9] {
+        [
+            &self.conflicted_files,
+            &self.new_files,
+            &self.added_files,
+            &self.modified_files,
+            &self.typechanged_files,
+            &self.deleted_files,
+            &self.missing_files,
+            &self.renamed_files,
+            &self.copied_files,
+        ]
+    }
+}
+
+/// Counts over a set of repos, for summaries of a whole scan
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    /// repos read
+    pub repos: usize,
+    /// repos with uncommitted changes
+    pub dirty: usize,
+    /// repos without uncommitted changes
+    pub clean: usize,
+    /// repos with unmerged paths
+    pub conflicted: usize,
+    /// changed files over all repos
+    pub files_changed: usize,
+    /// repos with commits their upstream doesn't have
+    pub ahead: usize,
+    /// repos missing commits from their upstream
+    pub behind: usize,
+}
+
+impl Totals {
+    /// Adds up `repos`
+    pub fn of<'a>(repos: impl IntoIterator<Item = &'a RepoInfo>) -> Totals {
+        let mut totals = Totals::default();
+        for repo in repos {
+            totals.repos += 1;
+            if repo.has_changes() {
+                totals.dirty += 1;
+            } else {
+                totals.clean += 1;
+            }
+            if repo.has_conflicts() {
+                totals.conflicted += 1;
+            }
+            totals.files_changed += repo.total_changes();
+            if repo.upstream.as_ref().is_some_and(|u| u.ahead > 0) {
+                totals.ahead += 1;
+            }
+            if repo.is_behind() {
+                totals.behind += 1;
+            }
+        }
+        totals
+    }
+
+    /// One line like "12 repos: 5 dirty, 7 clean, 1 conflicted, 23 files changed, 2 ahead, 1 behind",
+    /// counts that are zero are left out except for dirty and clean
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("{} dirty", self.dirty), format!("{} clean", self.clean)];
+        if self.conflicted > 0 {
+            parts.push(format!("{} conflicted", self.conflicted));
+        }
+        if self.files_changed > 0 {
+            parts.push(format!("{} files changed", self.files_changed));
+        }
+        if self.ahead > 0 {
+            parts.push(format!("{} ahead", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("{} behind", self.behind));
+        }
+        format!("{} repos: {}", self.repos, parts.join(", "))
+    }
+}
+
+/// Limits for network operations against the same host, so a bulk fetch doesn't trip rate limits
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// fetches running against one host at a time, 0 for no limit
+    pub per_host: usize,
+    /// minimum time between the starts of two fetches against one host
+    pub stagger: Duration,
+}
+
+/// Name of the threads repos are read on. A panic while reading a repo is caught and reported as
+/// [`RepoliceError::Panic`], so a panic hook can leave panics on these threads out of its output.
+pub const SCAN_THREAD: &str = "repolice-scan";
+
+/// How often waits for a host check whether their scan was cancelled
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// Network operations running per host, shared by all scans of the process
+static HOSTS: HostGate = HostGate { hosts: Mutex::new(BTreeMap::new()), changed: Condvar::new() };
+
+struct HostGate {
+    hosts: Mutex<BTreeMap<String, HostUse>>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct HostUse {
+    running: usize,
+    last_start: Option<Instant>,
+}
+
+impl HostGate {
+    /// Blocks until `host` has a free slot and the stagger since the last start has passed.
+    /// False when `cancel` was cancelled meanwhile, the slot isn't taken then.
+    fn enter(&self, host: &str, limits: &FetchLimits, cancel: &CancellationToken) -> bool {
+        let mut hosts = self.hosts.lock().unwrap();
+        loop {
+            if cancel.is_cancelled() {
+                return false;
+            }
+            let host_use = hosts.entry(host.to_string()).or_default();
+            let full = limits.per_host > 0 && host_use.running >= limits.per_host;
+            let wait = host_use.last_start
+                .map_or(Duration::ZERO, |start| (start + limits.stagger).saturating_duration_since(Instant::now()));
+            if !full && wait.is_zero() {
+                host_use.running += 1;
+                host_use.last_start = Some(Instant::now());
+                return true;
+            }
+            // woken up now and then to notice cancellation
+            let wait = if full { CANCEL_POLL } else { wait.min(CANCEL_POLL) };
+            hosts = self.changed.wait_timeout(hosts, wait).unwrap().0;
+        }
+    }
+
+    fn leave(&self, host: &str) {
+        // also runs while a panic unwinds, which must not panic again
+        if let Some(host_use) = self.hosts.lock().unwrap_or_else(PoisonError::into_inner).get_mut(host) {
+            host_use.running -= 1;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Runs `operation` within the limits of the host behind `url`. Local remotes are never limited.
+    /// None when `cancel` was cancelled while waiting for the host.
+    fn limited<T>(&self, url: Option<&str>, limits: &FetchLimits, cancel: &CancellationToken, operation: impl FnOnce() -> T) -> Option<T> {
+        let host = url.and_then(Provider::host);
+        let Some(host) = host.filter(|_| *limits != FetchLimits::default()) else {
+            return Some(operation());
+        };
+        if !self.enter(&host, limits, cancel) {
+            return None;
+        }
+        // a panicking operation is caught further up, its slot must not stay taken for the rest of the scan
+        let _slot = HostSlot { gate: self, host: &host };
+        Some(operation())
+    }
+}
+
+/// Cancels the reads that run over their timeout, one thread for all scans of the process
+static WATCHDOG: Watchdog = Watchdog { reads: Mutex::new(BTreeMap::new()), next_id: AtomicU64::new(0), started: Once::new(), changed: Condvar::new() };
+
+struct Watchdog {
+    /// deadline of each read being watched and the token stopping it, by watch id
+    reads: Mutex<BTreeMap<u64, (Instant, CancellationToken)>>,
+    next_id: AtomicU64,
+    started: Once,
+    changed: Condvar,
+}
+
+impl Watchdog {
+    /// Cancels `cancel` at `deadline` unless the returned guard is dropped before.
+    /// Nothing is ever cancelled when the watchdog thread can't be started.
+    fn watch(&'static self, deadline: Instant, cancel: &CancellationToken) -> Watched {
+        self.started.call_once(|| {
+            let _ = thread::Builder::new().name("repolice-watchdog".to_string()).spawn(|| self.run());
+        });
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.reads.lock().unwrap().insert(id, (deadline, cancel.clone()));
+        self.changed.notify_all();
+        Watched { watchdog: self, id }
+    }
+
+    fn run(&self) {
+        let mut reads = self.reads.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let now = Instant::now();
+            reads.retain(|_, (deadline, cancel)| {
+                if *deadline <= now {
+                    cancel.cancel();
+                }
+                *deadline > now
+            });
+            let next = reads.values().map(|(deadline, _)| *deadline).min();
+            reads = match next {
+                Some(next) => self.changed.wait_timeout(reads, next - now).unwrap_or_else(PoisonError::into_inner).0,
+                None => self.changed.wait(reads).unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+/// A read watched with [`Watchdog::watch`], no longer watched once dropped
+struct Watched {
+    watchdog: &'static Watchdog,
+    id: u64,
+}
+
+impl Drop for Watched {
+    fn drop(&mut self) {
+        // also runs while a panic unwinds
+        self.watchdog.reads.lock().unwrap_or_else(PoisonError::into_inner).remove(&self.id);
+    }
+}
+
+/// A slot taken with [`HostGate::enter`], given back when dropped
+struct HostSlot<'a> {
+    gate: &'a HostGate,
+    host: &'a str,
+}
+
+impl Drop for HostSlot<'_> {
+    fn drop(&mut self) {
+        self.gate.leave(self.host);
+    }
+}
+
+/// How repos that share a directory name are told apart
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum NameStyle {
+    /// `parent/name`, with the name of the directory the repo is in
+    #[default]
+    Parent,
+    /// `owner/name`, with the user or group owning origin, `parent/name` for repos without one
+    Owner,
+    /// the repo's full path
+    Path,
+    /// the aliases given in [`Naming::aliases`], `parent/name` for repos without one
+    Alias,
+}
+
+impl NameStyle {
+    /// Parses a style's name, "org" means owner
+    pub fn parse(name: &str) -> Result<NameStyle, String> {
+        match name.to_lowercase().as_str() {
+            "parent" => Ok(NameStyle::Parent),
+            "owner" | "org" => Ok(NameStyle::Owner),
+            "path" => Ok(NameStyle::Path),
+            "alias" => Ok(NameStyle::Alias),
+            _ => Err(format!("unknown name style '{}', expected one of parent, owner, path, alias", name)),
+        }
+    }
+}
+
+/// Names the repos of a scan. Repos keep their directory name unless another repo of the scan has the same,
+/// so every name shown or matched against points at one repo.
+#[derive(Clone, Default)]
+pub struct Naming {
+    /// how repos sharing a directory name are told apart
+    pub style: NameStyle,
+    /// names of repos keyed by their canonical path, used with [`NameStyle::Alias`] whether the name is shared or not
+    pub aliases: Vec<(PathBuf, String)>,
+    /// names given by the last scan keyed by path, so re-reading a single repo keeps its name.
+    /// Shared by clones of the options.
+    names: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl Naming {
+    /// Naming with `style`, `aliases` are keyed by canonical path
+    pub fn new(style: NameStyle, aliases: Vec<(PathBuf, String)>) -> Naming {
+        Naming { style, aliases, names: Arc::default() }
+    }
+
+    /// Names every repo in `repo_list`, telling apart the ones with the same directory name
+    fn assign(&self, repo_list: &[String]) {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for path in repo_list {
+            *counts.entry(Reader::repo_name(path)).or_default() += 1;
+        }
+        let mut names = self.names.lock().unwrap();
+        for path in repo_list {
+            let shared = counts.get(Reader::repo_name(path)).is_some_and(|count| *count > 1);
+            names.insert(path.clone(), self.pick(path, shared));
+        }
+    }
+
+    /// Name of the repo at `path`, its directory name when it wasn't part of a scan yet
+    fn name(&self, path: &str) -> String {
+        self.names.lock().unwrap().get(path).cloned().unwrap_or_else(|| Reader::repo_name(path).to_string())
+    }
+
+    fn pick(&self, path: &str, shared: bool) -> String {
+        let name = Reader::repo_name(path);
+        if self.style == NameStyle::Alias && let Some(alias) = self.alias(path) {
+            return alias;
+        }
+        if !shared {
+            return name.to_string();
+        }
+        let parent = || Path::new(path).parent().and_then(Path::file_name).map(|parent| parent.to_string_lossy().to_string());
+        let prefix = match self.style {
+            NameStyle::Path => return path.to_string(),
+            NameStyle::Owner => gix::open(path).ok()
+                .and_then(|repo| Reader::find_remote_url(&repo))
+                .and_then(|url| Provider::owner(&url))
+                .or_else(parent),
+            NameStyle::Parent | NameStyle::Alias => parent(),
+        };
+        match prefix {
+            Some(prefix) => format!("{}/{}", prefix, name),
+            None => name.to_string(),
+        }
+    }
+
+    fn alias(&self, path: &str) -> Option<String> {
+        if self.aliases.is_empty() {
+            return None;
+        }
+        let canonical = Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path));
+        self.aliases.iter().find(|(alias_path, _)| *alias_path == canonical).map(|(_, alias)| alias.clone())
+    }
+}
+
+/// A repo whose git directory lives apart from its work tree, like dotfiles managed with
+/// `git --git-dir=$HOME/.dotfiles --work-tree=$HOME`. Scans can't find these on their own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraRepo {
+    /// the repo's git directory
+    pub git_dir: PathBuf,
+    /// the directory checked out from it, which is also the path the repo is reported by
+    pub work_tree: PathBuf,
+}
+
+impl ExtraRepo {
+    /// Parses `gitdir:worktree`. Either path may start with a Windows drive, like `C:\dot\.git:C:\Users\me`.
+    pub fn parse(spec: &str) -> Result<ExtraRepo, String> {
+        let is_drive = |letters: &str| letters.len() == 1 && letters.bytes().all(|letter| letter.is_ascii_alphabetic());
+        let split = spec.rsplit_once(':').and_then(|(rest, path)| {
+            if !path.starts_with(['\\', '/']) {
+                return Some((rest, path));
+            }
+            // the colon of the work tree's drive, the paths are split at the one before it
+            match rest.rsplit_once(':') {
+                Some((git_dir, drive)) if is_drive(drive) => Some((git_dir, &spec[git_dir.len() + 1..])),
+                None if is_drive(rest) => None,
+                _ => Some((rest, path)),
+            }
+        });
+        match split {
+            Some((git_dir, work_tree)) if !git_dir.is_empty() && !work_tree.is_empty() => Ok(ExtraRepo {
+                git_dir: PathBuf::from(git_dir),
+                work_tree: PathBuf::from(work_tree),
+            }),
+            _ => Err(format!("invalid extra repo '{}', expected gitdir:worktree", spec)),
+        }
+    }
+}
+
+/// Options shared by every repo of a scan
+#[derive(Clone, Default)]
+pub struct ScanOptions {
+    /// keep the changed file names, not only their counts
+    pub verbose: bool,
+    /// sub-paths of big repos that are reported as their own entries, keyed by the repo's canonical path
+    pub subpaths: Vec<(PathBuf, Vec<String>)>,
+    /// repos with a separate git directory, read on top of the repos found below the scanned directories
+    pub extra_repos: Vec<ExtraRepo>,
+    /// globs of repo paths to leave out of the scan
+    pub exclude: Vec<String>,
+    /// how many directory levels below the scanned directory are searched for repos, none for all of them
+    pub max_depth: Option<usize>,
+    /// search symlinked directories for repos too, each directory only once however many links lead to it
+    pub follow_symlinks: bool,
+    /// files with gitignore-style patterns of directories that aren't searched for repos, on top of
+    /// the `.repoliceignore` in the scanned directory. Patterns are relative to the scanned directory.
+    pub ignore_files: Vec<PathBuf>,
+    /// only repos matching all of these are reported
+    pub filters: Vec<RepoFilter>,
+    /// check whether Cargo.lock follows Cargo.toml changes
+    pub deps: bool,
+    /// number of repos read at the same time, 0 uses one worker per CPU
+    pub jobs: usize,
+    /// run `git fetch` before reading a repo
+    pub fetch: bool,
+    /// how hard fetches and remote checks may hit a single host
+    pub fetch_limits: FetchLimits,
+    /// count stash entries
+    pub stashes: bool,
+    /// measure the size of the `.git` directory
+    pub size: bool,
+    /// measure the size of ignored files in the work tree
+    pub ignored: bool,
+    /// look for tracked files that are ignored and for untracked files matching these globs that aren't, none skips the check
+    pub hygiene: Option<Vec<String>>,
+    /// check that `origin` still exists and lets us in
+    pub verify_remote: bool,
+    /// globs of branch names that shouldn't be worked on directly, like `main` or `release/*`
+    pub protected_branches: Vec<String>,
+    /// limits past which repos are flagged as needing attention
+    pub thresholds: Thresholds,
+    /// order of the repos returned by [`Reader::collect_repos`]
+    pub sort: SortOrder,
+    /// canonical paths of repos the user pinned, they come first whatever the sort order and pass every filter
+    pub pinned: Vec<PathBuf>,
+    /// list every file inside untracked directories instead of only the directory, like `git status -uall`
+    pub all_untracked: bool,
+    /// tell whose work each repo holds and leave out repos with only someone else's, see [`RepoInfo::mine`].
+    /// Changed files only count when `verbose` keeps their names.
+    pub mine: bool,
+    /// reuse the status of clean repos from earlier scans, shared by clones of the options
+    pub cache: Option<Arc<Cache>>,
+    /// how repos are named, telling apart repos with the same directory name
+    pub naming: Naming,
+    /// stops the scan: no further repos are read and running fetches and remote checks are killed
+    pub cancel: CancellationToken,
+    /// how long reading one repo may take, fetching included, before it is stopped and reported as
+    /// [`RepoliceError::Timeout`]. None waits forever.
+    pub timeout: Option<Duration>,
+    /// counts found, read and dirty repos while the scan runs, shared by clones of the options
+    pub progress: ScanProgress,
+    /// what HEAD and the index differed by at earlier reads of each repo, shared by clones of the options
+    pub snapshots: Snapshots,
+}
+
+impl ScanOptions {
+    fn jobs(&self) -> usize {
+        match self.jobs {
+            0 => thread::available_parallelism().map_or(4, |n| n.get()),
+            jobs => jobs,
+        }
+    }
+
+    pub(crate) fn subpaths_for(&self, repo_path: &str) -> &[String] {
+        let canonical = Path::new(repo_path).canonicalize().unwrap_or_else(|_| PathBuf::from(repo_path));
+        self.subpaths.iter()
+            .find(|(path, _)| *path == canonical)
+            .map(|(_, subpaths)| subpaths.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Whether the repo at `path` is one of [`ScanOptions::pinned`]
+    pub(crate) fn is_pinned(&self, path: &str) -> bool {
+        !self.pinned.is_empty() && self.pinned.contains(&Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path)))
+    }
+
+    /// The registered extra repo whose work tree is `path`
+    pub(crate) fn extra_repo_for(&self, path: &Path) -> Option<&ExtraRepo> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.extra_repos.iter()
+            .find(|extra| extra.work_tree.canonicalize().unwrap_or_else(|_| extra.work_tree.clone()) == canonical)
+    }
+}
+
+/// Changed paths of a repo, bucketed by category before they become FileTrackers
+#[derive(Default)]
+struct Changes {
+    conflicted: Vec<String>,
+    new: Vec<String>,
+    added: Vec<String>,
+    modified: Vec<String>,
+    typechanged: Vec<String>,
+    deleted: Vec<String>,
+    missing: Vec<String>,
+    renamed: Vec<String>,
+    copied: Vec<String>,
+    /// entries of the lists above with a change in the index
+    staged: HashSet<String>,
+    /// entries of the lists above with a change in the worktree
+    unstaged: HashSet<String>,
+    /// untracked directories that are repos of their own, kept apart from `new` as the scan lists them by themselves
+    nested: Vec<String>,
+}
+
+impl Changes {
+    /// Computes the equivalent of `git status --porcelain` natively with gix.
+    /// The first status column compares HEAD with the index, the second one the index with the worktree.
+    /// The work tree walk stops once `cancel` is cancelled itself, a half-read status is then returned.
+    fn read_status(repo: &gix::Repository, all_untracked: bool, snapshots: Option<&Snapshots>, cancel: &CancellationToken) -> Result<Changes, String> {
+        let index = repo.index_or_empty().map_err(|e| e.to_string())?;
+        let unborn = repo.head_tree_id().is_err();
+        let staged = Self::read_staged(repo, &index, snapshots)?;
+        let conflicted: HashSet<String> = staged.conflicted.into_iter().collect();
+        let mut codes: BTreeMap<String, [char; 2]> = staged.staged.into_iter().map(|(path, code)| (path, [code, ' '])).collect();
+
+        let mut untracked_paths = Vec::new();
+        let mut nested = Vec::new();
+        let hide_untracked = Self::hides_untracked(repo);
+        if unborn {
+            // gix can't read the worktree status of an unborn HEAD without a `.gitmodules`, a plain walk still finds
+            // the untracked files. Staged files that were edited again show as staged only until the first commit.
+            if !hide_untracked {
+                (untracked_paths, nested) = Self::walk_untracked(repo, index.clone(), all_untracked)?;
+            }
+        } else {
+            let untracked = match (hide_untracked, all_untracked) {
+                (true, _) => UntrackedFiles::None,
+                (false, true) => UntrackedFiles::Files,
+                (false, false) => UntrackedFiles::Collapsed,
+            };
+            let worktree = repo.status(gix::progress::Discard).map_err(|e| e.to_string())?
+                .should_interrupt_owned(cancel.flag())
+                .untracked_files(untracked)
+                .into_index_worktree_iter(Vec::new()).map_err(|e| e.to_string())?;
+            for item in worktree.flatten() {
+                match item {
+                    index_worktree::iter::Item::Modification { rela_path, status, .. } => {
+                        let unstaged = match status {
+                            EntryStatus::Change(IndexChange::Removed) => 'D',
+                            EntryStatus::Change(IndexChange::Type) => 'T',
+                            EntryStatus::Change(_) => 'M',
+                            EntryStatus::IntentToAdd => 'A',
+                            EntryStatus::Conflict(_) | EntryStatus::NeedsUpdate(_) => continue,
+                        };
+                        let path = rela_path.to_string();
+                        if conflicted.contains(&path) {
+                            continue;
+                        }
+                        // paths without a staged change aren't listed yet
+                        let code = codes.entry(path).or_insert([' ', ' ']);
+                        // `git add -N` puts an empty entry in the index, which isn't staged yet
+                        *code = if unstaged == 'A' { [' ', 'A'] } else { [code[0], unstaged] };
+                    }
+                    index_worktree::iter::Item::DirectoryContents { entry, .. } if entry.status == DirStatus::Untracked => {
+                        Self::sort_untracked(&entry, &mut untracked_paths, &mut nested);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut changes = Changes { nested, ..Changes::default() };
+        for (staged, pairs) in [('R', &staged.renames), ('C', &staged.copies)] {
+            for (from, to) in pairs {
+                let unstaged = codes.remove(to).map_or(' ', |code| code[1]);
+                changes.push(&[staged, unstaged], format!("{} -> {}", from, to));
+            }
+        }
+        for path in &conflicted {
+            changes.push(&['U', 'U'], path.clone());
+        }
+        for (path, code) in codes {
+            changes.push(&code, path);
+        }
+        // the walk takes files with only the other side of a conflict in the index for untracked ones
+        for path in untracked_paths.into_iter().filter(|path| !conflicted.contains(path)) {
+            changes.push(&['?', '?'], path);
+        }
+        Ok(changes)
+    }
+
+    /// The HEAD to index half of the status, taken from `snapshots` while HEAD and the index are unchanged
+    fn read_staged(repo: &gix::Repository, index: &gix::index::File, snapshots: Option<&Snapshots>) -> Result<Snapshot, String> {
+        let head_tree = repo.head_tree_id().ok();
+        let key = head_tree.zip(index.checksum()).map(|(tree, checksum)| (tree.to_string(), checksum.to_string()));
+        if let (Some(snapshots), Some((head, checksum))) = (snapshots, &key)
+            && let Some(snapshot) = snapshots.get(repo.git_dir(), head, checksum) {
+            return Ok(snapshot);
+        }
+
+        // an unborn HEAD has no tree, so everything in the index counts as added
+        let head_index = match head_tree {
+            Some(tree) => Some(repo.index_from_tree(&tree).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        let head_entry = |path: &BStr| head_index.as_ref().and_then(|head| head.entry_by_path(path));
+
+        let mut codes: BTreeMap<String, char> = BTreeMap::new();
+        let mut conflicted: HashSet<String> = HashSet::new();
+        for entry in index.entries() {
+            let path = entry.path(index);
+            if entry.stage() != Stage::Unconflicted {
+                conflicted.insert(path.to_string());
+                continue;
+            }
+            let staged = match head_entry(path) {
+                None => 'A',
+                Some(head) if !same_kind(head.mode, entry.mode) => 'T',
+                Some(head) if head.id != entry.id || head.mode != entry.mode => 'M',
+                Some(_) => continue,
+            };
+            codes.insert(path.to_string(), staged);
+        }
+        if let Some(head) = &head_index {
+            for entry in head.entries() {
+                let path = entry.path(head);
+                if index.entry_by_path(path).is_none() && !conflicted.contains(&path.to_string()) {
+                    codes.insert(path.to_string(), 'D');
+                }
+            }
+        }
+
+        // staged deletions and additions of the same blob are exact renames, git also pairs similar content
+        // but exact matches cover `git mv`
+        let mut renames: Vec<(String, String)> = Vec::new();
+        for (deleted_path, _) in codes.iter().filter(|(_, code)| **code == 'D') {
+            let deleted_id = head_entry(deleted_path.as_str().into()).map(|e| e.id);
+            let destination = codes.iter()
+                .filter(|(_, code)| **code == 'A')
+                .find(|(path, _)| {
+                    let added_id = index.entry_by_path(path.as_str().into()).map(|e| e.id);
+                    added_id.is_some() && added_id == deleted_id && !renames.iter().any(|(_, to)| to == *path)
+                });
+            if let Some((added_path, _)) = destination {
+                renames.push((deleted_path.clone(), added_path.clone()));
+            }
+        }
+        // like renames, only exact copies are found. git looks for them with `status.renames` or `diff.renames`
+        // set to "copies", and only copies of files that changed too
+        let mut copies: Vec<(String, String)> = Vec::new();
+        if Self::detects_copies(repo) {
+            let sources: Vec<(&String, gix::ObjectId)> = codes.iter()
+                .filter(|(_, code)| matches!(code, 'M' | 'T' | 'D'))
+                .filter_map(|(path, _)| Some((path, head_entry(path.as_str().into())?.id)))
+                .collect();
+            for (added_path, _) in codes.iter().filter(|(_, code)| **code == 'A') {
+                if renames.iter().any(|(_, to)| to == added_path) {
+                    continue;
+                }
+                let added_id = index.entry_by_path(added_path.as_str().into()).map(|e| e.id);
+                if let Some((source, _)) = sources.iter().find(|(_, id)| Some(*id) == added_id) {
+                    copies.push(((*source).clone(), added_path.clone()));
+                }
+            }
+        }
+        for (from, to) in &renames {
+            codes.remove(from);
+            codes.remove(to);
+        }
+        for (_, to) in &copies {
+            codes.remove(to);
+        }
+
+        let (head, checksum) = key.clone().unwrap_or_default();
+        let mut snapshot = Snapshot::new(head, checksum);
+        snapshot.staged = codes.into_iter().collect();
+        snapshot.conflicted = conflicted.into_iter().collect();
+        snapshot.conflicted.sort();
+        snapshot.renames = renames;
+        snapshot.copies = copies;
+        if let (Some(snapshots), Some(_)) = (snapshots, key) {
+            snapshots.put(repo.git_dir(), snapshot.clone());
+        }
+        Ok(snapshot)
+    }
+
+    /// Whether the repo's config leaves untracked files out of `git status`, usual for dotfiles checked out to the home directory
+    fn hides_untracked(repo: &gix::Repository) -> bool {
+        repo.config_snapshot().string("status.showUntrackedFiles").is_some_and(|mode| mode.as_ref() == "no")
+    }
+
+    /// Whether the work tree of the clean repo at `path` still matches its index, checked before its cached status is reused.
+    /// Edits in place and untracked files in subdirectories leave `.git` alone. Tracked files are compared by their
+    /// stat data like `git status` does, untracked repos other than `nested_repos` count as changes too.
+    fn worktree_unchanged(path: &str, nested_repos: &[String]) -> bool {
+        let Ok(repo) = gix::open(path) else {
+            return false;
+        };
+        let untracked = if Self::hides_untracked(&repo) { UntrackedFiles::None } else { UntrackedFiles::Collapsed };
+        let Ok(status) = repo.status(gix::progress::Discard) else {
+            return false;
+        };
+        let Ok(mut items) = status.untracked_files(untracked).into_index_worktree_iter(Vec::new()) else {
+            return false;
+        };
+        !items.any(|item| match item {
+            // only the stat data differs, the content is the same
+            Ok(index_worktree::iter::Item::Modification { status: EntryStatus::NeedsUpdate(_), .. }) => false,
+            Ok(index_worktree::iter::Item::DirectoryContents { entry, .. }) => entry.status == DirStatus::Untracked
+                && !(entry.disk_kind == Some(DirKind::Repository) && nested_repos.iter().any(|nested| *nested == entry.rela_path)),
+            Ok(_) | Err(_) => true,
+        })
+    }
+
+    /// Whether the repo's config asks `git status` for copies, `status.renames` falls back to `diff.renames`
+    fn detects_copies(repo: &gix::Repository) -> bool {
+        let config = repo.config_snapshot();
+        let renames = config.string("status.renames").or_else(|| config.string("diff.renames"));
+        renames.is_some_and(|mode| mode.as_ref() == "copies" || mode.as_ref() == "copy")
+    }
+
+    /// Untracked files and directories found by walking the worktree, in the format of [`Changes::read_status`],
+    /// followed by the untracked repos
+    fn walk_untracked(repo: &gix::Repository, index: gix::worktree::Index, all_untracked: bool) -> Result<(Vec<String>, Vec<String>), String> {
+        let mode = if all_untracked { EmissionMode::Matching } else { EmissionMode::CollapseDirectory };
+        let options = repo.dirwalk_options().map_err(|e| e.to_string())?.emit_untracked(mode);
+        let walk = repo.dirwalk_iter(index, Vec::<BString>::new(), Default::default(), options).map_err(|e| e.to_string())?;
+        let (mut paths, mut nested) = (Vec::new(), Vec::new());
+        for item in walk.flatten() {
+            if item.entry.status == DirStatus::Untracked {
+                Self::sort_untracked(&item.entry, &mut paths, &mut nested);
+            }
+        }
+        Ok((paths, nested))
+    }
+
+    /// Adds an untracked entry to `nested` when it is a repo, otherwise to `paths`, directories ending in `/`
+    fn sort_untracked(entry: &gix::dir::Entry, paths: &mut Vec<String>, nested: &mut Vec<String>) {
+        let mut path = entry.rela_path.to_string();
+        match entry.disk_kind {
+            Some(DirKind::Repository) => nested.push(path),
+            Some(DirKind::Directory) => {
+                path.push('/');
+                paths.push(path);
+            }
+            _ => paths.push(path),
+        }
+    }
+
+    /// Number of changed paths over all categories
+    fn count(&self) -> usize {
+        [&self.conflicted, &self.new, &self.added, &self.modified, &self.typechanged, &self.deleted, &self.missing, &self.renamed, &self.copied].iter()
+            .map(|paths| paths.len())
+            .sum()
+    }
+
+    fn push(&mut self, status_code: &[char; 2], file_path: String) {
+        let conflicted = matches!(status_code, ['D', 'D'] | ['A', 'U'] | ['U', 'D'] | ['U', 'A'] | ['D', 'U'] | ['A', 'A'] | ['U', 'U']);
+        if !conflicted && !matches!(status_code[0], ' ' | '?') {
+            self.staged.insert(file_path.clone());
+        }
+        if status_code[1] != ' ' {
+            self.unstaged.insert(file_path.clone());
+        }
+        // a staged change decides the category, whatever happened to the file in the work tree since
+        match status_code {
+            // unmerged paths, see the "Short Format" section of git-status(1)
+            ['D', 'D'] | ['A', 'U'] | ['U', 'D'] | ['U', 'A'] | ['D', 'U'] | ['A', 'A'] | ['U', 'U'] => self.conflicted.push(file_path),
+            ['?', '?'] => self.new.push(file_path),
+            ['A', _] | [' ', 'A'] => self.added.push(file_path),
+            ['M', _] | [' ', 'M'] => self.modified.push(file_path),
+            ['T', _] | [' ', 'T'] => self.typechanged.push(file_path),
+            ['D', _] => self.deleted.push(file_path),
+            [' ', 'D'] => self.missing.push(file_path),
+            // renames and copies are reported as "old -> new"
+            ['R', _] => self.renamed.push(file_path),
+            ['C', _] => self.copied.push(file_path),
+            _ => {}
+        }
+    }
+
+    /// Moves every change below `prefix` into a new set of changes
+    fn split_off(&mut self, prefix: &str) -> Changes {
+        let prefix = format!("{}/", prefix.trim_end_matches('/'));
+        // for renames and copies the destination decides where the change belongs
+        let belongs = |file: &String| file.rsplit(" -> ").next().unwrap_or(file).starts_with(&prefix);
+        let take = |files: &mut Vec<String>| {
+            let (inside, outside): (Vec<String>, Vec<String>) = files.drain(..).partition(|f| belongs(f));
+            *files = outside;
+            inside
+        };
+        Changes {
+            conflicted: take(&mut self.conflicted),
+            new: take(&mut self.new),
+            added: take(&mut self.added),
+            modified: take(&mut self.modified),
+            typechanged: take(&mut self.typechanged),
+            deleted: take(&mut self.deleted),
+            missing: take(&mut self.missing),
+            renamed: take(&mut self.renamed),
+            copied: take(&mut self.copied),
+            // paths are unique, so both halves can keep looking them up
+            staged: self.staged.clone(),
+            unstaged: self.unstaged.clone(),
+            // nested repos stay with the repo itself
+            nested: Vec::new(),
+        }
+    }
+
+    /// Modification time of the least recently changed file that is still in the work tree at `root`
+    fn oldest_change(&self, root: &str) -> Option<i64> {
+        [&self.conflicted, &self.new, &self.added, &self.modified, &self.typechanged, &self.renamed, &self.copied].into_iter()
+            .flatten()
+            .map(|file| file.rsplit(" -> ").next().unwrap_or(file))
+            .filter_map(|file| Path::new(root).join(file).symlink_metadata().and_then(|m| m.modified()).ok())
+            .min()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_secs() as i64)
+    }
+
+    fn into_repo_info(self, name: &str, path: &str, root: &str, branch: &str, options: &ScanOptions) -> RepoInfo {
+        let dirty_since = self.oldest_change(root);
+        let languages = Language::detect(path);
+        let dep_sync = if options.deps && languages.contains(&Language::Rust) {
+            let files: Vec<&String> = self.added.iter().chain(&self.modified).chain(&self.typechanged).chain(&self.renamed).chain(&self.copied).chain(&self.new).collect();
+            Some(DepSync::from_changes(&files, Path::new(path).join("Cargo.lock").exists()))
+        } else {
+            None
+        };
+
+        let tracker = |verbose_status: &str, short_status: &str, files: Vec<String>| {
+            let staged = files.iter().filter(|f| self.staged.contains(*f)).cloned().collect();
+            let unstaged = files.iter().filter(|f| self.unstaged.contains(*f)).cloned().collect();
+            let status = if options.verbose { verbose_status } else { short_status };
+            FileTracker::new(status, files, staged, unstaged, options.verbose)
+        };
+        RepoInfo {
+            name: name.to_string(),
+            path: path.to_string(),
+            root: root.to_string(),
+            branch: branch.to_string(),
+            protected_branch: false,
+            repo_state: RepoState::Normal,
+            conflicted_files: tracker("Conflicted", "U", self.conflicted),
+            new_files: tracker("New", "??", self.new),
+            added_files: tracker("Added", "A", self.added),
+            modified_files: tracker("Modified", "M", self.modified),
+            typechanged_files: tracker("Type changed", "T", self.typechanged),
+            deleted_files: tracker("Deleted", "D", self.deleted),
+            missing_files: tracker("Missing", "X", self.missing),
+            renamed_files: tracker("Renamed", "R", self.renamed),
+            copied_files: tracker("Copied", "C", self.copied),
+            dirty_since,
+            last_commit: None,
+            latest_tag: None,
+            languages,
+            dep_sync,
+            remote_url: None,
+            local_only: false,
+            shallow: false,
+            partial: false,
+            provider: None,
+            remote_check: None,
+            upstream: None,
+            default_branch: None,
+            stashes: None,
+            size: None,
+            ignored: None,
+            hygiene: None,
+            submodules: Vec::new(),
+            worktrees: Vec::new(),
+            nested_in: None,
+            nested_repos: self.nested,
+            gone_branches: Vec::new(),
+            read_millis: 0,
+            attention: None,
+            pinned: false,
+            mine: None,
+            host: None,
+        }
+    }
+}
+
+/// One search for repos below a scanned directory
+struct Walk {
+    ignore: Gitignore,
+    follow_symlinks: bool,
+    /// canonical paths of the directories searched so far, only kept when following symlinks.
+    /// A link back to one of them would otherwise go in circles or find its repos twice.
+    visited: HashSet<PathBuf>,
+    /// symlinked directories waiting to be searched, with the depth left below them
+    links: VecDeque<(PathBuf, Option<usize>)>,
+    /// globs of repo paths that aren't collected
+    exclude: GlobSet,
+    repos: Vec<PathBuf>,
+    /// stop at the first repo, when only whether there are any matters
+    first_only: bool,
+    /// stop searching at this time, directories left are recorded as skipped
+    deadline: Option<Instant>,
+    /// where directories that can't be listed are recorded
+    progress: ScanProgress,
+}
+
+impl Walk {
+    fn new(ignore: Gitignore, exclude: GlobSet, follow_symlinks: bool, progress: ScanProgress) -> Walk {
+        Walk { ignore, follow_symlinks, visited: HashSet::new(), links: VecDeque::new(), exclude, repos: Vec::new(), first_only: false, deadline: None, progress }
+    }
+
+    /// Searches `root` at most `depth` levels deep. Symlinked directories come last,
+    /// so a repo that can also be reached without them keeps its real path.
+    fn run(&mut self, root: &Path, depth: Option<usize>) {
+        self.enter(root, depth);
+        while let Some((link, depth)) = self.links.pop_front() {
+            if self.done(&link) {
+                break;
+            }
+            self.enter(&link, depth);
+        }
+        for (link, _) in std::mem::take(&mut self.links) {
+            self.done(&link);
+        }
+    }
+
+    /// Whether there is no point in searching further, recording `dir` as skipped when time ran out before it was searched through
+    fn done(&self, dir: &Path) -> bool {
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.progress.skip(dir.to_string_lossy().to_string(), "not searched in time".to_string());
+            return true;
+        }
+        self.first_only && !self.repos.is_empty()
+    }
+
+    fn enter(&mut self, dir: &Path, depth: Option<usize>) {
+        if self.follow_symlinks && !self.visited.insert(dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())) {
+            return;
+        }
+        self.find_git_dirs(dir, depth);
+    }
+
+    /// Walks `dir` for directories containing a `.git` directory. Ignored directories are skipped, symlinks too
+    /// unless they are followed. Unreadable ones are skipped and recorded, unless they vanished in the meantime.
+    fn find_git_dirs(&mut self, dir: &Path, depth: Option<usize>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                self.progress.skip(dir.to_string_lossy().to_string(), e.kind().to_string());
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            if self.done(dir) {
+                return;
+            }
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            let is_dir = if kind.is_symlink() { self.follow_symlinks && path.is_dir() } else { kind.is_dir() };
+            if !is_dir {
+                continue;
+            }
+            if entry.file_name() == ".git" {
+                if !self.exclude.is_match(dir) {
+                    self.repos.push(dir.to_path_buf());
+                }
+            } else if depth != Some(0) && !self.ignore.matched(&path, true).is_ignore() {
+                let depth = depth.map(|depth| depth - 1);
+                if kind.is_symlink() {
+                    self.links.push_back((path, depth));
+                } else {
+                    // repos nested inside other repos are reported too
+                    self.enter(&path, depth);
+                }
+            }
+        }
+    }
+}
+
+/// Finds repos and reads their status
+pub struct Reader {}
+
+impl Reader {
+    /// Paths of all repos below `path`, minus the excluded ones. Directories matching the patterns
+    /// of `path/.repoliceignore` or [`ScanOptions::ignore_files`] aren't searched at all.
+    pub fn get_repos(path: PathBuf, options: &ScanOptions) -> Vec<String> {
+        Self::get_repos_before(&path, options, None)
+    }
+
+    fn get_repos_before(path: &Path, options: &ScanOptions, deadline: Option<Instant>) -> Vec<String> {
+        let mut walk = Self::walk(path, options);
+        walk.deadline = deadline;
+        walk.run(path, options.max_depth);
+        walk.repos.into_iter().map(|repo| repo.to_string_lossy().to_string()).to_vec()
+    }
+
+    /// Whether there is a repo below any of `roots` or in [`ScanOptions::extra_repos`], like
+    /// [`Reader::get_repos_in`] would find, but without searching further than the first one
+    pub fn has_repos_in(roots: &[PathBuf], options: &ScanOptions) -> bool {
+        !options.extra_repos.is_empty() || roots.iter().any(|root| {
+            let mut walk = Self::walk(root, options);
+            walk.first_only = true;
+            walk.run(root, options.max_depth);
+            !walk.repos.is_empty()
+        })
+    }
+
+    fn walk(root: &Path, options: &ScanOptions) -> Walk {
+        Walk::new(Self::build_ignore(root, &options.ignore_files), Self::build_globs(&options.exclude), options.follow_symlinks, options.progress.clone())
+    }
+
+    /// Paths of all repos below any of `roots`, see [`Reader::get_repos`], followed by the work trees of
+    /// [`ScanOptions::extra_repos`]. A repo below several of them, e.g. for nested roots or a root reached
+    /// through a symlink, is only listed for the first. Directories that can't be listed end up in
+    /// [`ScanProgress::skipped`].
+    pub fn get_repos_in(roots: &[PathBuf], options: &ScanOptions) -> Vec<String> {
+        Self::get_repos_in_until(roots, options, None)
+    }
+
+    /// Like [`Reader::get_repos_in`], but stops searching at `deadline` with the repos found by then.
+    /// The directories it didn't get through end up in [`ScanProgress::skipped`].
+    pub fn get_repos_in_until(roots: &[PathBuf], options: &ScanOptions, deadline: Option<Instant>) -> Vec<String> {
+        options.progress.clear_skipped();
+        let mut seen = HashSet::new();
+        let extra = options.extra_repos.iter().map(|extra| extra.work_tree.to_string_lossy().to_string());
+        roots.iter()
+            .flat_map(|root| Self::get_repos_before(root, options, deadline))
+            .chain(extra)
+            .filter(|repo| seen.insert(Path::new(repo).canonicalize().unwrap_or_else(|_| PathBuf::from(repo))))
+            .to_vec()
+    }
+
+    /// Last component of a repo's path, the whole path when it has none (like `C:\`).
+    /// Both `/` and `\` separate components, so Windows paths are named the same on every platform.
+    pub fn repo_name(path: &str) -> &str {
+        let trimmed = path.trim_end_matches(['/', '\\']);
+        match trimmed.rsplit(['/', '\\']).next() {
+            // the root itself, or a drive like `C:`
+            Some("") | None => path,
+            Some(name) if name.len() == trimmed.len() && name.ends_with(':') => path,
+            Some(name) => name,
+        }
+    }
+
+    /// Patterns of `root/.repoliceignore` and `files`, relative to `root`. Missing files are fine.
+    fn build_ignore(root: &Path, files: &[PathBuf]) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(root);
+        for file in files.iter().cloned().chain([root.join(".repoliceignore")]) {
+            if !file.is_file() {
+                continue;
+            }
+            if let Some(e) = builder.add(&file) {
+                eprintln!("Ignoring invalid patterns in {}: {}", file.display(), e);
+            }
+        }
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    }
+
+    fn build_globs(patterns: &[String]) -> GlobSet {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            match Glob::new(pattern) {
+                Ok(glob) => { builder.add(glob); }
+                Err(e) => eprintln!("Ignoring invalid glob {}: {}", pattern, e),
+            }
+        }
+        builder.build().unwrap_or_else(|_| GlobSet::empty())
+    }
+
+    /// Creates a stream of RepoInfo as repositories below any of `roots`, or of the error when a repo can't be read.
+    /// Processes repos on a pool of `options.jobs` workers and sends results as they are found
+    pub fn stream_repos(roots: Vec<PathBuf>, options: ScanOptions) -> impl Stream<Item = Result<RepoInfo, RepoliceError>> {
+        let (tx, rx) = mpsc::channel(100);
+
+        tokio::task::spawn_blocking(move || {
+            let repo_paths = Self::get_repos_in(&roots, &options);
+            Self::scan_pool(repo_paths, &options, None, |_, infos| {
+                let results: Vec<Result<RepoInfo, RepoliceError>> = match infos {
+                    Ok(infos) => infos.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                for result in results {
+                    // nobody is listening anymore, don't keep reading repos for nothing
+                    if tx.blocking_send(result).is_err() {
+                        options.cancel.cancel();
+                    }
+                }
+            });
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    /// Collects info for all repos inside a dir tree, followed by the repos that couldn't be read
+    pub fn collect_repos(repo_list: Vec<String>, options: &ScanOptions) -> (Vec<RepoInfo>, Vec<RepoliceError>) {
+        let repos = Mutex::new(Vec::new());
+        let errors = Mutex::new(Vec::new());
+        Self::scan_pool(repo_list, options, None, |_, infos| match infos {
+            Ok(infos) => repos.lock().unwrap().extend(infos),
+            Err(e) => errors.lock().unwrap().push(e),
+        });
+        let mut repos = repos.into_inner().unwrap();
+        options.sort.sort(&mut repos);
+        let mut errors = errors.into_inner().unwrap();
+        errors.sort_by(|a, b| a.path().cmp(b.path()));
+        (repos, errors)
+    }
+
+    /// Like [`Reader::collect_repos`], but returns at `deadline` with whatever was read by then,
+    /// followed by the paths of the repos that weren't. Repos still being read finish in the background.
+    pub fn collect_repos_until(repo_list: Vec<String>, options: &ScanOptions, deadline: Instant) -> (Vec<RepoInfo>, Vec<RepoliceError>, Vec<String>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let pending = repo_list.clone();
+        let pool_options = options.clone();
+        // not joined, a slow repo must not hold up the caller
+        thread::spawn(move || {
+            Self::scan_pool(repo_list, &pool_options, Some(deadline), |path, infos| {
+                let _ = tx.send((path.to_string(), infos));
+            });
+        });
+
+        let mut repos = Vec::new();
+        let mut errors = Vec::new();
+        let mut done = HashSet::new();
+        while let Ok((path, infos)) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            done.insert(path);
+            match infos {
+                Ok(infos) => repos.extend(infos),
+                Err(e) => errors.push(e),
+            }
+        }
+        options.sort.sort(&mut repos);
+        errors.sort_by(|a: &RepoliceError, b| a.path().cmp(b.path()));
+        let unscanned = pending.into_iter().filter(|path| !done.contains(path)).collect();
+        (repos, errors, unscanned)
+    }
+
+    /// Reads a single repo again, `path` may also be one of its registered sub-paths.
+    /// Returns the repo followed by its sub-path entries, empty if `path` isn't inside a repo.
+    pub fn read_repo(path: &str, options: &ScanOptions) -> Result<Vec<RepoInfo>, RepoliceError> {
+        let Some(root) = Path::new(path).ancestors().find(|dir| dir.join(".git").exists() || options.extra_repo_for(dir).is_some()) else {
+            return Ok(Vec::new());
+        };
+        Self::read_guarded(&root.to_string_lossy(), options)
+    }
+
+    /// Reads the repo at `path` like `find_repo_info`, but turns a crash into an error, and a read running
+    /// over `options.timeout` too. The watchdog stops a read that runs over through its cancel token:
+    /// its fetches and remote checks are killed and it reads nothing further.
+    fn read_guarded(path: &str, options: &ScanOptions) -> Result<Vec<RepoInfo>, RepoliceError> {
+        let Some(timeout) = options.timeout else {
+            return Self::read_caught(path, options);
+        };
+        let cancel = options.cancel.child();
+        let read = {
+            let _watched = WATCHDOG.watch(Instant::now() + timeout, &cancel);
+            Self::read_caught(path, &ScanOptions { cancel: cancel.clone(), ..options.clone() })
+        };
+        if options.cancel.is_cancelled() {
+            // nobody waits for the repo anymore
+            Ok(Vec::new())
+        } else if cancel.is_cancelled() {
+            Err(RepoliceError::Timeout { path: path.to_string(), secs: timeout.as_secs() })
+        } else {
+            read
+        }
+    }
+
+    /// Reads the repo at `path`, a crash inside one repo must not take the whole scan down
+    fn read_caught(path: &str, options: &ScanOptions) -> Result<Vec<RepoInfo>, RepoliceError> {
+        let started = Instant::now();
+        let read = panic::catch_unwind(AssertUnwindSafe(|| Self::find_repo_info(path, &options.naming.name(path), options)));
+        let read_millis = started.elapsed().as_millis() as u64;
+        let mut entries = read.unwrap_or_else(|payload| Err(RepoliceError::Panic {
+            path: path.to_string(),
+            reason: panic_message(payload.as_ref()),
+        }))?;
+        for entry in &mut entries {
+            entry.read_millis = read_millis;
+        }
+        Ok(entries)
+    }
+
+    /// The last `limit` HEAD reflog entries of the repo at `path`, newest first
+    pub fn head_reflog(path: &str, limit: usize) -> Vec<ReflogEntry> {
+        // discover, as sub-path entries point below the repo's root
+        let Ok(repo) = gix::discover(path) else {
+            return Vec::new();
+        };
+        let Ok(head) = repo.find_reference("HEAD") else {
+            return Vec::new();
+        };
+        let mut log = head.log_iter();
+        let Ok(Some(entries)) = log.rev() else {
+            return Vec::new();
+        };
+        entries.flatten()
+            .take(limit)
+            .map(|line| ReflogEntry {
+                short_id: line.new_oid.to_hex_with_len(7).to_string(),
+                message: line.message.to_string(),
+                time: line.signature.time.seconds,
+            })
+            .collect()
+    }
+
+    /// Every local branch of the repo at `path`, the checked out one first and the rest by name
+    pub fn branches(path: &str) -> Result<Vec<Branch>, RepoliceError> {
+        let repo = gix::discover(path).map_err(|e| RepoliceError::Open { path: path.to_string(), reason: e.to_string() })?;
+        let status_error = |reason: String| RepoliceError::Status { path: path.to_string(), reason };
+        let references = repo.references().map_err(|e| status_error(e.to_string()))?;
+        let local = references.local_branches().map_err(|e| status_error(e.to_string()))?;
+        let head = repo.head_name().ok().flatten();
+        let mut branches = local.flatten()
+            .map(|mut reference| {
+                let full_name = reference.name().to_owned();
+                let configured = repo.branch_remote_tracking_ref_name(full_name.as_ref(), gix::remote::Direction::Fetch)
+                    .and_then(Result::ok);
+                // configured but never fetched or deleted on the remote
+                let gone = configured.as_ref().is_some_and(|tracking| !Self::has_reference(&repo, tracking.as_ref()));
+                let tracking = configured.filter(|_| !gone);
+                let tracked = tracking.and_then(|tracking| Some((Self::reference_id(&repo, tracking.as_ref())?, tracking)));
+                let upstream = match (tracked, reference.peel_to_id_in_place()) {
+                    (Some((tracking_id, tracking)), Ok(id)) => {
+                        let (ahead, behind) = Self::ahead_behind(&repo, id.detach(), tracking_id).map_err(status_error)?;
+                        Some(Upstream { name: tracking.shorten().to_string(), ahead, behind })
+                    }
+                    _ => None,
+                };
+                let time = reference.peel_to_commit().ok()
+                    .and_then(|commit| commit.time().ok())
+                    .map_or(0, |time| time.seconds);
+                Ok(Branch {
+                    name: full_name.shorten().to_string(),
+                    current: head.as_ref() == Some(&full_name),
+                    upstream,
+                    gone,
+                    unpushed: Self::count_unpushed(repo.path(), full_name.as_bstr()),
+                    time,
+                })
+            })
+            .collect::<Result<Vec<Branch>, RepoliceError>>()?;
+        branches.sort_by(|a, b| b.current.cmp(&a.current).then_with(|| a.name.cmp(&b.name)));
+        Ok(branches)
+    }
+
+    /// Local branches whose remote-tracking branch is configured but missing, by name
+    fn gone_branches(repo: &gix::Repository) -> Vec<String> {
+        let Ok(references) = repo.references() else {
+            return Vec::new();
+        };
+        let Ok(local) = references.local_branches() else {
+            return Vec::new();
+        };
+        let mut gone: Vec<String> = local.flatten()
+            .filter(|reference| {
+                repo.branch_remote_tracking_ref_name(reference.name(), gix::remote::Direction::Fetch)
+                    .and_then(Result::ok)
+                    .is_some_and(|tracking| !Self::has_reference(repo, tracking.as_ref()))
+            })
+            .map(|reference| reference.name().shorten().to_string())
+            .collect();
+        gone.sort();
+        gone
+    }
+
+    fn has_reference(repo: &gix::Repository, name: &gix::refs::FullNameRef) -> bool {
+        repo.try_find_reference(name).ok().flatten().is_some()
+    }
+
+    /// Where a reference points once peeled, or nothing for a reference that doesn't exist
+    fn reference_id(repo: &gix::Repository, name: &gix::refs::FullNameRef) -> Option<gix::ObjectId> {
+        let mut reference = repo.try_find_reference(name).ok()??;
+        reference.peel_to_id_in_place().ok().map(gix::Id::detach)
+    }
+
+    /// Commits only `left` has and commits only `right` has.
+    /// Like rev-list, both tips are walked newest first and every commit is marked with the sides that reach it,
+    /// a side reaching a commit that was already walked is passed down to its walked history at once.
+    /// The walk stops when both sides reach every queued commit and none of them is as new as a one-sided one.
+    fn ahead_behind(repo: &gix::Repository, left: gix::ObjectId, right: gix::ObjectId) -> Result<(usize, usize), String> {
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        const BOTH: u8 = LEFT | RIGHT;
+        // the parents of a shallow clone's oldest commits were never fetched
+        let shallow = repo.shallow_commits().map_err(|e| e.to_string())?;
+        let commit_time = |id: gix::ObjectId| -> Result<i64, String> {
+            let commit = repo.find_commit(id).map_err(|e| e.to_string())?;
+            commit.time().map(|time| time.seconds).map_err(|e| e.to_string())
+        };
+
+        let mut sides: HashMap<gix::ObjectId, u8> = HashMap::from([(left, LEFT)]);
+        *sides.entry(right).or_default() |= RIGHT;
+        let mut queue: BinaryHeap<(i64, gix::ObjectId)> = sides.keys().map(|&id| Ok((commit_time(id)?, id))).collect::<Result<_, String>>()?;
+        let mut walked: HashMap<gix::ObjectId, Vec<gix::ObjectId>> = HashMap::new();
+        let mut oldest_one_sided = i64::MAX;
+        while queue.iter().any(|(_, id)| sides[id] != BOTH) || queue.peek().is_some_and(|&(time, _)| time >= oldest_one_sided) {
+            let Some((time, id)) = queue.pop() else {
+                break;
+            };
+            let side = sides[&id];
+            if side != BOTH {
+                oldest_one_sided = oldest_one_sided.min(time);
+            }
+            let parents: Vec<gix::ObjectId> = if shallow.as_ref().is_some_and(|shallow| shallow.contains(&id)) {
+                Vec::new()
+            } else {
+                repo.find_commit(id).map_err(|e| e.to_string())?.parent_ids().map(gix::Id::detach).collect()
+            };
+            let mut marking: Vec<(gix::ObjectId, u8)> = parents.iter().map(|&parent| (parent, side)).collect();
+            walked.insert(id, parents);
+            while let Some((id, side)) = marking.pop() {
+                let marked = sides.entry(id).or_default();
+                if *marked | side == *marked {
+                    continue;
+                }
+                let found = *marked == 0;
+                *marked |= side;
+                if found {
+                    queue.push((commit_time(id)?, id));
+                } else if let Some(parents) = walked.get(&id) {
+                    marking.extend(parents.iter().map(|&parent| (parent, side)));
+                }
+            }
+        }
+        let ahead = sides.values().filter(|&&side| side == LEFT).count();
+        let behind = sides.values().filter(|&&side| side == RIGHT).count();
+        Ok((ahead, behind))
+    }
+
+    /// Commits reachable from `reference` but from no remote-tracking branch
+    fn count_unpushed(git_dir: &Path, reference: &BStr) -> usize {
+        Command::new("git")
+            .arg("--git-dir").arg(git_dir)
+            .args(["rev-list", "--count", &reference.to_string(), "--not", "--remotes"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Author emails of the commits of HEAD on no remote-tracking branch, newest first
+    fn unpushed_authors(git_dir: &Path) -> Vec<String> {
+        Command::new("git")
+            .arg("--git-dir").arg(git_dir)
+            .args(["log", "--format=%ae", "HEAD", "--not", "--remotes"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the unpushed commits and changed files of `entry` are the user's, see [`RepoInfo::mine`].
+    /// Best effort: a file someone else edited keeps the owner who created it.
+    fn is_mine(entry: &RepoInfo, root: &Path, email: Option<&str>, unpushed_authors: &[String]) -> Option<bool> {
+        let commits = email.map_or(Vec::new(), |email| unpushed_authors.iter().map(|author| author.eq_ignore_ascii_case(email)).collect());
+        let uid = current_uid();
+        let files = entry.trackers().into_iter()
+            .flat_map(|tracker| tracker.files.iter().flatten())
+            .map(|file| file.rsplit(" -> ").next().unwrap_or(file).trim_matches('"'))
+            .filter_map(|file| Some(file_owner(&root.join(file))? == uid?));
+        let mut evidence = commits.into_iter().chain(files).peekable();
+        evidence.peek()?;
+        Some(evidence.any(|mine| mine))
+    }
+
+    /// Reads every repo in `repo_list` on a fixed number of worker threads, handing each repo's path and entries,
+    /// or why it couldn't be read, to `emit` as soon as it is read. Blocks until all repos are done, or no new repo is started after `deadline`
+    /// or once the scan is cancelled.
+    fn scan_pool(repo_list: Vec<String>, options: &ScanOptions, deadline: Option<Instant>, emit: impl Fn(&str, Result<Vec<RepoInfo>, RepoliceError>) + Sync) {
+        options.naming.assign(&repo_list);
+        options.progress.start(repo_list.len());
+        let jobs = options.jobs().min(repo_list.len()).max(1);
+        let queue = Mutex::new(repo_list.into_iter());
+
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let worker = thread::Builder::new().name(SCAN_THREAD.to_string());
+                let spawned = worker.spawn_scoped(scope, || {
+                    loop {
+                        if options.cancel.is_cancelled() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                            break;
+                        }
+                        // the lock is only held while taking the next path
+                        let Some(path) = queue.lock().unwrap().next() else { break };
+                        let infos = Self::read_guarded(&path, options);
+                        options.progress.record(&infos);
+                        emit(&path, infos);
+                    }
+                });
+                // the workers already started drain the queue on their own
+                if spawned.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Reads a repo's status. Registered sub-paths of the repo are split off into their own entries,
+    /// the first entry is always the repo itself.
+    fn find_repo_info(path: &str, repo_name: &str, options: &ScanOptions) -> Result<Vec<RepoInfo>, RepoliceError> {
+        let cached = options.cache.as_ref().and_then(|cache| cache.get(path, options))
+            .filter(|entries| Changes::worktree_unchanged(path, entries.first().map_or(&[], |repo| &repo.nested_repos)));
+        let mut entries = match cached {
+            // names depend on the other repos of the scan, not on the repo itself
+            Some(mut entries) => {
+                let subpaths = options.subpaths_for(path);
+                for (entry, subpath) in entries.iter_mut().skip(1).zip(subpaths) {
+                    entry.name = Self::subpath_name(repo_name, subpath);
+                }
+                if let Some(entry) = entries.first_mut() {
+                    entry.name = repo_name.to_string();
+                }
+                entries
+            }
+            None => {
+                let entries = Self::read_repo_info(path, repo_name, options)?;
+                if let Some(cache) = &options.cache {
+                    cache.put(path, options, &entries);
+                }
+                entries
+            }
+        };
+        // not part of what is cached, the patterns, thresholds and pins may change between runs
+        let protected = Self::build_globs(&options.protected_branches);
+        for entry in &mut entries {
+            entry.protected_branch = protected.is_match(&entry.branch);
+            entry.attention = options.thresholds.check(entry);
+            entry.pinned = options.is_pinned(&entry.path);
+        }
+        entries.retain(|entry| entry.pinned || (entry.mine != Some(false) && options.filters.iter().all(|filter| filter.matches(entry))));
+        Ok(entries)
+    }
+
+    fn subpath_name(repo_name: &str, subpath: &str) -> String {
+        format!("{}/{}", repo_name, subpath.trim_end_matches('/'))
+    }
+
+    /// Opens the repo at `path`, extra repos through their git directory with `path` as the work tree
+    fn open(path: &str, options: &ScanOptions) -> Result<gix::Repository, String> {
+        let Some(extra) = options.extra_repo_for(Path::new(path)) else {
+            return gix::open(path).map_err(|e| e.to_string());
+        };
+        // such git directories are usually bare, so neither core.worktree nor the parent directory apply
+        let mut repo = gix::ThreadSafeRepository::open(&extra.git_dir).map_err(|e| e.to_string())?;
+        repo.work_tree = Some(Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path)));
+        Ok(repo.to_thread_local())
+    }
+
+    /// Reads a repo without looking at the cache or the filters
+    fn read_repo_info(path: &str, repo_name: &str, options: &ScanOptions) -> Result<Vec<RepoInfo>, RepoliceError> {
+        let mut repo = Self::open(path, options).map_err(|reason| RepoliceError::Open { path: path.to_string(), reason })?;
+        if options.fetch {
+            HOSTS.limited(Self::find_remote_url(&repo).as_deref(), &options.fetch_limits, &options.cancel, || Self::fetch(repo.git_dir(), &options.cancel));
+            // pick up the refs the fetch wrote
+            repo = Self::open(path, options).unwrap_or(repo);
+        }
+        // a half-read repo is worse than none
+        if options.cancel.is_cancelled() {
+            return Ok(Vec::new());
+        }
+        
+        let branch = Self::branch_name(&repo);
+        let repo_state = RepoState::detect(&repo, branch == "HEAD");
+        // a rebase detaches HEAD, but git remembers which branch it is rebasing
+        let branch = if repo_state == RepoState::Rebasing {
+            Self::rebased_branch(repo.git_dir()).unwrap_or(branch)
+        } else {
+            branch
+        };
+
+        // untracked directories are collapsed by default, which would hide which sub-path they belong to
+        let subpaths = options.subpaths_for(path);
+        let mut changes = Changes::read_status(&repo, options.all_untracked || !subpaths.is_empty(), Some(&options.snapshots), &options.cancel)
+            .map_err(|reason| RepoliceError::Status { path: path.to_string(), reason })?;
+
+        let mut entries = Vec::new();
+        for subpath in subpaths {
+            let scoped = changes.split_off(subpath);
+            let name = Self::subpath_name(repo_name, subpath);
+            let sub_dir = Path::new(path).join(subpath);
+            entries.push(scoped.into_repo_info(&name, &sub_dir.to_string_lossy(), path, &branch, options));
+        }
+        entries.insert(0, changes.into_repo_info(repo_name, path, path, &branch, options));
+
+        // repo-wide facts are shared by the repo and its sub-path entries
+        let last_commit = Self::find_last_commit(&repo);
+        let latest_tag = Self::find_latest_tag(&repo);
+        let remote_url = Self::find_remote_url(&repo);
+        let local_only = repo.remote_names().is_empty();
+        let shallow = repo.is_shallow();
+        let partial = Self::is_partial(&repo);
+        let upstream = Self::find_upstream(&repo)
+            .map_err(|reason| RepoliceError::Status { path: path.to_string(), reason })?;
+        let default_branch = Self::find_default_branch(&repo, upstream.as_ref())
+            .map_err(|reason| RepoliceError::Status { path: path.to_string(), reason })?;
+        let remote_check = if options.verify_remote && remote_url.is_some() {
+            let check = HOSTS.limited(remote_url.as_deref(), &options.fetch_limits, &options.cancel, || Self::verify_remote(repo.git_dir(), &options.cancel));
+            match check.flatten() {
+                Some(check) => Some(check),
+                None => return Ok(Vec::new()),
+            }
+        } else {
+            None
+        };
+        let provider = remote_url.as_deref().map(Provider::from_url);
+        let stashes = if options.stashes { Some(Self::count_stashes(&repo)) } else { None };
+        let size = if options.size { Some(dir_size(repo.git_dir())) } else { None };
+        let ignored = if options.ignored { Self::ignored_usage(&repo) } else { None };
+        let hygiene = options.hygiene.as_deref().and_then(|patterns| Self::check_hygiene(&repo, patterns));
+        let submodules = Self::find_submodules(&repo);
+        let worktrees = Self::find_worktrees(&repo, &options.cancel);
+        let gone_branches = Self::gone_branches(&repo);
+        let unpushed_authors = if options.mine && !local_only { Self::unpushed_authors(repo.git_dir()) } else { Vec::new() };
+        let email = options.mine.then(|| repo.config_snapshot().string("user.email").map(|email| email.to_string())).flatten();
+        if let Some(entry) = entries.first_mut() {
+            entry.submodules = submodules;
+            entry.worktrees = worktrees;
+            entry.gone_branches = gone_branches;
+            entry.nested_in = Self::enclosing_repo(Path::new(path));
+        }
+        if options.mine {
+            for entry in &mut entries {
+                entry.mine = Self::is_mine(entry, Path::new(path), email.as_deref(), &unpushed_authors);
+            }
+        }
+        for entry in &mut entries {
+            entry.repo_state = repo_state;
+            entry.last_commit = last_commit.clone();
+            entry.latest_tag = latest_tag.clone();
+            entry.remote_url = remote_url.clone();
+            entry.local_only = local_only;
+            entry.shallow = shallow;
+            entry.partial = partial;
+            entry.provider = provider;
+            entry.upstream = upstream.clone();
+            entry.default_branch = default_branch.clone();
+            entry.remote_check = remote_check.clone();
+            entry.stashes = stashes;
+            entry.size = size;
+            entry.ignored = ignored.clone();
+            entry.hygiene = hygiene.clone();
+        }
+        Ok(entries)
+    }
+
+    /// Sizes of the ignored files and directories of the work tree, none for bare repos or when the walk fails
+    fn ignored_usage(repo: &gix::Repository) -> Option<IgnoredUsage> {
+        let root = repo.work_dir()?.to_path_buf();
+        let index = repo.index_or_empty().ok()?;
+        // whole ignored directories are reported once, their contents are counted with dir_size
+        let options = repo.dirwalk_options().ok()?
+            .emit_ignored(Some(EmissionMode::CollapseDirectory))
+            .emit_untracked(EmissionMode::CollapseDirectory);
+        let walk = repo.dirwalk_iter(index, Vec::<BString>::new(), Default::default(), options).ok()?;
+        let mut paths: Vec<(String, u64)> = walk.flatten()
+            .filter(|item| matches!(item.entry.status, DirStatus::Ignored(_)))
+            .map(|item| {
+                let mut path = item.entry.rela_path.to_string();
+                let full = root.join(&path);
+                let size = if item.entry.disk_kind == Some(DirKind::Directory) {
+                    path.push('/');
+                    dir_size(&full)
+                } else {
+                    std::fs::symlink_metadata(&full).map_or(0, |m| m.len())
+                };
+                (path, size)
+            })
+            .collect();
+        paths.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let total = paths.iter().map(|(_, size)| size).sum();
+        paths.truncate(IgnoredUsage::LARGEST);
+        Some(IgnoredUsage { total, largest: paths })
+    }
+
+    /// Tracked files that are ignored and untracked files matching `patterns` that aren't, none for bare repos.
+    /// git lists them, it applies `core.excludesFile` and `info/exclude` like it does for `git status`.
+    fn check_hygiene(repo: &gix::Repository, patterns: &[String]) -> Option<Hygiene> {
+        let work_dir = repo.work_dir()?;
+        let ls_files = |args: &[&str]| -> Option<Vec<String>> {
+            let output = Command::new("git")
+                .arg("--git-dir").arg(repo.git_dir())
+                .arg("--work-tree").arg(work_dir)
+                .args(["ls-files", "-z", "--exclude-standard"])
+                .args(args)
+                .stdin(Stdio::null())
+                .output().ok()
+                .filter(|output| output.status.success())?;
+            Some(String::from_utf8_lossy(&output.stdout).split('\0').filter(|file| !file.is_empty()).map(str::to_string).collect())
+        };
+        let tracked_ignored = ls_files(&["--cached", "--ignored"])?;
+        // `*.log` matches anywhere below the root, `.env` only by the file's name
+        let globs = Self::build_globs(patterns);
+        let unignored = ls_files(&["--others"])?.into_iter()
+            .filter(|file| globs.is_match(file) || Path::new(file).file_name().is_some_and(|name| globs.is_match(name)))
+            .collect();
+        Some(Hygiene { tracked_ignored, unignored })
+    }
+
+    /// Checked out branch, "HEAD" when detached
+    fn branch_name(repo: &gix::Repository) -> String {
+        match repo.head() {
+            Ok(head) => {
+                match head.referent_name() {
+                    Some(name) => {
+                        let full_name = name.as_bstr().to_string();
+                        full_name.strip_prefix("refs/heads/").unwrap_or(&full_name).to_string()
+                    }
+                    None => "HEAD".to_string(),
+                }
+            }
+            _ => "HEAD".to_string(),
+        }
+    }
+
+    /// Branch and status of each linked worktree, sorted by path
+    fn find_worktrees(repo: &gix::Repository, cancel: &CancellationToken) -> Vec<WorktreeStatus> {
+        let Ok(proxies) = repo.worktrees() else {
+            return Vec::new();
+        };
+        let mut worktrees: Vec<WorktreeStatus> = proxies.into_iter()
+            .filter_map(|proxy| {
+                let path = proxy.base().ok()?;
+                let missing = !path.is_dir();
+                let worktree = proxy.into_repo_with_possibly_inaccessible_worktree().ok()?;
+                let changes = if missing {
+                    0
+                } else {
+                    Changes::read_status(&worktree, false, None, cancel).map_or(0, |changes| changes.count())
+                };
+                Some(WorktreeStatus {
+                    path: path.to_string_lossy().to_string(),
+                    branch: Self::branch_name(&worktree),
+                    changes,
+                    missing,
+                })
+            })
+            .collect();
+        worktrees.sort_by(|a, b| a.path.cmp(&b.path));
+        worktrees
+    }
+
+    /// Work tree of the closest repo above `work_tree` unless it registers `work_tree` as a submodule.
+    /// Repos hiding untracked files don't count, like dotfiles checked out to the home directory would
+    /// otherwise make every repo below it nested.
+    fn enclosing_repo(work_tree: &Path) -> Option<String> {
+        // "." has no parent to look at otherwise
+        let work_tree = work_tree.canonicalize().ok()?;
+        let parent = work_tree.ancestors().skip(1).find(|dir| dir.join(".git").exists())?;
+        let repo = gix::open(parent).ok()?;
+        if repo.config_snapshot().string("status.showUntrackedFiles").is_some_and(|mode| mode.as_ref() == "no") {
+            return None;
+        }
+        let relative = work_tree.strip_prefix(parent).ok()?.to_string_lossy().replace('\\', "/");
+        let registered = repo.submodules().ok().flatten()
+            .is_some_and(|mut submodules| submodules.any(|submodule| submodule.path().is_ok_and(|path| *path == relative)));
+        (!registered).then(|| parent.to_string_lossy().to_string())
+    }
+
+    /// Status of each submodule, honoring its `ignore` setting from `.gitmodules`
+    fn find_submodules(repo: &gix::Repository) -> Vec<SubmoduleStatus> {
+        let Ok(Some(submodules)) = repo.submodules() else {
+            return Vec::new();
+        };
+        submodules
+            .filter_map(|submodule| {
+                let path = submodule.path().ok()?.to_string();
+                let ignore = submodule.ignore().ok().flatten().unwrap_or_default();
+                let status = submodule.status(ignore, false).ok()?;
+                let uninitialized = !status.state.repository_exists || !status.state.worktree_checkout;
+                Some(SubmoduleStatus {
+                    path,
+                    uninitialized,
+                    out_of_sync: !uninitialized && status.checked_out_head_id.is_some() && status.checked_out_head_id != status.index_id,
+                    dirty: status.changes.is_some_and(|changes| !changes.is_empty()),
+                })
+            })
+            .collect()
+    }
+
+    /// Branch named in `rebase-merge/head-name` or `rebase-apply/head-name`
+    fn rebased_branch(git_dir: &Path) -> Option<String> {
+        ["rebase-merge", "rebase-apply"].iter()
+            .filter_map(|dir| std::fs::read_to_string(git_dir.join(dir).join("head-name")).ok())
+            .map(|name| name.trim().trim_start_matches("refs/heads/").to_string())
+            .find(|name| !name.is_empty() && name != "detached HEAD")
+    }
+
+    /// Fetches all remotes, failures like missing credentials only mean the status stays as it was
+    fn fetch(git_dir: &Path, cancel: &CancellationToken) {
+        let _ = cancel.output(Command::new("git")
+            .arg("--git-dir").arg(git_dir)
+            .args(["fetch", "--all", "--quiet"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()));
+    }
+
+    /// None when the check was cancelled
+    fn verify_remote(git_dir: &Path, cancel: &CancellationToken) -> Option<RemoteCheck> {
+        let mut command = Command::new("git");
+        command.arg("--git-dir").arg(git_dir)
+            .args(["ls-remote", "--quiet", "origin", "HEAD"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        // a password or host key prompt would hang the scan, fail instead
+        if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+            command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+        }
+        Some(match cancel.output(&mut command)? {
+            Ok(output) if output.status.success() => RemoteCheck::Reachable,
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("ls-remote failed");
+                RemoteCheck::Unreachable(reason.trim_start_matches("fatal: ").to_string())
+            }
+            Err(e) => RemoteCheck::Unreachable(e.to_string()),
+        })
+    }
+
+    fn count_stashes(repo: &gix::Repository) -> usize {
+        let Ok(Some(stash)) = repo.try_find_reference("refs/stash") else {
+            return 0;
+        };
+        match stash.log_iter().all() {
+            Ok(Some(entries)) => entries.count(),
+            _ => 0,
+        }
+    }
+
+    fn find_upstream(repo: &gix::Repository) -> Result<Option<Upstream>, String> {
+        let Some(head) = repo.head_name().ok().flatten() else {
+            return Ok(None);
+        };
+        let Some(Ok(tracking)) = repo.branch_remote_tracking_ref_name(head.as_ref(), gix::remote::Direction::Fetch) else {
+            return Ok(None);
+        };
+        let (Ok(head_id), Some(tracking_id)) = (repo.head_id(), Self::reference_id(repo, tracking.as_ref())) else {
+            return Ok(None);
+        };
+        let name = tracking.shorten().to_string();
+
+        let (ahead, behind) = Self::ahead_behind(repo, head_id.detach(), tracking_id)?;
+        Ok(Some(Upstream { name, ahead, behind }))
+    }
+
+    /// Where HEAD stands against origin's default branch, unless HEAD is on it or tracks it
+    fn find_default_branch(repo: &gix::Repository, upstream: Option<&Upstream>) -> Result<Option<Upstream>, String> {
+        let (Some(head), Ok(origin_head)) = (repo.head_name().ok().flatten(), repo.find_reference("refs/remotes/origin/HEAD")) else {
+            return Ok(None);
+        };
+        let gix::refs::TargetRef::Symbolic(target) = origin_head.target() else {
+            return Ok(None);
+        };
+        let name = target.shorten().to_string();
+        let on_default = name.strip_prefix("origin/") == Some(head.shorten().to_string().as_str());
+        if on_default || upstream.is_some_and(|upstream| upstream.name == name) {
+            return Ok(None);
+        }
+        let (Ok(head_id), Some(target_id)) = (repo.head_id(), Self::reference_id(repo, target)) else {
+            return Ok(None);
+        };
+        let (ahead, behind) = Self::ahead_behind(repo, head_id.detach(), target_id)?;
+        Ok(Some(Upstream { name, ahead, behind }))
+    }
+
+    /// Whether a remote is a promisor, which `git clone --filter` sets up
+    fn is_partial(repo: &gix::Repository) -> bool {
+        let config = repo.config_snapshot();
+        config.string("extensions.partialClone").is_some()
+            || repo.remote_names().iter().any(|name| config.boolean(format!("remote.{}.promisor", name).as_str()) == Some(true))
+    }
+
+    fn find_remote_url(repo: &gix::Repository) -> Option<String> {
+        let remote = repo.find_remote("origin").ok()?;
+        let url = remote.url(gix::remote::Direction::Fetch)?;
+        Some(url.to_bstring().to_string())
+    }
+
+    fn find_last_commit(repo: &gix::Repository) -> Option<CommitSummary> {
+        let commit = repo.head_commit().ok()?;
+        let message = commit.message().ok()?;
+        let author = commit.author().ok()?;
+        Some(CommitSummary {
+            short_id: commit.id().shorten_or_id().to_string(),
+            subject: message.summary().to_string(),
+            author: author.name.to_string(),
+            time: commit.time().ok()?.seconds,
+        })
+    }
+
+    fn find_latest_tag(repo: &gix::Repository) -> Option<LatestTag> {
+        let commit = repo.head_commit().ok()?;
+        let resolution = commit.describe().names(SelectRef::AllTags).try_resolve().ok()??;
+        let name = resolution.outcome.name?.to_string();
+        let target = repo.find_reference(format!("refs/tags/{}", name).as_str()).ok()?.target().try_id()?.to_owned();
+        Some(LatestTag {
+            name,
+            commits_since: resolution.outcome.depth,
+            annotated: repo.find_header(target).is_ok_and(|header| header.kind() == gix::object::Kind::Tag),
+        })
+    }
+}
+
+/// The message a panic was raised with, panics carry either a `&str` or a `String`
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// User id the scan runs as, the owner of the process' own entry in /proc or else of the home directory
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata("/proc/self").ok().or_else(|| std::fs::metadata(std::env::var_os("HOME")?).ok())?;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> Option<u32> {
+    None
+}
+
+/// Owner of `path` itself, none for files that are gone
+#[cfg(unix)]
+fn file_owner(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().map(|metadata| metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn file_owner(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Bytes used by all files below `dir`, symlinks are not followed
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries.flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Whether two index entries are the same kind of thing, a file (executable or not), a symlink or a submodule
+fn same_kind(a: Mode, b: Mode) -> bool {
+    let kind = |mode: Mode| (mode == Mode::SYMLINK, mode == Mode::COMMIT);
+    kind(a) == kind(b)
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+
+/// What HEAD and the index of each repo differ by, reused while neither of them changed.
+///
+/// Comparing the two means reading the whole tree of HEAD, which takes most of a status read in repos with
+/// hundreds of thousands of files. A snapshot is kept per git directory along with the tree of HEAD and the
+/// checksum of the index it was taken from, any commit, checkout or `git add` rewrites one of them.
+/// The work tree is still compared with the index on every read, edits don't show in either.
+///
+/// Clones share their snapshots, so re-scans of `--watch` and re-checks in the TUI use those of the first scan.
+#[derive(Clone, Default)]
+pub struct Snapshots {
+    entries: Arc<Mutex<BTreeMap<String, Snapshot>>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Snapshot {
+    /// tree of HEAD, hex
+    head: String,
+    /// checksum of the index file, hex
+    index: String,
+    /// first status column of every path that differs, deletions and additions that are renames excluded
+    pub(crate) staged: Vec<(String, char)>,
+    /// paths with unmerged entries in the index
+    pub(crate) conflicted: Vec<String>,
+    /// staged renames, from and to
+    pub(crate) renames: Vec<(String, String)>,
+    /// staged copies, from and to, only looked for when git is set up to
+    #[serde(default)]
+    pub(crate) copies: Vec<(String, String)>,
+}
+
+impl Snapshot {
+    pub(crate) fn new(head: String, index: String) -> Snapshot {
+        Snapshot { head, index, staged: Vec::new(), conflicted: Vec::new(), renames: Vec::new(), copies: Vec::new() }
+    }
+}
+
+impl Snapshots {
+    /// No snapshots yet
+    pub fn new() -> Snapshots {
+        Snapshots::default()
+    }
+
+    /// Reads snapshots saved earlier, a missing or unreadable file gives none
+    pub fn load(path: &Path) -> Snapshots {
+        let entries = fs::read_to_string(path).ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Snapshots { entries: Arc::new(Mutex::new(entries)) }
+    }
+
+    /// Writes the snapshots of every repo that still exists
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|git_dir, _| Path::new(git_dir).is_dir());
+        let contents = serde_json::to_string(&*entries).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// The snapshot of the repo at `git_dir` when it was taken from the same HEAD and index
+    pub(crate) fn get(&self, git_dir: &Path, head: &str, index: &str) -> Option<Snapshot> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(&*git_dir.to_string_lossy())
+            .filter(|snapshot| snapshot.head == head && snapshot.index == index)
+            .cloned()
+    }
+
+    pub(crate) fn put(&self, git_dir: &Path, snapshot: Snapshot) {
+        self.entries.lock().unwrap().insert(git_dir.to_string_lossy().to_string(), snapshot);
+    }
+}
@@ -0,0 +1,193 @@
+use repolice_core::{ExtraRepo, Reader, ScanOptions};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("repolice-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn finds_repos_without_find() {
+    let root = scratch("discovery");
+    for repo in ["api", "libs/ui", "libs/ui/vendored"] {
+        fs::create_dir_all(root.join(repo).join(".git").join("objects")).unwrap();
+    }
+    fs::create_dir_all(root.join("notes").join("drafts")).unwrap();
+
+    let mut repos = Reader::get_repos(root.clone(), &ScanOptions::default());
+    repos.sort();
+    let expected: Vec<String> = ["api", "libs/ui", "libs/ui/vendored"].iter()
+        .map(|repo| root.join(repo).to_string_lossy().to_string())
+        .collect();
+    assert_eq!(repos, expected);
+
+    let options = ScanOptions { exclude: vec!["**/vendored".to_string()], ..ScanOptions::default() };
+    assert_eq!(Reader::get_repos(root.clone(), &options).len(), 2);
+
+    let options = ScanOptions { max_depth: Some(1), ..ScanOptions::default() };
+    assert_eq!(Reader::get_repos(root.clone(), &options), vec![root.join("api").to_string_lossy().to_string()]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn tells_whether_there_are_repos() {
+    let root = scratch("any");
+    fs::create_dir_all(root.join("notes").join("drafts")).unwrap();
+    assert!(!Reader::has_repos_in(std::slice::from_ref(&root), &ScanOptions::default()));
+
+    fs::create_dir_all(root.join("code").join("vendored").join(".git")).unwrap();
+    assert!(Reader::has_repos_in(std::slice::from_ref(&root), &ScanOptions::default()));
+    let options = ScanOptions { exclude: vec!["**/vendored".to_string()], ..ScanOptions::default() };
+    assert!(!Reader::has_repos_in(std::slice::from_ref(&root), &options));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn prunes_ignored_directories() {
+    let root = scratch("ignore");
+    for repo in ["api", "node_modules/left-pad", "build/out/cache", "libs/keep", "libs/skip"] {
+        fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+    }
+    fs::write(root.join(".repoliceignore"), "node_modules/\n/build\n# comment\n").unwrap();
+    let home_ignore = root.join("home-ignore");
+    fs::write(&home_ignore, "libs/*\n!libs/keep\n").unwrap();
+
+    let options = ScanOptions { ignore_files: vec![home_ignore], ..ScanOptions::default() };
+    let mut repos = Reader::get_repos(root.clone(), &options);
+    repos.sort();
+    let expected: Vec<String> = ["api", "libs/keep"].iter()
+        .map(|repo| root.join(repo).to_string_lossy().to_string())
+        .collect();
+    assert_eq!(repos, expected);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn merges_overlapping_roots() {
+    let root = scratch("roots");
+    for repo in ["work/api", "work/web", "oss/lib"] {
+        fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+    }
+
+    // nested roots and the same root spelled differently list each repo once
+    let roots = [root.join("work"), root.clone(), root.join("oss/../work")];
+    let repos = Reader::get_repos_in(&roots, &ScanOptions::default());
+    assert_eq!(repos.len(), 3);
+    assert!(repos[..2].iter().all(|repo| repo.starts_with(root.join("work").to_string_lossy().as_ref())));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn lists_extra_repos_by_work_tree() {
+    let root = scratch("extra");
+    fs::create_dir_all(root.join("api").join(".git")).unwrap();
+    fs::create_dir_all(root.join(".dotfiles")).unwrap();
+
+    let dotfiles = ExtraRepo::parse(&format!("{}:{}", root.join(".dotfiles").display(), root.display())).unwrap();
+    assert_eq!(dotfiles.work_tree, root);
+    assert!(ExtraRepo::parse("~/.dotfiles").is_err());
+
+    // a work tree that is also found by the scan is listed once
+    let api = ExtraRepo { git_dir: root.join("api").join(".git"), work_tree: root.join("api") };
+    let options = ScanOptions { extra_repos: vec![dotfiles, api], ..ScanOptions::default() };
+    let repos = Reader::get_repos_in(std::slice::from_ref(&root), &options);
+    assert_eq!(repos, vec![root.join("api").to_string_lossy().to_string(), root.to_string_lossy().to_string()]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn names_repos_by_last_component() {
+    assert_eq!(Reader::repo_name("/home/me/code/api"), "api");
+    assert_eq!(Reader::repo_name("/home/me/code/api/"), "api");
+    assert_eq!(Reader::repo_name("api"), "api");
+    assert_eq!(Reader::repo_name("/"), "/");
+}
+
+#[test]
+fn names_windows_paths() {
+    assert_eq!(Reader::repo_name(r"C:\Users\me\code\api"), "api");
+    assert_eq!(Reader::repo_name(r"C:\Users\me\code\api\"), "api");
+    assert_eq!(Reader::repo_name(r"\\server\share\code\api"), "api");
+    assert_eq!(Reader::repo_name(r"C:\"), r"C:\");
+}
+
+#[test]
+fn parses_extra_repos_with_windows_drives() {
+    let parse = |spec: &str| ExtraRepo::parse(spec).map(|extra| (extra.git_dir, extra.work_tree));
+    assert_eq!(parse(r"C:\dot\.git:C:\Users\me"), Ok((PathBuf::from(r"C:\dot\.git"), PathBuf::from(r"C:\Users\me"))));
+    assert_eq!(parse(r"C:\dot\.git:D:/me"), Ok((PathBuf::from(r"C:\dot\.git"), PathBuf::from("D:/me"))));
+    assert_eq!(parse(r"~/.dotfiles:C:\Users\me"), Ok((PathBuf::from("~/.dotfiles"), PathBuf::from(r"C:\Users\me"))));
+    assert_eq!(parse(r"C:\dot\.git:~"), Ok((PathBuf::from(r"C:\dot\.git"), PathBuf::from("~"))));
+    assert_eq!(parse("~/.dotfiles:~"), Ok((PathBuf::from("~/.dotfiles"), PathBuf::from("~"))));
+    assert!(parse(r"C:\Users\me").is_err());
+    assert!(parse(r"C:\dot\.git:").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn follows_symlinks_once() {
+    use std::os::unix::fs::symlink;
+
+    let root = scratch("symlinks");
+    let outside = scratch("symlinks-outside");
+    fs::create_dir_all(root.join("real").join("api").join(".git")).unwrap();
+    fs::create_dir_all(outside.join("tool").join(".git")).unwrap();
+    // a second way to the same repo, a way out of the scanned directory and a loop back into it
+    symlink(root.join("real"), root.join("alias")).unwrap();
+    symlink(&outside, root.join("elsewhere")).unwrap();
+    symlink(&root, root.join("real").join("up")).unwrap();
+
+    let repos = Reader::get_repos(root.clone(), &ScanOptions::default());
+    assert_eq!(repos, vec![root.join("real").join("api").to_string_lossy().to_string()]);
+
+    let options = ScanOptions { follow_symlinks: true, ..ScanOptions::default() };
+    let mut repos = Reader::get_repos(root.clone(), &options);
+    repos.sort();
+    let expected: Vec<String> = ["elsewhere/tool", "real/api"].iter()
+        .map(|repo| root.join(repo).to_string_lossy().to_string())
+        .collect();
+    assert_eq!(repos, expected);
+
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&outside).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn records_unreadable_directories() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = scratch("unreadable");
+    fs::create_dir_all(root.join("api").join(".git")).unwrap();
+    let locked = root.join("locked");
+    fs::create_dir_all(locked.join("hidden").join(".git")).unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+    let options = ScanOptions::default();
+    let repos = Reader::get_repos_in(std::slice::from_ref(&root), &options);
+    // root reads it anyway
+    let readable = fs::read_dir(&locked).is_ok();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    if !readable {
+        assert_eq!(repos, vec![root.join("api").to_string_lossy().to_string()]);
+        let skipped = options.progress.skipped();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, locked.to_string_lossy());
+        assert_eq!(skipped[0].reason, "permission denied");
+
+        // each search starts over
+        Reader::get_repos_in(std::slice::from_ref(&root), &options);
+        assert!(options.progress.skipped().is_empty());
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,537 @@
+use repolice_core::{Cache, Level, Limits, Reader, RepoFilter, RepoInfo, RepoliceError, ScanOptions, Snapshots, SortOrder, Thresholds};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("repolice-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Tester", "-c", "user.email=tester@example.com", "-c", "commit.gpgsign=false"])
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// A repo with one commit holding `files`, each containing its own name
+fn repo_with(name: &str, files: &[&str]) -> PathBuf {
+    let root = scratch(name);
+    git(&root, &["init", "--quiet"]);
+    for file in files {
+        fs::write(root.join(file), file).unwrap();
+    }
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "--quiet", "-m", "init"]);
+    root
+}
+
+fn read(root: &Path) -> RepoInfo {
+    let options = ScanOptions { verbose: true, ..ScanOptions::default() };
+    Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0)
+}
+
+/// Label of the category `file` ended up in, and whether it is staged and unstaged
+fn category(repo: &RepoInfo, file: &str) -> (String, (bool, bool)) {
+    let tracker = repo.trackers().into_iter()
+        .find(|tracker| tracker.files.iter().flatten().any(|f| f == file))
+        .unwrap_or_else(|| panic!("{} is not reported", file));
+    (tracker.status.clone(), tracker.staging(file).unwrap())
+}
+
+#[test]
+fn sorts_every_porcelain_code() {
+    let root = repo_with("porcelain", &["wm", "im", "imwm", "imwd", "id", "wd", "from", "moved-from"]);
+    // " M", "M ", "MM" and "MD"
+    fs::write(root.join("wm"), "changed in the work tree").unwrap();
+    fs::write(root.join("im"), "changed in the index").unwrap();
+    fs::write(root.join("imwm"), "changed in the index").unwrap();
+    fs::write(root.join("imwd"), "changed in the index").unwrap();
+    git(&root, &["add", "im", "imwm", "imwd"]);
+    fs::write(root.join("imwm"), "and again in the work tree").unwrap();
+    fs::remove_file(root.join("imwd")).unwrap();
+    // "D " and " D"
+    git(&root, &["rm", "--quiet", "id"]);
+    fs::remove_file(root.join("wd")).unwrap();
+    // "R " and "RM"
+    git(&root, &["mv", "from", "to"]);
+    git(&root, &["mv", "moved-from", "moved-to"]);
+    fs::write(root.join("moved-to"), "changed after the move").unwrap();
+    // "A ", "AM", "AD", " A" and "??"
+    for file in ["ia", "iawm", "iawd", "intent", "untracked"] {
+        fs::write(root.join(file), file).unwrap();
+    }
+    git(&root, &["add", "ia", "iawm", "iawd"]);
+    git(&root, &["add", "--intent-to-add", "intent"]);
+    fs::write(root.join("iawm"), "changed after adding").unwrap();
+    fs::remove_file(root.join("iawd")).unwrap();
+
+    let repo = read(&root);
+    let expected = [
+        ("wm", "Modified", (false, true)),
+        ("im", "Modified", (true, false)),
+        ("imwm", "Modified", (true, true)),
+        ("imwd", "Modified", (true, true)),
+        ("id", "Deleted", (true, false)),
+        ("wd", "Missing", (false, true)),
+        ("from -> to", "Renamed", (true, false)),
+        ("moved-from -> moved-to", "Renamed", (true, true)),
+        ("ia", "Added", (true, false)),
+        ("iawm", "Added", (true, true)),
+        ("iawd", "Added", (true, true)),
+        ("intent", "Added", (false, true)),
+        ("untracked", "New", (false, true)),
+    ];
+    for (file, status, staging) in expected {
+        assert_eq!(category(&repo, file), (status.to_string(), staging), "{}", file);
+    }
+    assert_eq!(repo.total_changes(), expected.len());
+    assert_eq!(repo.conflicted_files.amount, 0);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn finds_copies_when_git_is_set_up_to() {
+    let root = repo_with("copies", &["original"]);
+    fs::copy(root.join("original"), root.join("copy")).unwrap();
+    fs::write(root.join("original"), "changed after copying").unwrap();
+    git(&root, &["add", "."]);
+    assert_eq!(category(&read(&root), "copy"), ("Added".to_string(), (true, false)));
+
+    git(&root, &["config", "status.renames", "copies"]);
+    fs::write(root.join("copy"), "changed in the work tree").unwrap();
+    let repo = read(&root);
+    assert_eq!(category(&repo, "original -> copy"), ("Copied".to_string(), (true, true)));
+    assert_eq!(category(&repo, "original"), ("Modified".to_string(), (true, false)));
+    assert_eq!(repo.total_changes(), 2);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn sorts_every_unmerged_code() {
+    let root = repo_with("unmerged", &["uu", "dd", "ud", "du"]);
+    git(&root, &["checkout", "--quiet", "-b", "theirs"]);
+    fs::write(root.join("uu"), "theirs").unwrap();
+    fs::write(root.join("aa"), "theirs").unwrap();
+    fs::write(root.join("du"), "theirs").unwrap();
+    git(&root, &["mv", "dd", "dd-theirs"]);
+    git(&root, &["rm", "--quiet", "ud"]);
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "--quiet", "-m", "theirs"]);
+    git(&root, &["checkout", "--quiet", "-"]);
+    fs::write(root.join("uu"), "ours").unwrap();
+    fs::write(root.join("aa"), "ours").unwrap();
+    fs::write(root.join("ud"), "ours").unwrap();
+    git(&root, &["mv", "dd", "dd-ours"]);
+    git(&root, &["rm", "--quiet", "du"]);
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "--quiet", "-m", "ours"]);
+    // a rename to two different names leaves DD for the old name, AU and UA for the new ones
+    let merged = Command::new("git")
+        .args(["-c", "user.name=Tester", "-c", "user.email=tester@example.com", "merge", "--quiet", "--no-edit", "theirs"])
+        .current_dir(&root)
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .output()
+        .unwrap();
+    assert!(!merged.status.success());
+
+    let repo = read(&root);
+    // UU, AA, DD, AU, UA, DU and UD
+    let unmerged = ["uu", "aa", "dd", "dd-ours", "dd-theirs", "du", "ud"];
+    for file in unmerged {
+        assert_eq!(category(&repo, file), ("Conflicted".to_string(), (false, true)), "{}", file);
+    }
+    assert_eq!(repo.total_changes(), unmerged.len());
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn reads_back_json_of_older_versions() {
+    let tracker = |status: &str| format!(r#"{{"status": "{}", "amount": 0, "staged": 0, "unstaged": 0, "files": null, "staged_files": null, "unstaged_files": null}}"#, status);
+    let json = format!(
+        r#"{{"name": "api", "path": "/src/api", "branch": "main", "repo_state": "normal",
+            "conflicted_files": {}, "new_files": {}, "added_files": {}, "modified_files": {}, "deleted_files": {},
+            "renamed_files": {}, "copied_files": {}, "last_commit": null, "languages": [], "dep_sync": null,
+            "remote_url": null, "provider": null, "remote_check": null, "upstream": null, "stashes": null, "size": null}}"#,
+        tracker("U"), tracker("??"), tracker("A"), tracker("M"), tracker("D"), tracker("R"), tracker("C"),
+    );
+    let repo: RepoInfo = serde_json::from_str(&json).unwrap();
+    assert_eq!((repo.name.as_str(), repo.branch.as_str()), ("api", "main"));
+    assert!(!repo.has_changes() && !repo.pinned && repo.attention.is_none() && repo.nested_repos.is_empty());
+}
+
+#[test]
+fn cached_repos_still_show_edits() {
+    let root = repo_with("cached", &["file"]);
+    fs::create_dir(root.join("dir")).unwrap();
+    fs::write(root.join("dir/tracked"), "tracked").unwrap();
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "--quiet", "-m", "dir"]);
+    let options = ScanOptions { cache: Some(Arc::new(Cache::new(Duration::from_secs(3600)))), ..ScanOptions::default() };
+    let read_cached = || Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0);
+    assert!(!read_cached().has_changes());
+
+    // neither touches .git nor the root directory
+    fs::write(root.join("dir/tracked"), "edited in place").unwrap();
+    assert_eq!(read_cached().modified_files.amount, 1);
+    git(&root, &["checkout", "--quiet", "--", "."]);
+    assert!(!read_cached().has_changes());
+    fs::write(root.join("dir/untracked"), "new").unwrap();
+    assert_eq!(read_cached().new_files.amount, 1);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn stops_reads_that_run_over() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = repo_with("timeout", &["file"]);
+    let ssh = root.join(".git").join("hanging-ssh");
+    fs::write(&ssh, "#!/bin/sh\nexec sleep 30\n").unwrap();
+    fs::set_permissions(&ssh, fs::Permissions::from_mode(0o755)).unwrap();
+    git(&root, &["remote", "add", "origin", "ssh://nowhere/repo"]);
+    git(&root, &["config", "core.sshCommand", &ssh.to_string_lossy()]);
+
+    let options = ScanOptions { fetch: true, timeout: Some(Duration::from_millis(300)), ..ScanOptions::default() };
+    let started = SystemTime::now();
+    let read = Reader::read_repo(&root.to_string_lossy(), &options);
+    assert!(matches!(read, Err(RepoliceError::Timeout { .. })));
+    assert!(started.elapsed().unwrap() < Duration::from_secs(5));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn detects_type_changes() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let root = repo_with("typechange", &["staged", "unstaged", "script"]);
+    for file in ["staged", "unstaged"] {
+        fs::remove_file(root.join(file)).unwrap();
+        symlink("script", root.join(file)).unwrap();
+    }
+    git(&root, &["add", "staged"]);
+    // only the executable bit changes, which is a modification
+    fs::set_permissions(root.join("script"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let repo = read(&root);
+    assert_eq!(category(&repo, "staged"), ("Type changed".to_string(), (true, false)));
+    assert_eq!(category(&repo, "unstaged"), ("Type changed".to_string(), (false, true)));
+    assert_eq!(category(&repo, "script"), ("Modified".to_string(), (false, true)));
+    assert_eq!(repo.total_changes(), 3);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn flags_ignore_rules_that_miss() {
+    let root = repo_with("hygiene", &["keep.bak", ".gitignore"]);
+    let excludes = root.join(".git").join("global-excludes");
+    fs::write(&excludes, "*.bak\n").unwrap();
+    git(&root, &["config", "core.excludesFile", &excludes.to_string_lossy()]);
+    fs::write(root.join(".gitignore"), "build/\n").unwrap();
+    fs::create_dir(root.join("build")).unwrap();
+    fs::write(root.join("build").join("out.o"), "").unwrap();
+    git(&root, &["add", "--force", "build/out.o", ".gitignore"]);
+    git(&root, &["commit", "--quiet", "-m", "ignore build"]);
+    fs::create_dir(root.join("sub")).unwrap();
+    for file in ["debug.log", "sub/.env", "sub/other.bak", "notes.txt"] {
+        fs::write(root.join(file), file).unwrap();
+    }
+
+    let options = ScanOptions { hygiene: Some(vec!["*.log".to_string(), ".env".to_string()]), ..ScanOptions::default() };
+    let repo = Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0);
+    let hygiene = repo.hygiene.unwrap();
+    assert_eq!(hygiene.tracked_ignored, ["build/out.o", "keep.bak"]);
+    assert_eq!(hygiene.unignored, ["debug.log", "sub/.env"]);
+    assert_eq!(hygiene.describe(), "2 tracked but ignored, 2 not ignored");
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn snapshots_follow_the_index() {
+    let root = repo_with("snapshots", &["staged", "edited", "moved"]);
+    fs::write(root.join("staged"), "changed in the index").unwrap();
+    git(&root, &["add", "staged"]);
+    git(&root, &["mv", "moved", "moved-to"]);
+    let options = ScanOptions { verbose: true, snapshots: Snapshots::new(), ..ScanOptions::default() };
+    let read = || Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0);
+    let fresh = read();
+
+    // the work tree is read again even when HEAD and the index are unchanged
+    fs::write(root.join("edited"), "changed in the work tree").unwrap();
+    let edited = read();
+    assert_eq!(category(&edited, "staged"), ("Modified".to_string(), (true, false)));
+    assert_eq!(category(&edited, "edited"), ("Modified".to_string(), (false, true)));
+    assert_eq!(category(&edited, "moved -> moved-to"), ("Renamed".to_string(), (true, false)));
+    assert_eq!(edited.total_changes(), fresh.total_changes() + 1);
+
+    git(&root, &["add", "edited"]);
+    assert_eq!(category(&read(), "edited"), ("Modified".to_string(), (true, false)));
+    git(&root, &["commit", "--quiet", "-m", "all of it"]);
+    assert!(!read().has_changes());
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn puts_edits_to_protected_branches_first() {
+    let release = repo_with("protected", &["one"]);
+    git(&release, &["checkout", "--quiet", "-b", "release/1.0"]);
+    fs::write(release.join("one"), "hotfix").unwrap();
+    let feature = repo_with("unprotected", &["one", "two"]);
+    git(&feature, &["checkout", "--quiet", "-b", "feature"]);
+    fs::write(feature.join("one"), "work").unwrap();
+    fs::write(feature.join("two"), "more work").unwrap();
+
+    let options = ScanOptions { protected_branches: vec!["main".to_string(), "release/*".to_string()], ..ScanOptions::default() };
+    let mut repos: Vec<RepoInfo> = [&feature, &release].iter()
+        .map(|root| Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0))
+        .collect();
+    assert!(repos[1].edits_protected_branch());
+    assert!(!repos[0].protected_branch);
+    SortOrder::Changes.sort(&mut repos);
+    assert_eq!(repos[0].branch, "release/1.0");
+
+    git(&release, &["commit", "--quiet", "-am", "hotfix"]);
+    let committed = Reader::read_repo(&release.to_string_lossy(), &options).unwrap().remove(0);
+    assert!(committed.protected_branch && !committed.edits_protected_branch());
+
+    fs::remove_dir_all(&release).unwrap();
+    fs::remove_dir_all(&feature).unwrap();
+}
+
+#[test]
+fn dates_the_oldest_change() {
+    let root = repo_with("dirty-since", &["old", "new", "gone"]);
+    let ten_days_ago = SystemTime::now() - Duration::from_secs(10 * 86400);
+    fs::write(root.join("old"), "forgotten").unwrap();
+    fs::File::options().write(true).open(root.join("old")).unwrap().set_modified(ten_days_ago).unwrap();
+    fs::write(root.join("new"), "fresh").unwrap();
+    fs::remove_file(root.join("gone")).unwrap();
+
+    let repo = read(&root);
+    let expected = ten_days_ago.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    assert_eq!(repo.dirty_since, Some(expected));
+    assert_eq!(repo.dirty_for().as_deref(), Some("10d"));
+
+    // deletions leave nothing to date
+    git(&root, &["checkout", "--", "old", "new"]);
+    let repo = read(&root);
+    assert!(repo.has_changes());
+    assert_eq!(repo.dirty_since, None);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn flags_repos_over_thresholds() {
+    let root = repo_with("thresholds", &["one", "two", "three"]);
+    for file in ["one", "two", "three"] {
+        fs::write(root.join(file), "changed").unwrap();
+    }
+    let thresholds = Thresholds {
+        warn: Limits { changes: Some(2), ..Limits::default() },
+        critical: Limits { changes: Some(10), behind: Some(0), ..Limits::default() },
+    };
+    let options = ScanOptions { thresholds, ..ScanOptions::default() };
+    let read = || Reader::read_repo(&root.to_string_lossy(), &options).unwrap().remove(0);
+
+    // without an upstream there is nothing to be behind
+    let attention = read().attention.unwrap();
+    assert_eq!(attention.level, Level::Warn);
+    assert_eq!(attention.describe(), "warn: 3 changes");
+
+    git(&root, &["checkout", "--quiet", "--", "one", "two"]);
+    assert_eq!(read().attention, None);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn tells_nested_repos_from_submodules() {
+    let root = repo_with("nested", &["file"]);
+    let library = repo_with("nested-library", &["lib"]);
+    let inner = root.join("inner");
+    fs::create_dir(&inner).unwrap();
+    git(&inner, &["init", "--quiet"]);
+    git(&root, &["-c", "protocol.file.allow=always", "submodule", "add", "--quiet", &library.to_string_lossy(), "lib"]);
+
+    // the nested repo is listed by itself rather than as an untracked directory
+    let outer = read(&root);
+    assert_eq!(outer.new_files.amount, 0);
+    assert_eq!(outer.nested_repos, ["inner"]);
+    assert_eq!(outer.nested_in, None);
+
+    assert_eq!(read(&inner).nested_in, Some(root.canonicalize().unwrap().to_string_lossy().to_string()));
+    assert_eq!(read(&root.join("lib")).nested_in, None);
+
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&library).unwrap();
+}
+
+#[test]
+fn pinned_repos_come_first_and_pass_filters() {
+    let dirty = repo_with("pin-dirty", &["file"]);
+    let pinned = repo_with("pin-clean", &["file"]);
+    fs::write(dirty.join("file"), "changed").unwrap();
+    let options = ScanOptions {
+        filters: vec![RepoFilter::Dirty],
+        pinned: vec![pinned.canonicalize().unwrap()],
+        ..ScanOptions::default()
+    };
+    let list = [&dirty, &pinned].iter().map(|root| root.to_string_lossy().to_string()).collect();
+
+    let (repos, _) = Reader::collect_repos(list, &options);
+    let paths: Vec<PathBuf> = repos.iter().map(|repo| PathBuf::from(&repo.path)).collect();
+    assert_eq!(paths, [pinned.clone(), dirty.clone()]);
+    assert!(repos[0].pinned && !repos[1].pinned);
+
+    fs::remove_dir_all(&dirty).unwrap();
+    fs::remove_dir_all(&pinned).unwrap();
+}
+
+#[test]
+fn measures_drift_from_the_default_branch() {
+    let origin = repo_with("drift-origin", &["file"]);
+    let clone = scratch("drift-clone");
+    git(&clone, &["clone", "--quiet", &origin.to_string_lossy(), "."]);
+    assert!(read(&clone).default_branch.is_none());
+
+    git(&clone, &["checkout", "--quiet", "-b", "feature"]);
+    git(&clone, &["commit", "--quiet", "--allow-empty", "-m", "feature"]);
+    for message in ["one", "two"] {
+        git(&origin, &["commit", "--quiet", "--allow-empty", "-m", message]);
+    }
+    git(&clone, &["fetch", "--quiet"]);
+
+    let default = read(&clone).default_branch.unwrap();
+    assert!(default.name.starts_with("origin/"));
+    assert_eq!((default.ahead, default.behind), (1, 2));
+
+    fs::remove_dir_all(&origin).unwrap();
+    fs::remove_dir_all(&clone).unwrap();
+}
+
+#[test]
+fn counts_ahead_and_behind_across_merges() {
+    let origin = repo_with("upstream-origin", &["file"]);
+    let clone = scratch("upstream-clone");
+    git(&clone, &["clone", "--quiet", &origin.to_string_lossy(), "."]);
+    for message in ["one", "two"] {
+        git(&origin, &["commit", "--quiet", "--allow-empty", "-m", message]);
+    }
+    git(&clone, &["commit", "--quiet", "--allow-empty", "-m", "local"]);
+    git(&clone, &["pull", "--quiet", "--no-rebase", "--no-edit"]);
+    git(&clone, &["commit", "--quiet", "--allow-empty", "-m", "after the merge"]);
+    git(&origin, &["commit", "--quiet", "--allow-empty", "-m", "three"]);
+    git(&clone, &["fetch", "--quiet"]);
+
+    // the local commit, the merge and the one after it; "one" and "two" came in with the merge
+    let upstream = read(&clone).upstream.unwrap();
+    assert_eq!((upstream.ahead, upstream.behind), (3, 1));
+
+    // the history below a shallow clone's first commit is missing, not an error
+    let shallow = scratch("upstream-shallow");
+    git(&shallow, &["clone", "--quiet", "--depth", "1", &format!("file://{}", origin.to_string_lossy()), "."]);
+    git(&origin, &["commit", "--quiet", "--allow-empty", "-m", "four"]);
+    git(&shallow, &["fetch", "--quiet"]);
+    let upstream = read(&shallow).upstream.unwrap();
+    assert_eq!((upstream.ahead, upstream.behind), (0, 1));
+
+    fs::remove_dir_all(&origin).unwrap();
+    fs::remove_dir_all(&clone).unwrap();
+    fs::remove_dir_all(&shallow).unwrap();
+}
+
+#[test]
+fn leaves_out_repos_holding_someone_elses_work() {
+    let origin = repo_with("mine-origin", &["file"]);
+    let clone = scratch("mine-clone");
+    git(&clone, &["clone", "--quiet", &origin.to_string_lossy(), "."]);
+    git(&clone, &["config", "user.email", "me@example.com"]);
+    let options = ScanOptions { verbose: true, mine: true, ..ScanOptions::default() };
+    let read = |options: &ScanOptions| Reader::read_repo(&clone.to_string_lossy(), options).unwrap();
+    assert_eq!(read(&options)[0].mine, None);
+
+    // by tester@example.com
+    git(&clone, &["commit", "--quiet", "--allow-empty", "-m", "theirs"]);
+    assert!(read(&options).is_empty());
+    assert_eq!(read(&ScanOptions { pinned: vec![clone.canonicalize().unwrap()], ..options.clone() })[0].mine, Some(false));
+
+    git(&clone, &["-c", "user.email=me@example.com", "commit", "--quiet", "--allow-empty", "-m", "mine"]);
+    assert_eq!(read(&options)[0].mine, Some(true));
+
+    fs::remove_dir_all(&origin).unwrap();
+    fs::remove_dir_all(&clone).unwrap();
+}
+
+#[test]
+fn flags_shallow_and_partial_clones() {
+    let origin = repo_with("clone-origin", &["file"]);
+    git(&origin, &["commit", "--quiet", "--allow-empty", "-m", "second"]);
+    git(&origin, &["config", "uploadpack.allowFilter", "true"]);
+    let url = format!("file://{}", origin.to_string_lossy());
+    let full = scratch("clone-full");
+    git(&full, &["clone", "--quiet", &url, "."]);
+    let shallow = scratch("clone-shallow");
+    git(&shallow, &["clone", "--quiet", "--depth", "1", &url, "."]);
+    let partial = scratch("clone-partial");
+    git(&partial, &["clone", "--quiet", "--filter=blob:none", &url, "."]);
+
+    let flags = |root: &Path| {
+        let repo = read(root);
+        (repo.shallow, repo.partial)
+    };
+    assert_eq!(flags(&full), (false, false));
+    assert_eq!(flags(&shallow), (true, false));
+    assert_eq!(flags(&partial), (false, true));
+
+    for dir in [origin, full, shallow, partial] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn finds_branches_whose_upstream_is_gone() {
+    let origin = repo_with("gone-origin", &["file"]);
+    let clone = scratch("gone-clone");
+    git(&clone, &["clone", "--quiet", &origin.to_string_lossy(), "."]);
+    for branch in ["merged", "kept"] {
+        git(&clone, &["switch", "--quiet", "-c", branch]);
+        git(&clone, &["push", "--quiet", "-u", "origin", branch]);
+    }
+    git(&clone, &["switch", "--quiet", "-"]);
+    assert!(read(&clone).gone_branches.is_empty());
+
+    git(&origin, &["branch", "-D", "merged"]);
+    git(&clone, &["fetch", "--quiet", "--prune"]);
+    assert_eq!(read(&clone).gone_branches, vec!["merged".to_string()]);
+    let branches = Reader::branches(&clone.to_string_lossy()).unwrap();
+    let gone: Vec<&str> = branches.iter().filter(|b| b.gone).map(|b| b.name.as_str()).collect();
+    assert_eq!(gone, vec!["merged"]);
+    assert!(branches.iter().all(|b| b.gone || b.upstream.is_some()));
+
+    fs::remove_dir_all(&origin).unwrap();
+    fs::remove_dir_all(&clone).unwrap();
+}
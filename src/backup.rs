@@ -0,0 +1,144 @@
+use crate::format::Format;
+use crate::exec::ExecResult;
+use repolice_core::{CancellationToken, RepoInfo};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes `git bundle` backups of whole repos into one directory.
+///
+/// The first backup of a repo is a full `<name>.bundle`, later ones only bundle the commits that are new
+/// since then as `<name>.<unix time>.bundle`. `<name>.tips` remembers which commits are already backed up,
+/// restoring means fetching from the full bundle and then from every incremental one in order.
+pub struct Backup {
+    dest: PathBuf,
+}
+
+impl Backup {
+    pub fn new(dest: &str) -> Result<Backup, String> {
+        fs::create_dir_all(dest).map_err(|e| format!("Could not create {}: {}", dest, e))?;
+        Ok(Backup { dest: PathBuf::from(dest) })
+    }
+
+    /// Backs up every repo until `cancel` is cancelled, `roots` are the scanned directories and decide the bundle names
+    pub fn run_all(&self, repos: &[RepoInfo], roots: &[PathBuf], cancel: &CancellationToken) -> Vec<ExecResult> {
+        repos.iter()
+            // sub-path entries share their repo's history
+            .filter(|repo| !repo.is_subpath())
+            .take_while(|_| !cancel.is_cancelled())
+            .map(|repo| {
+                let root = roots.iter().find(|root| Path::new(&repo.path).starts_with(root));
+                let name = Self::bundle_name(&repo.path, root.map_or(Path::new(""), |root| root.as_path()));
+                let (success, output) = match self.backup(&repo.path, &name) {
+                    Ok(summary) => (true, summary),
+                    Err(e) => (false, e),
+                };
+                ExecResult { repo: repo.name.clone(), success, output }
+            })
+            .collect()
+    }
+
+    /// The repo's path below the scanned directory, so equally named repos don't overwrite each other
+    fn bundle_name(repo_path: &str, root: &Path) -> String {
+        let repo_path = Path::new(repo_path);
+        let relative = repo_path.strip_prefix(root).ok()
+            .filter(|p| !p.as_os_str().is_empty())
+            .or_else(|| repo_path.file_name().map(Path::new))
+            .unwrap_or(repo_path);
+        // components rather than separators, so `C:\\code\\api` and `code/api` are named alike
+        relative.components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    fn backup(&self, path: &str, name: &str) -> Result<String, String> {
+        let tips_file = self.dest.join(format!("{}.tips", name));
+        let full_bundle = self.dest.join(format!("{}.bundle", name));
+        let mut known: BTreeSet<String> = if full_bundle.exists() {
+            fs::read_to_string(&tips_file).unwrap_or_default().lines().map(String::from).collect()
+        } else {
+            BTreeSet::new()
+        };
+
+        let tips = Self::tips(path)?;
+        if tips.is_empty() {
+            return Ok("no commits, nothing to back up".to_string());
+        }
+        // also when the new tips are old commits, like a new branch where another one was,
+        // git refuses to write a bundle without commits
+        if tips.iter().all(|tip| known.contains(tip)) || !Self::has_new_commits(path, &tips, &known)? {
+            if !tips.is_subset(&known) {
+                known.extend(tips);
+                Self::write_tips(&tips_file, &known)?;
+            }
+            return Ok("up to date".to_string());
+        }
+
+        let (bundle, kind) = if known.is_empty() {
+            (full_bundle, "full")
+        } else {
+            let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            (self.dest.join(format!("{}.{}.bundle", name, time)), "incremental")
+        };
+        let mut args = vec!["-C".to_string(), path.to_string(), "bundle".to_string(), "create".to_string(), "--quiet".to_string(),
+                            bundle.to_string_lossy().to_string(), "--all".to_string()];
+        args.extend(known.iter().map(|tip| format!("^{}", tip)));
+        let output = Command::new("git").args(&args).output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("git bundle failed").to_string());
+        }
+
+        known.extend(tips);
+        Self::write_tips(&tips_file, &known)?;
+
+        let size = fs::metadata(&bundle).map_or(0, |m| m.len());
+        Ok(format!("{} bundle {}, {}", kind, bundle.file_name().unwrap_or_default().to_string_lossy(), Format::current().size(size)))
+    }
+
+    /// Whether `tips` lead to commits that none of `known` lead to
+    fn has_new_commits(path: &str, tips: &BTreeSet<String>, known: &BTreeSet<String>) -> Result<bool, String> {
+        let mut args = vec!["-C".to_string(), path.to_string(), "rev-list".to_string(), "--count".to_string()];
+        args.extend(tips.iter().cloned());
+        args.extend(known.iter().map(|tip| format!("^{}", tip)));
+        let output = Command::new("git").args(&args).output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim() != "0")
+    }
+
+    fn write_tips(tips_file: &Path, tips: &BTreeSet<String>) -> Result<(), String> {
+        let contents: String = tips.iter().map(|tip| format!("{}\n", tip)).collect();
+        fs::write(tips_file, contents).map_err(|e| format!("Could not write {}: {}", tips_file.display(), e))
+    }
+
+    /// Objects HEAD and all refs point at, which is what `--all` bundles
+    fn tips(path: &str) -> Result<BTreeSet<String>, String> {
+        let output = Command::new("git")
+            .args(["-C", path, "for-each-ref", "--format=%(objectname)"])
+            .output()
+            .map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let mut tips: BTreeSet<String> = String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect();
+
+        // a detached HEAD may point at commits no ref has
+        let head = Command::new("git")
+            .args(["-C", path, "rev-parse", "--verify", "--quiet", "HEAD"])
+            .output()
+            .map_err(|e| e.to_string())?;
+        if head.status.success() {
+            tips.insert(String::from_utf8_lossy(&head.stdout).trim().to_string());
+        }
+        Ok(tips)
+    }
+}
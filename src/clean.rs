@@ -0,0 +1,205 @@
+use crate::config::{expand_home, home_dir, CleanConfig};
+use repolice_core::now;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+
+/// What a clean moved out of a repo, stored next to the files so they can be put back
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub repo: String,
+    /// seconds since the unix epoch
+    pub time: i64,
+    /// paths relative to the repo
+    pub files: Vec<String>,
+}
+
+/// One clean of one repo, restorable by its id
+pub struct Batch {
+    pub id: String,
+    pub manifest: Manifest,
+}
+
+/// Moves untracked files into a quarantine directory instead of deleting them.
+/// Every clean gets its own `<id>/` below the quarantine with a `manifest.toml` and the files under `files/`.
+pub struct Cleaner {
+    dir: PathBuf,
+}
+
+impl Cleaner {
+    pub fn new(config: &CleanConfig) -> Option<Cleaner> {
+        let dir = match &config.quarantine_dir {
+            Some(dir) => PathBuf::from(expand_home(dir)),
+            None => Self::default_dir()?,
+        };
+        Some(Cleaner { dir })
+    }
+
+    fn default_dir() -> Option<PathBuf> {
+        let base = match env::var("XDG_DATA_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".local").join("share"),
+        };
+        Some(base.join("repolice").join("quarantine"))
+    }
+
+    /// Untracked files that `git clean -fd` would remove, ignored files are left alone
+    pub fn untracked_files(repo_path: &str) -> Result<Vec<String>, String> {
+        let output = Command::new("git")
+            .args(["-C", repo_path, "ls-files", "--others", "--exclude-standard", "-z"])
+            .output()
+            .map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|f| !f.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Moves the untracked files of a repo into a new batch, returns none when there was nothing to clean
+    pub fn clean(&self, repo_path: &str, repo_name: &str) -> Result<Option<Batch>, String> {
+        let files = Self::untracked_files(repo_path)?;
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let time = now();
+        let id = self.new_id(time, repo_name);
+        let batch_dir = self.dir.join(&id);
+        fs::create_dir_all(batch_dir.join("files")).map_err(|e| format!("Could not create {}: {}", batch_dir.display(), e))?;
+
+        let repo_path = Path::new(repo_path).canonicalize().unwrap_or_else(|_| PathBuf::from(repo_path));
+        let mut moved = Vec::new();
+        let mut error = None;
+        for file in files {
+            match move_path(&repo_path.join(&file), &batch_dir.join("files").join(&file)) {
+                Ok(()) => moved.push(file),
+                Err(e) => {
+                    error = Some(format!("Could not move {}: {}", file, e));
+                    break;
+                }
+            }
+        }
+        for file in &moved {
+            remove_empty_parents(&repo_path, file);
+        }
+
+        // the manifest lists whatever did get moved, even when a later file failed
+        let manifest = Manifest { repo: repo_path.to_string_lossy().to_string(), time, files: moved };
+        let contents = toml::to_string(&manifest).map_err(|e| e.to_string())?;
+        fs::write(batch_dir.join("manifest.toml"), contents).map_err(|e| format!("Could not write manifest: {}", e))?;
+
+        match error {
+            Some(e) => Err(format!("{}, moved files are in {}", e, id)),
+            None => Ok(Some(Batch { id, manifest })),
+        }
+    }
+
+    /// All batches in the quarantine, oldest first
+    pub fn list(&self) -> Vec<Batch> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut batches: Vec<Batch> = entries.flatten()
+            .filter_map(|entry| {
+                let id = entry.file_name().to_string_lossy().to_string();
+                self.read_manifest(&id).ok().map(|manifest| Batch { id, manifest })
+            })
+            .collect();
+        batches.sort_by(|a, b| a.manifest.time.cmp(&b.manifest.time).then_with(|| a.id.cmp(&b.id)));
+        batches
+    }
+
+    /// Moves a batch's files back into their repo and removes the batch.
+    /// Nothing is moved if any of the files exists in the repo again.
+    pub fn restore(&self, id: &str) -> Result<Manifest, String> {
+        let manifest = self.read_manifest(id)?;
+        let batch_dir = self.dir.join(id);
+        let repo_path = Path::new(&manifest.repo);
+
+        let existing: Vec<&String> = manifest.files.iter().filter(|f| repo_path.join(f).symlink_metadata().is_ok()).collect();
+        if !existing.is_empty() {
+            return Err(format!("Would overwrite {} in {}", existing.iter().map(|f| f.as_str()).collect::<Vec<&str>>().join(", "), manifest.repo));
+        }
+
+        for file in &manifest.files {
+            move_path(&batch_dir.join("files").join(file), &repo_path.join(file))
+                .map_err(|e| format!("Could not restore {}: {}", file, e))?;
+        }
+        fs::remove_dir_all(&batch_dir).map_err(|e| format!("Restored, but could not remove {}: {}", batch_dir.display(), e))?;
+        Ok(manifest)
+    }
+
+    fn read_manifest(&self, id: &str) -> Result<Manifest, String> {
+        // ids are plain directory names, never paths
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            return Err(format!("Invalid id {}", id));
+        }
+        let path = self.dir.join(id).join("manifest.toml");
+        let contents = fs::read_to_string(&path).map_err(|_| format!("No quarantined files with id {}", id))?;
+        toml::from_str(&contents).map_err(|e| format!("Invalid manifest {}: {}", path.display(), e))
+    }
+
+    fn new_id(&self, time: i64, repo_name: &str) -> String {
+        let name = repo_name.replace(['/', '\\'], "-");
+        let mut id = format!("{}-{}", time, name);
+        let mut n = 1;
+        while self.dir.join(&id).exists() {
+            n += 1;
+            id = format!("{}-{}-{}", time, name, n);
+        }
+        id
+    }
+}
+
+/// Renames `from` to `to`, copying when they are on different filesystems
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let metadata = from.symlink_metadata()?;
+    if metadata.file_type().is_symlink() {
+        copy_symlink(from, to)?;
+    } else {
+        fs::copy(from, to)?;
+    }
+    fs::remove_file(from)
+}
+
+/// Creates `to` as a link to wherever the link `from` points
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+/// Creates `to` as a link to wherever the link `from` points. Windows tells links to directories from
+/// links to files, a dangling link is taken for a file link.
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+/// Removes the directories a moved file leaves empty, up to the repo root
+fn remove_empty_parents(repo_path: &Path, file: &str) {
+    let mut dir = repo_path.join(file);
+    while dir.pop() && dir.starts_with(repo_path) && dir != repo_path {
+        if fs::remove_dir(&dir).is_err() {
+            break;
+        }
+    }
+}
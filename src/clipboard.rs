@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Copies text to the system clipboard through whichever clipboard tool is installed
+pub struct Clipboard {}
+
+impl Clipboard {
+    /// Tools tried in order, the first that runs successfully wins
+    const TOOLS: [(&'static str, &'static [&'static str]); 5] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("pbcopy", &[]),
+        ("clip.exe", &[]),
+    ];
+
+    /// Returns the name of the tool that took the text. Without any tool the text is sent to the terminal
+    /// as an OSC 52 sequence, which most terminals put on the clipboard, over ssh too.
+    pub fn copy(text: &str) -> String {
+        for (tool, args) in Self::TOOLS {
+            if Self::pipe(tool, args, text).is_ok() {
+                return tool.to_string();
+            }
+        }
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()));
+        let _ = stdout.flush();
+        "terminal".to_string()
+    }
+
+    fn pipe(tool: &str, args: &[&str], text: &str) -> io::Result<()> {
+        let mut child = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        child.stdin.take().map_or(Ok(()), |mut stdin| stdin.write_all(text.as_bytes()))?;
+        if child.wait()?.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("{} failed", tool)))
+        }
+    }
+
+    /// A `cd` command for `path` that survives spaces and quotes
+    pub fn cd_command(path: &str) -> String {
+        let safe = |c: char| c.is_ascii_alphanumeric() || "/._-+~".contains(c);
+        if path.chars().all(safe) {
+            format!("cd {}", path)
+        } else {
+            format!("cd '{}'", path.replace('\'', "'\\''"))
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
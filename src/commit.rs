@@ -0,0 +1,121 @@
+use crate::config::CommitConfig;
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use regex::Regex;
+
+pub struct Committer {}
+
+impl Committer {
+    /// The repo's `commit.template` without comment lines, or an empty message if none is configured
+    pub fn template(path: &str) -> String {
+        let Ok(repo) = gix::open(path) else {
+            return String::new();
+        };
+        let template_path = match repo.config_snapshot().trusted_path("commit.template") {
+            Some(Ok(p)) => repo.work_dir().unwrap_or(repo.git_dir()).join(p),
+            _ => return String::new(),
+        };
+
+        fs::read_to_string(template_path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// Checks the message against the configured pattern and appends the configured co-author trailers
+    pub fn prepare_message(message: &str, config: &CommitConfig) -> Result<String, String> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err("Empty commit message".to_string());
+        }
+        if let Some(pattern) = &config.message_pattern {
+            let re = Regex::new(pattern).map_err(|e| format!("Invalid message_pattern: {}", e))?;
+            if !re.is_match(message) {
+                return Err(format!("Message does not match {}", pattern));
+            }
+        }
+
+        let trailers: Vec<String> = config.co_authors.iter()
+            .map(|author| format!("Co-authored-by: {}", author))
+            .filter(|trailer| !message.contains(trailer.as_str()))
+            .collect();
+        if trailers.is_empty() {
+            Ok(message.to_string())
+        } else {
+            Ok(format!("{}\n\n{}", message, trailers.join("\n")))
+        }
+    }
+
+    /// Commits whatever is currently staged in the repo, returning git's summary line.
+    /// With `signoff` git adds a `Signed-off-by:` trailer for the configured user.
+    pub fn commit(path: &str, message: &str, signoff: bool) -> Result<String, String> {
+        let mut args = vec!["-C", path, "commit", "--quiet", "--file", "-"];
+        if signoff {
+            args.push("--signoff");
+        }
+        let mut child = Command::new("git")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(message.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+        if output.status.success() {
+            Ok(message.lines().next().unwrap_or_default().to_string())
+        } else {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // git explains "nothing added to commit" on stdout, real errors on stderr
+            let reason = stderr.lines().chain(stdout.lines()).find(|l| !l.trim().is_empty()).unwrap_or("git commit failed");
+            Err(reason.trim().to_string())
+        }
+    }
+
+    /// Adds `files` to the index as they are in the worktree, deletions included.
+    /// Paths are relative to the repo's root, also when `path` is a sub-path of it.
+    pub fn stage(path: &str, files: &[&str]) -> Result<(), String> {
+        Self::git_on_files(path, &["add", "--all", "--"], files)
+    }
+
+    /// Takes `files` back out of the index, keeping their changes in the worktree. Without a first commit
+    /// there is nothing to restore them from, so they are removed from the index instead.
+    pub fn unstage(path: &str, files: &[&str], unborn: bool) -> Result<(), String> {
+        if unborn {
+            Self::git_on_files(path, &["rm", "--cached", "-r", "--quiet", "--"], files)
+        } else {
+            Self::git_on_files(path, &["restore", "--staged", "--"], files)
+        }
+    }
+
+    fn git_on_files(path: &str, args: &[&str], files: &[&str]) -> Result<(), String> {
+        let output = Command::new("git")
+            .arg("-C").arg(path)
+            .args(args)
+            // taken as they are and from the root, whatever characters they contain
+            .args(files.iter().map(|file| format!(":(top,literal){}", file)))
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().find(|l| !l.trim().is_empty()).map(str::trim);
+        Err(reason.map_or_else(|| format!("git {} failed", args[0]), str::to_string))
+    }
+}
+
+/// Whether a commit message carries a `Signed-off-by:` trailer
+pub fn is_signed_off(message: &str) -> bool {
+    message.lines().any(|line| line.trim_start().starts_with("Signed-off-by: "))
+}
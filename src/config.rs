@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Deserialize;
+use repolice_core::{ExtraRepo, FetchLimits, Language, NameStyle, Naming, RepoInfo, Thresholds};
+
+/// Settings read from `~/.config/repolice/config.toml`, every key is optional
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// globs of repo paths to skip, same as `--exclude`
+    pub exclude: Vec<String>,
+    /// globs of branches that only change through pull requests, same as `--protected-branch`
+    pub protected_branches: Vec<String>,
+    pub monorepo: Vec<MonorepoConfig>,
+    /// repos with a separate git directory, same as `--extra-repo`
+    pub extra_repo: Vec<ExtraRepoConfig>,
+    pub commit: CommitConfig,
+    pub policy: PolicyConfig,
+    pub clean: CleanConfig,
+    pub tools: ToolsConfig,
+    pub fetch: FetchConfig,
+    pub cache: CacheConfig,
+    pub names: NamesConfig,
+    pub remote_status: RemoteStatusConfig,
+    pub notify: NotifyConfig,
+    pub hygiene: HygieneConfig,
+    /// when repos need attention, flagged in listings, JSON and on TUI cards
+    pub thresholds: Thresholds,
+    /// color theme, same as `--theme`
+    pub theme: Option<String>,
+    /// "unicode", "icons" or "ascii", same as `--icons` and `--ascii`
+    pub glyphs: Option<String>,
+    /// "relative" or "absolute", same as `--dates`
+    pub dates: Option<String>,
+    /// "binary" or "si", same as `--units`
+    pub units: Option<String>,
+    /// files listed per category in verbose printed listings, same as `--max-files`
+    pub max_files: Option<usize>,
+    /// seconds reading one repo may take before it is reported as timed out, same as `--repo-timeout`
+    pub repo_timeout_secs: Option<f64>,
+    /// commands bound to function keys in the TUI's detail view
+    pub shortcuts: Vec<ShortcutConfig>,
+    /// named sets of checks selected with `--profile`
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// How commits made from the TUI are written
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// appended as `Co-authored-by:` trailers, e.g. "Jane Doe <jane@example.com>"
+    pub co_authors: Vec<String>,
+    /// regex every commit message has to match, e.g. a ticket id
+    pub message_pattern: Option<String>,
+    /// always add a `Signed-off-by:` trailer
+    pub signoff: bool,
+}
+
+/// Rules checked by `repolice police`
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// repos below these paths follow the DCO: unpushed commits need a `Signed-off-by:` trailer
+    pub signoff_paths: Vec<String>,
+    /// violations accepted for now, see `repolice police --write-baseline`
+    pub baseline: Option<String>,
+}
+
+impl PolicyConfig {
+    pub fn requires_signoff(&self, repo_path: &str) -> bool {
+        is_below_any(repo_path, &self.signoff_paths)
+    }
+}
+
+/// Where `repolice clean` moves untracked files
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct CleanConfig {
+    /// defaults to `~/.local/share/repolice/quarantine`
+    pub quarantine_dir: Option<String>,
+}
+
+/// Programs the TUI opens on the selected repo
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// opened with `e`, defaults to `$VISUAL` or `$EDITOR`
+    pub editor: Option<String>,
+    /// opened with `g`, defaults to lazygit
+    pub git: Option<String>,
+}
+
+/// A command run in the selected repo by pressing `key` in the detail view
+#[derive(Deserialize, Debug, Clone)]
+pub struct ShortcutConfig {
+    /// a function key from "F1" to "F12"
+    pub key: String,
+    /// run with `sh -c` in the repo's directory
+    pub command: String,
+    /// only offered for repos below one of these paths, all repos when empty
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// only offered for repos of this language
+    pub lang: Option<String>,
+}
+
+impl ShortcutConfig {
+    /// Number of the function key, none when `key` isn't one
+    pub fn function_key(&self) -> Option<u8> {
+        self.key.strip_prefix(['F', 'f'])?.parse().ok().filter(|n| (1..=12).contains(n))
+    }
+
+    pub fn applies_to(&self, repo: &RepoInfo) -> bool {
+        let path_matches = self.paths.is_empty() || is_below_any(&repo.path, &self.paths);
+        let lang_matches = self.lang.as_deref().is_none_or(|lang| Language::parse(lang).is_some_and(|l| repo.languages.contains(&l)));
+        path_matches && lang_matches
+    }
+}
+
+/// How fetches and remote checks spread over the hosts they talk to
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FetchConfig {
+    /// fetches running against one host at a time, 0 for no limit
+    pub per_host: usize,
+    /// milliseconds between the starts of two fetches against one host
+    pub stagger_ms: u64,
+}
+
+impl Default for FetchConfig {
+    fn default() -> FetchConfig {
+        FetchConfig { per_host: 4, stagger_ms: 100 }
+    }
+}
+
+impl FetchConfig {
+    pub fn limits(&self) -> FetchLimits {
+        FetchLimits { per_host: self.per_host, stagger: Duration::from_millis(self.stagger_ms) }
+    }
+}
+
+/// What `--hygiene` looks out for
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HygieneConfig {
+    /// globs of untracked files that should be ignored, matched against the path and the file's name
+    pub patterns: Vec<String>,
+}
+
+impl Default for HygieneConfig {
+    fn default() -> HygieneConfig {
+        let patterns = ["*.log", ".env", ".env.local", "*.pem", "*.key", "*.swp", ".DS_Store", "Thumbs.db"];
+        HygieneConfig { patterns: patterns.iter().map(|pattern| pattern.to_string()).collect() }
+    }
+}
+
+/// Where `--cache` keeps the status of clean repos and for how long
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// defaults to `~/.cache/repolice/status.json`
+    pub path: Option<String>,
+    /// seconds a cached status is used at most, edits that don't touch `.git` show up after this
+    pub max_age_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> CacheConfig {
+        CacheConfig { path: None, max_age_secs: 600 }
+    }
+}
+
+impl CacheConfig {
+    pub fn path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.path {
+            return Some(PathBuf::from(expand_home(path)));
+        }
+        let base = match env::var("XDG_CACHE_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".cache"),
+        };
+        Some(base.join("repolice").join("status.json"))
+    }
+
+    /// `snapshots.json` next to the cache, see [`repolice_core::Snapshots`]
+    pub fn snapshots_path(&self) -> Option<PathBuf> {
+        self.path().map(|path| path.with_file_name("snapshots.json"))
+    }
+}
+
+/// API tokens for `--remote-status`, the environment variables win over these
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct RemoteStatusConfig {
+    /// same as `GITHUB_TOKEN`, needs read access to pull requests and checks
+    pub github_token: Option<String>,
+    /// same as `GITLAB_TOKEN`, needs the read_api scope
+    pub gitlab_token: Option<String>,
+}
+
+/// Where `repolice report` sends its summary, to every target that is set
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// URL the report is POSTed to as JSON
+    pub webhook: Option<String>,
+    /// shape of the webhook's payload: "slack", "discord" or "json" (the default)
+    pub format: Option<String>,
+    /// text of the report, see `Notifier` for the placeholders
+    pub template: Option<String>,
+    pub smtp: Option<SmtpConfig>,
+}
+
+/// Mail server and addresses the report is mailed with
+#[derive(Deserialize, Debug, Clone)]
+pub struct SmtpConfig {
+    /// e.g. "smtps://smtp.example.com:465" or "smtp://localhost"
+    pub url: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub user: Option<String>,
+    /// defaults to `REPOLICE_SMTP_PASSWORD`
+    pub password: Option<String>,
+    /// defaults to "repolice report"
+    pub subject: Option<String>,
+    /// require STARTTLS on an `smtp://` URL, e.g. for port 587
+    #[serde(default)]
+    pub starttls: bool,
+}
+
+/// How repos with the same directory name are shown
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct NamesConfig {
+    /// "parent" (the default), "owner", "path" or "alias"
+    pub style: Option<String>,
+    /// names of repos by path, used with the "alias" style
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl NamesConfig {
+    /// The naming for a scan, aliases keyed by canonical path like monorepo sub-paths
+    pub fn naming(&self) -> Result<Naming, String> {
+        let style = self.style.as_deref().map(NameStyle::parse).transpose()?.unwrap_or_default();
+        let aliases = self.aliases.iter()
+            .map(|(path, alias)| {
+                let path = PathBuf::from(expand_home(path));
+                (path.canonicalize().unwrap_or(path), alias.clone())
+            })
+            .collect();
+        Ok(Naming::new(style, aliases))
+    }
+}
+
+/// Checks that only run when asked for, everything is off by default, and optionally the repos to check
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// directories scanned when no `--path` is given
+    pub paths: Vec<String>,
+    /// globs of repo paths to skip, on top of the top-level `exclude`
+    pub exclude: Vec<String>,
+    /// same as `--depth`, which wins when both are given
+    pub depth: Option<u8>,
+    /// same as `--sort`, which wins when both are given
+    pub sort: Option<String>,
+    /// run `git fetch` before reading each repo
+    pub fetch: bool,
+    /// stash counts, same as `--stashes`
+    pub stashes: bool,
+    /// disk usage of the `.git` directory, same as `--git-size`
+    pub size: bool,
+    /// disk usage of ignored files, same as `--disk-usage`
+    pub disk_usage: bool,
+    /// Cargo.lock checks, same as `--deps`
+    pub deps: bool,
+    /// ignored files that are tracked and files that should be ignored, same as `--hygiene`
+    pub hygiene: bool,
+    /// report `repolice police` violations below the listing
+    pub policy: bool,
+    /// check that each repo's origin still exists and lets us in, same as `--verify-remote`
+    pub remote: bool,
+}
+
+impl ProfileConfig {
+    fn deep() -> ProfileConfig {
+        ProfileConfig { fetch: true, stashes: true, size: true, disk_usage: true, deps: true, hygiene: true, policy: true, remote: true, ..ProfileConfig::default() }
+    }
+}
+
+/// A big repo whose sub-paths should be reported as separate entries
+#[derive(Deserialize, Debug, Clone)]
+pub struct MonorepoConfig {
+    pub path: String,
+    pub subpaths: Vec<String>,
+}
+
+/// A repo whose git directory lives apart from its work tree, e.g. dotfiles in `~/.dotfiles` checked out to `~`
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExtraRepoConfig {
+    pub git_dir: String,
+    pub work_tree: String,
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the default location when none is given.
+    /// A missing file yields the default config, an unreadable one a warning and the default config.
+    pub fn load(path: Option<&str>) -> Config {
+        let path = match path {
+            Some(p) => PathBuf::from(expand_home(p)),
+            None => match Self::default_path() {
+                Some(p) if p.exists() => p,
+                _ => return Config::default(),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Could not read config {}: {}", path.display(), e);
+                return Config::default();
+            }
+        };
+        match toml::from_str(&contents) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Invalid config {}: {}", path.display(), e);
+                Config::default()
+            }
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        Some(Self::dir()?.join("config.toml"))
+    }
+
+    /// `~/.config/repolice/ignore`, gitignore-style patterns of directories no scan searches for repos
+    pub fn ignore_path() -> Option<PathBuf> {
+        Some(Self::dir()?.join("ignore"))
+    }
+
+    fn dir() -> Option<PathBuf> {
+        let base = match env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".config"),
+        };
+        Some(base.join("repolice"))
+    }
+
+    /// A profile from the config, or one of the built-in "quick" (status only) and "deep" (every check)
+    pub fn profile(&self, name: &str) -> Option<ProfileConfig> {
+        match (self.profiles.get(name), name) {
+            (Some(profile), _) => Some(profile.clone()),
+            (None, "quick") => Some(ProfileConfig::default()),
+            (None, "deep") => Some(ProfileConfig::deep()),
+            _ => None,
+        }
+    }
+
+    /// Monorepo sub-paths keyed by the canonical path of their repo
+    pub fn subpaths(&self) -> Vec<(PathBuf, Vec<String>)> {
+        self.monorepo.iter()
+            .map(|m| {
+                let path = PathBuf::from(expand_home(&m.path));
+                (path.canonicalize().unwrap_or(path), m.subpaths.clone())
+            })
+            .collect()
+    }
+
+    pub fn extra_repos(&self) -> Vec<ExtraRepo> {
+        self.extra_repo.iter()
+            .map(|extra| ExtraRepo {
+                git_dir: PathBuf::from(expand_home(&extra.git_dir)),
+                work_tree: PathBuf::from(expand_home(&extra.work_tree)),
+            })
+            .collect()
+    }
+}
+
+/// Parses `--extra-repo`, `~` is expanded in both paths as the shell leaves it alone after the colon
+pub fn parse_extra_repo(spec: &str) -> Result<ExtraRepo, String> {
+    let extra = ExtraRepo::parse(spec)?;
+    Ok(ExtraRepo {
+        git_dir: PathBuf::from(expand_home(&extra.git_dir.to_string_lossy())),
+        work_tree: PathBuf::from(expand_home(&extra.work_tree.to_string_lossy())),
+    })
+}
+
+/// `$HOME`, or `%USERPROFILE%` on Windows where HOME is usually unset
+pub fn home_dir() -> Option<PathBuf> {
+    ["HOME", "USERPROFILE"].iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Expands a leading `~` to the user's home directory
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Whether `path` lies below one of `roots`, comparing canonical paths
+pub fn is_below_any(path: &str, roots: &[String]) -> bool {
+    let path = Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path));
+    roots.iter().any(|root| {
+        let root = PathBuf::from(expand_home(root));
+        path.starts_with(root.canonicalize().unwrap_or(root))
+    })
+}
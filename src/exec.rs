@@ -0,0 +1,55 @@
+use repolice_core::{CancellationToken, RepoInfo};
+
+use std::process::{Command, Stdio};
+
+pub struct ExecResult {
+    pub repo: String,
+    pub success: bool,
+    pub output: String,
+}
+
+pub struct Exec {}
+
+impl Exec {
+    /// Runs `command` with each repo as working directory, one repo after the other until `cancel` is cancelled
+    pub fn run(repos: &[RepoInfo], command: &[String], cancel: &CancellationToken) -> Vec<ExecResult> {
+        let Some((program, args)) = command.split_first() else {
+            return Vec::new();
+        };
+
+        repos.iter()
+            .take_while(|_| !cancel.is_cancelled())
+            .map(|repo| {
+                match Command::new(program).args(args).current_dir(&repo.path).output() {
+                    Ok(output) => ExecResult {
+                        repo: repo.name.clone(),
+                        success: output.status.success(),
+                        output: format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)),
+                    },
+                    Err(e) => ExecResult {
+                        repo: repo.name.clone(),
+                        success: false,
+                        output: format!("could not run {}: {}", program, e),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Runs a command line through `sh -c` in one repo, `cmd /C` on Windows
+    pub fn run_shell(repo_name: &str, path: &str, command: &str) -> ExecResult {
+        let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+        match Command::new(shell).args([flag, command]).current_dir(path).stdin(Stdio::null()).output() {
+            Ok(output) => ExecResult {
+                repo: repo_name.to_string(),
+                success: output.status.success(),
+                output: format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)),
+            },
+            Err(e) => ExecResult {
+                repo: repo_name.to_string(),
+                success: false,
+                output: format!("could not run sh: {}", e),
+            },
+        }
+    }
+}
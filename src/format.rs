@@ -0,0 +1,143 @@
+use clap::ValueEnum;
+use repolice_core::relative_age;
+
+use std::env;
+use std::sync::OnceLock;
+
+/// How times and sizes are written in the TUI and printed listings, the defaults until `Format::use_format` picks others
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+/// How commit, branch and dirty times are shown
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Dates {
+    /// how long ago, e.g. "3d ago"
+    #[default]
+    Relative,
+    /// the date and time in UTC, e.g. "2024-05-01 14:03"
+    Absolute,
+}
+
+/// Units of disk sizes
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Units {
+    /// powers of 1024: KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// powers of 1000: kB, MB, GB
+    Si,
+}
+
+/// Dates, units and the decimal separator of the user's locale
+#[derive(Clone, Copy, Default)]
+pub struct Format {
+    pub dates: Dates,
+    pub units: Units,
+    /// "1,5 MiB" instead of "1.5 MiB"
+    decimal_comma: bool,
+}
+
+impl Format {
+    /// `dates` and `units`, with the decimal separator of LC_ALL, LC_NUMERIC or LANG
+    pub fn new(dates: Dates, units: Units) -> Format {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"].iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Format { dates, units, decimal_comma: decimal_comma(&locale) }
+    }
+
+    pub fn parse_dates(name: &str) -> Result<Dates, String> {
+        Dates::from_str(name, true).map_err(|_| format!("unknown dates '{}', expected relative or absolute", name))
+    }
+
+    pub fn parse_units(name: &str) -> Result<Units, String> {
+        Units::from_str(name, true).map_err(|_| format!("unknown units '{}', expected binary or si", name))
+    }
+
+    /// Writes everything shown afterwards with `format`, only the first call counts
+    pub fn use_format(format: Format) {
+        let _ = FORMAT.set(format);
+    }
+
+    /// The format picked with [`Format::use_format`]
+    pub fn current() -> Format {
+        FORMAT.get().copied().unwrap_or_default()
+    }
+
+    /// Byte count with a unit, e.g. "3.2 MiB" or "3.4 MB"
+    pub fn size(self, bytes: u64) -> String {
+        let (base, units) = match self.units {
+            Units::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB"]),
+            Units::Si => (1000.0, ["B", "kB", "MB", "GB", "TB"]),
+        };
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= base && unit < units.len() - 1 {
+            size /= base;
+            unit += 1;
+        }
+        if unit == 0 {
+            return format!("{} B", bytes);
+        }
+        let number = format!("{:.1}", size);
+        let number = if self.decimal_comma { number.replace('.', ",") } else { number };
+        format!("{} {}", number, units[unit])
+    }
+
+    /// When something happened at `time`, seconds since the unix epoch: "3d ago" or "2024-05-01 14:03"
+    pub fn time(self, time: i64) -> String {
+        match self.dates {
+            Dates::Relative => relative_age(time),
+            Dates::Absolute => Format::timestamp(time),
+        }
+    }
+
+    /// How long something has been going on since `time`: "for 3d" or "since 2024-05-01 14:03"
+    pub fn since(self, time: i64, relative: &str) -> String {
+        match self.dates {
+            Dates::Relative => format!("for {}", relative),
+            Dates::Absolute => format!("since {}", Format::timestamp(time)),
+        }
+    }
+
+    /// UTC date and time of a unix time as "2024-05-01 14:03"
+    pub fn timestamp(time: i64) -> String {
+        format!("{} {:02}:{:02}", Format::date(time), time.rem_euclid(86400) / 3600, time.rem_euclid(3600) / 60)
+    }
+
+    /// UTC date of a unix time as "2024-05-01"
+    pub fn date(time: i64) -> String {
+        let (year, month, day) = Format::civil(time);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// UTC date and time of a unix time as mail headers want it, "Wed, 01 May 2024 14:03:09 +0000"
+    pub fn rfc2822(time: i64) -> String {
+        const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+        let (year, month, day) = Format::civil(time);
+        format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000", WEEKDAYS[time.div_euclid(86400).rem_euclid(7) as usize], day,
+            MONTHS[month as usize - 1], year, time.rem_euclid(86400) / 3600, time.rem_euclid(3600) / 60, time.rem_euclid(60))
+    }
+
+    /// Year, month and day of a unix time in UTC
+    fn civil(time: i64) -> (i64, i64, i64) {
+        // days to civil date, from Howard Hinnant's date algorithms
+        let z = time.div_euclid(86400) + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+}
+
+/// Whether the language of `locale`, e.g. "de_DE.UTF-8", writes decimals with a comma
+fn decimal_comma(locale: &str) -> bool {
+    let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+    ["de", "fr", "es", "it", "nl", "pt", "ru", "pl", "sv", "da", "fi", "nb", "nn", "cs", "sk", "tr", "uk", "el", "hu", "ro", "id"].contains(&language)
+}
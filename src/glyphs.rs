@@ -0,0 +1,91 @@
+use clap::ValueEnum;
+
+use std::env;
+use std::sync::OnceLock;
+
+/// Symbols of the TUI and printed listings, the default until `Glyphs::use_glyphs` picks another
+static GLYPHS: OnceLock<Glyphs> = OnceLock::new();
+
+/// How branches, commits ahead and behind, dirty ages and stashes are marked, shared by the TUI and printed listings
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Glyphs {
+    /// brackets and arrows
+    #[default]
+    Unicode,
+    /// Nerd Font icons, the terminal needs a patched font to show them
+    Icons,
+    /// nothing but ASCII, for dumb terminals and serial consoles
+    Ascii,
+}
+
+impl Glyphs {
+    pub fn parse(name: &str) -> Result<Glyphs, String> {
+        Glyphs::from_str(name, true).map_err(|_| format!("unknown glyphs '{}', expected unicode, icons or ascii", name))
+    }
+
+    /// `chosen`, or ASCII on terminals that call themselves dumb
+    pub fn detect(chosen: Option<Glyphs>) -> Glyphs {
+        chosen.unwrap_or_else(|| if env::var("TERM").is_ok_and(|term| term == "dumb") { Glyphs::Ascii } else { Glyphs::default() })
+    }
+
+    /// Marks everything shown afterwards with `glyphs`, only the first call counts
+    pub fn use_glyphs(glyphs: Glyphs) {
+        let _ = GLYPHS.set(glyphs);
+    }
+
+    /// The glyphs picked with [`Glyphs::use_glyphs`]
+    pub fn current() -> Glyphs {
+        GLYPHS.get().copied().unwrap_or_default()
+    }
+
+    /// "[main]", or the branch icon followed by the name
+    pub fn branch(self, name: &str) -> String {
+        match self {
+            Glyphs::Icons => format!("\u{e0a0} {}", name),
+            Glyphs::Unicode | Glyphs::Ascii => format!("[{}]", name),
+        }
+    }
+
+    /// Put before the number of commits ahead of the upstream
+    pub fn ahead(self) -> &'static str {
+        match self {
+            Glyphs::Unicode => "↑",
+            Glyphs::Icons => "\u{f062}",
+            Glyphs::Ascii => "^",
+        }
+    }
+
+    /// Put before the number of commits behind the upstream
+    pub fn behind(self) -> &'static str {
+        match self {
+            Glyphs::Unicode => "↓",
+            Glyphs::Icons => "\u{f063}",
+            Glyphs::Ascii => "v",
+        }
+    }
+
+    /// Put before how long a repo has been dirty, "for 3d" or "since 2024-05-01 14:03"
+    pub fn dirty(self) -> &'static str {
+        match self {
+            Glyphs::Icons => "\u{f044} ",
+            Glyphs::Unicode | Glyphs::Ascii => "dirty ",
+        }
+    }
+
+    /// Put before the names of pinned repos
+    pub fn pin(self) -> &'static str {
+        match self {
+            Glyphs::Unicode => "★",
+            Glyphs::Icons => "\u{f08d}",
+            Glyphs::Ascii => "*",
+        }
+    }
+
+    /// Put before the number of stash entries
+    pub fn stash(self) -> &'static str {
+        match self {
+            Glyphs::Icons => "\u{f01c} ",
+            Glyphs::Unicode | Glyphs::Ascii => "stash:",
+        }
+    }
+}
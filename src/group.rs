@@ -0,0 +1,30 @@
+use repolice_core::RepoInfo;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use clap::ValueEnum;
+
+/// What `--group-by` clusters repos under
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum GroupBy {
+    /// The directory a repo is in, like ~/work or ~/personal
+    Dir,
+}
+
+impl GroupBy {
+    /// The group `repo` belongs to, sub-path entries belong to the group of their repo
+    pub fn key(&self, repo: &RepoInfo) -> String {
+        match self {
+            GroupBy::Dir => Path::new(&repo.root).parent().map_or(String::new(), |dir| dir.to_string_lossy().to_string()),
+        }
+    }
+
+    /// `repos` split into groups ordered by key, the repos keep their order within a group
+    pub fn group(&self, repos: Vec<RepoInfo>) -> Vec<(String, Vec<RepoInfo>)> {
+        let mut groups: BTreeMap<String, Vec<RepoInfo>> = BTreeMap::new();
+        for repo in repos {
+            groups.entry(self.key(&repo)).or_default().push(repo);
+        }
+        groups.into_iter().collect()
+    }
+}
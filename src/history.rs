@@ -0,0 +1,176 @@
+use crate::config::{expand_home, home_dir};
+use repolice_core::{now, RepoInfo};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Scans older than this are dropped from the history
+const MAX_AGE_SECS: i64 = 365 * 86400;
+
+/// Summaries of earlier scans, one JSON line per scan in `~/.local/state/repolice/history.jsonl`
+#[derive(Default)]
+pub struct History {
+    /// oldest first
+    pub scans: Vec<ScanRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScanRecord {
+    /// seconds since the unix epoch
+    pub time: i64,
+    /// changed files per repo at the time, 0 for clean repos, keyed by the canonical path of the repo
+    pub repos: BTreeMap<String, usize>,
+}
+
+/// How a repo fared over the recorded scans
+pub struct RepoHistory {
+    pub path: String,
+    /// scans that saw the repo, and how many of them found it dirty
+    pub scans: usize,
+    pub dirty_scans: usize,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    /// changed files at the last scan that saw the repo
+    pub changes: usize,
+    /// first scan of the dirty streak the repo is in, none when it was clean at its last scan
+    pub dirty_since: Option<i64>,
+    /// last scan that found the repo clean, none when it never was
+    pub last_clean: Option<i64>,
+}
+
+/// A repo whose number of changed files is not what the last scan that saw it found
+pub struct RepoDiff {
+    pub path: String,
+    /// changed files at that scan, none for repos no scan saw before
+    pub before: Option<usize>,
+    pub after: usize,
+}
+
+impl History {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_STATE_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".local").join("state"),
+        };
+        Some(base.join("repolice").join("history.jsonl"))
+    }
+
+    /// A missing file is an empty history, lines that can't be read are skipped with a warning
+    pub fn load(path: &Path) -> History {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return History::default();
+        };
+        let mut scans = Vec::new();
+        for (number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(scan) => scans.push(scan),
+                Err(e) => eprintln!("Invalid history {} line {}: {}", path.display(), number + 1, e),
+            }
+        }
+        History { scans }
+    }
+
+    /// Appends a scan of `repos`, rewriting the file when scans ran out of age
+    pub fn record(path: &Path, repos: &[RepoInfo]) -> Result<(), String> {
+        let now = now();
+        let scan = ScanRecord {
+            time: now,
+            repos: repos.iter()
+                // sub-path entries are part of their repo's count
+                .filter(|repo| !repo.is_subpath())
+                .map(|repo| (key(&repo.path), repo.total_changes()))
+                .collect(),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let line = serde_json::to_string(&scan).map_err(|e| e.to_string())?;
+
+        let mut history = if path.exists() { History::load(path) } else { History::default() };
+        if history.scans.first().is_some_and(|oldest| oldest.time < now - MAX_AGE_SECS) {
+            history.scans.retain(|scan| scan.time >= now - MAX_AGE_SECS);
+            history.scans.push(scan);
+            let lines: Vec<String> = history.scans.iter().filter_map(|scan| serde_json::to_string(scan).ok()).collect();
+            return fs::write(path, lines.join("\n") + "\n").map_err(|e| format!("Could not write {}: {}", path.display(), e));
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Paths of recorded repos that `query` stands for: a path, or the name of the repo's directory
+    pub fn find(&self, query: &str) -> Vec<String> {
+        let path = PathBuf::from(expand_home(query.trim_end_matches('/')));
+        let path = path.canonicalize().unwrap_or(path);
+        let mut paths: Vec<String> = self.paths().into_iter().filter(|known| Path::new(known) == path).collect();
+        if paths.is_empty() {
+            paths = self.paths().into_iter().filter(|known| known.ends_with(&format!("/{}", query.trim_matches('/')))).collect();
+        }
+        paths
+    }
+
+    /// Every repo that was dirty at its last scan, the longest dirty first
+    pub fn dirty(&self) -> Vec<RepoHistory> {
+        let mut dirty: Vec<RepoHistory> = self.paths().iter()
+            .filter_map(|path| self.of(path))
+            .filter(|repo| repo.dirty_since.is_some())
+            .collect();
+        dirty.sort_by_key(|repo| repo.dirty_since);
+        dirty
+    }
+
+    /// Repos of `repos` that changed since the last scan that saw them, sorted by path
+    pub fn diff(&self, repos: &[RepoInfo]) -> Vec<RepoDiff> {
+        let mut diffs: Vec<RepoDiff> = repos.iter()
+            .filter(|repo| !repo.is_subpath())
+            .filter_map(|repo| {
+                let path = key(&repo.path);
+                let before = self.of(&path).map(|history| history.changes);
+                let after = repo.total_changes();
+                (before != Some(after)).then_some(RepoDiff { path, before, after })
+            })
+            .collect();
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+        diffs
+    }
+
+    pub fn of(&self, path: &str) -> Option<RepoHistory> {
+        let seen: Vec<(i64, usize)> = self.scans.iter()
+            .filter_map(|scan| scan.repos.get(path).map(|changes| (scan.time, *changes)))
+            .collect();
+        let (first_seen, _) = *seen.first()?;
+        let (last_seen, changes) = *seen.last()?;
+        let last_clean = seen.iter().rev().find(|(_, changes)| *changes == 0).map(|(time, _)| *time);
+        let dirty_since = seen.iter().rev()
+            .take_while(|(_, changes)| *changes > 0)
+            .last()
+            .map(|(time, _)| *time);
+        Some(RepoHistory {
+            path: path.to_string(),
+            scans: seen.len(),
+            dirty_scans: seen.iter().filter(|(_, changes)| *changes > 0).count(),
+            first_seen,
+            last_seen,
+            changes,
+            dirty_since,
+            last_clean,
+        })
+    }
+
+    fn paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.scans.iter().flat_map(|scan| scan.repos.keys().cloned()).collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
+
+/// History follows the repo whatever path it was scanned by
+fn key(repo_path: &str) -> String {
+    Path::new(repo_path).canonicalize().map_or(repo_path.to_string(), |path| path.to_string_lossy().to_string())
+}
@@ -0,0 +1,45 @@
+use crate::config::ToolsConfig;
+
+use std::env;
+use std::process::Command;
+
+/// Interactive programs the TUI hands the terminal to
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    Editor,
+    Git,
+}
+
+/// Builds the commands for the tools, opened in a repo's directory
+pub struct Launcher {}
+
+impl Launcher {
+    pub fn command(tool: Tool, config: &ToolsConfig, path: &str) -> Result<Command, String> {
+        let mut command = match tool {
+            Tool::Editor => {
+                let editor = config.editor.clone().unwrap_or_else(Self::editor);
+                let mut command = Self::command_line(&editor)?;
+                command.arg(".");
+                command
+            }
+            Tool::Git => Self::command_line(config.git.as_deref().unwrap_or("lazygit"))?,
+        };
+        command.current_dir(path);
+        Ok(command)
+    }
+
+    /// `$VISUAL`, then `$EDITOR`, then vi (notepad on Windows)
+    pub fn editor() -> String {
+        let fallback = if cfg!(windows) { "notepad" } else { "vi" };
+        env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_else(|_| fallback.to_string())
+    }
+
+    /// A command from a line that may carry its own arguments, like "code --wait"
+    pub fn command_line(line: &str) -> Result<Command, String> {
+        let mut parts = line.split_whitespace();
+        let program = parts.next().ok_or(format!("Empty command '{}'", line))?;
+        let mut command = Command::new(program);
+        command.args(parts);
+        Ok(command)
+    }
+}
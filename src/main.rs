@@ -1,73 +1,1195 @@
 use std::env;
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 use printer::Printer;
-use reader::Reader;
-use reader::RepoInfo;
-use clap::Parser;
+use repolice_core::{Cache, CancellationToken, ExtraRepo, Language, Reader, RepoFilter, RepoInfo, RepoliceError, SCAN_THREAD, ScanOptions, ScanProgress, SkippedDir, Snapshots, SortOrder, Thresholds};
+use config::{expand_home, Config, ProfileConfig};
+use tui::TuiOptions;
+use clap::{Parser, Subcommand, ValueEnum};
+use tokio_stream::StreamExt;
+use notify::Notifier;
+use release::Release;
+use remote::RemoteClient;
+use policy::{Baseline, Policy};
+use exec::Exec;
+use clean::Cleaner;
+use backup::Backup;
+use prune::Prune;
+use pull::Pull;
+use ssh::SshTarget;
+use push::Push;
+use state::State;
+use stats::Stats;
+use group::GroupBy;
+use history::History;
+use notes::Notes;
+use serve::Server;
+use theme::Theme;
+use glyphs::Glyphs;
+use format::{Dates, Units};
 
+mod backup;
+mod clean;
+mod clipboard;
+mod commit;
+mod config;
+mod exec;
+mod format;
+mod glyphs;
+mod group;
+mod history;
+mod launch;
+mod notes;
+mod notify;
+mod operation;
+mod policy;
 mod printer;
-mod reader;
+mod prune;
+mod pull;
+mod push;
+mod release;
+mod remote;
+mod render;
+mod resolve;
+mod serve;
+mod session;
+mod ssh;
+mod state;
+mod stats;
+#[cfg(test)]
+mod testing;
+mod theme;
 mod tui;
 
+/// `--check` exit code when a repo has uncommitted changes
+const EXIT_DIRTY: i32 = 3;
+/// `--check` exit code when every repo is committed but some have unpushed commits
+const EXIT_AHEAD: i32 = 4;
+/// files listed per category in verbose printed listings without `--max-files`
+const DEFAULT_MAX_FILES: usize = 50;
+/// `--strict` exit code when the search for repos had to skip directories it couldn't list
+const EXIT_SKIPPED: i32 = 5;
+/// exit code of bulk operations stopped with Ctrl-C, like a shell's for SIGINT
+const EXIT_INTERRUPTED: i32 = 130;
+/// how long reading one repo may take unless configured otherwise
+const DEFAULT_REPO_TIMEOUT: Duration = Duration::from_secs(60);
+/// scans that finish sooner don't flash a progress line
+const PROGRESS_DELAY: Duration = Duration::from_millis(300);
+/// how often the progress line is redrawn
+const PROGRESS_TICK: Duration = Duration::from_millis(100);
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Set a specific path to run in, instead of the current directory
-    #[arg(short, long, value_name = "PATH")]
-    path: Option<String>,
+    /// Set a specific path to run in, instead of the current directory. Repeat it or separate paths
+    /// with commas to scan several at once
+    #[arg(short, long, value_name = "PATH", global = true, value_delimiter = ',')]
+    path: Vec<String>,
 
     /// Set a max depth to search for repositories in the file-system
-    #[arg(short, long, value_name = "DEPTH")]
+    #[arg(short, long, value_name = "DEPTH", global = true)]
     depth: Option<u8>,
 
     /// Display a more verbose list of files staged for commits 
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
     
-    /// Display the status for a repository if it has new files or branches
-    #[arg(short, long)]
+    /// Fetch all remotes of each repository before reading its status
+    #[arg(short, long, global = true)]
     fetch: bool,
 
-    /// Disable TUI and print to stdout instead
+    /// Skip repos whose path matches this glob, e.g. '**/vendor/**' (repeatable)
+    #[arg(short, long, value_name = "GLOB", global = true)]
+    exclude: Vec<String>,
+
+    /// Warn about changes on branches matching this glob, e.g. 'main' or 'release/*', on top of those in the config (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    protected_branch: Vec<String>,
+
+    /// Also look for repos in symlinked directories, each directory is searched once however many links lead to it
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+
+    /// Also read the repo with this git directory and work tree, e.g. '~/.dotfiles:~' for dotfiles (repeatable)
+    #[arg(long, value_name = "GITDIR:WORKTREE", global = true, value_parser = config::parse_extra_repo)]
+    extra_repo: Vec<ExtraRepo>,
+
+    /// Only show repos matching KEY=VALUE, e.g. 'lang=rust' or 'name=work/api' (repeatable)
+    #[arg(long, value_name = "KEY=VALUE", global = true, value_parser = RepoFilter::parse)]
+    filter: Vec<RepoFilter>,
+
+    /// Only show repos with uncommitted changes, same as --filter state=dirty. Press o in the TUI to change it
+    #[arg(long, global = true, conflicts_with = "clean_only")]
+    dirty_only: bool,
+
+    /// Leave out repos dirtied by other users of a shared machine: unpushed commits by another user.email
+    /// and changed files owned by another user
+    #[arg(long, global = true)]
+    mine: bool,
+
+    /// Only show repos without uncommitted changes, same as --filter state=clean
+    #[arg(long, global = true)]
+    clean_only: bool,
+
+    /// Number of repos to read at the same time, defaults to the number of CPUs
+    #[arg(short, long, global = true)]
+    jobs: Option<usize>,
+
+    /// Show whether Cargo.lock follows Cargo.toml changes in Rust repos
+    #[arg(long, global = true)]
+    deps: bool,
+
+    /// Flag tracked files that match ignore rules and untracked files that look like they should be ignored, e.g. '*.log' or '.env'
+    #[arg(long, global = true)]
+    hygiene: bool,
+
+    /// Untracked files --hygiene expects to be ignored, instead of the patterns in the config (repeatable)
+    #[arg(long, value_name = "GLOB", global = true, requires = "hygiene")]
+    hygiene_pattern: Vec<String>,
+
+    /// Measure the disk space taken by ignored files like target/ or node_modules/, listed largest first
+    #[arg(long, global = true)]
+    disk_usage: bool,
+
+    /// Measure the size of each repo's .git directory
+    #[arg(long, global = true)]
+    git_size: bool,
+
+    /// Count the stash entries of each repo
+    #[arg(long, global = true)]
+    stashes: bool,
+
+    /// Check with `git ls-remote` that each repo's origin still exists and lets us in
+    #[arg(long, global = true)]
+    verify_remote: bool,
+
+    /// Flag repos over this limit, on top of the [thresholds] in the config, e.g. 'warn.changes=20' or 'critical.behind=50' (repeatable)
+    #[arg(long, value_name = "LEVEL.LIMIT=N", global = true, value_parser = parse_threshold)]
+    threshold: Vec<String>,
+
+    /// How untracked directories are shown, 'all' lists every file inside them like `git status -uall`
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = Untracked::Normal, global = true)]
+    show_untracked: Untracked,
+
+    /// Use a profile from the config: its checks, and its paths, excludes, depth and sort where no flag is given.
+    /// Built in are 'quick' and 'deep'
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Read settings from this file instead of ~/.config/repolice/config.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<String>,
+
+    /// Keep the TUI open and re-scan every SECS seconds (5 if no value is given)
+    #[arg(short, long, value_name = "SECS", num_args = 0..=1, default_missing_value = "5")]
+    watch: Option<u64>,
+
+    /// Also scan a directory on another machine over SSH, e.g. ci@build1:~/src. repolice has to be installed there. Repeat it for several machines, without --path nothing local is scanned
+    #[arg(long, value_name = "[USER@]HOST:PATH", value_parser = SshTarget::parse, conflicts_with_all = ["pull", "push", "prune_gone", "pick", "branches"])]
+    ssh: Vec<SshTarget>,
+
+    /// Take up the TUI where it was left when it last quit: sort order, filters, dismissed repos, folded groups and the selected card
+    #[arg(long)]
+    resume: bool,
+
+    /// Fast-forward pull every repo that is clean but behind its upstream, combine with --fetch to see new commits first
+    #[arg(long)]
+    pull: bool,
+
+    /// Push every branch that is ahead of its upstream and not behind it, asking before each repo. Never forces
+    #[arg(long, conflicts_with = "pull")]
+    push: bool,
+
+    /// Print nothing but the path of each repo, one per line, for piping into fzf: `cd $(repolice --pick | fzf)`. 'marked' puts "* " before dirty repos
+    #[arg(long, value_enum, value_name = "MARKS", num_args = 0..=1, default_missing_value = "plain", conflicts_with_all = ["pull", "push", "check", "branches", "prune_gone", "format", "timings"])]
+    pick: Option<Pick>,
+
+    /// Delete local branches whose upstream is gone, e.g. after their pull request was merged. Like `git branch -d`, keeps those not merged into the checked out branch
+    #[arg(long, conflicts_with_all = ["pull", "push", "check", "branches"])]
+    prune_gone: bool,
+
+    /// Push without asking, e.g. from a script
+    #[arg(long, requires = "push")]
+    yes: bool,
+
+    /// Print a compact summary and exit with 3 if any repo has uncommitted changes, else 4 if any is ahead of its upstream
+    #[arg(long, conflicts_with_all = ["pull", "push"])]
+    check: bool,
+
+    /// List every local branch of each repo with its upstream, flagging branches that were never pushed
+    #[arg(long, conflicts_with_all = ["pull", "push", "check"])]
+    branches: bool,
+
+    /// Print whatever was read within SECS seconds and list the repos that weren't, implies --no-tui
+    #[arg(long, value_name = "SECS", conflicts_with_all = ["pull", "push"], value_parser = parse_budget)]
+    quick: Option<Duration>,
+
+    /// After the output, list the N repos that took longest to read (10 when N is left out) and those that timed out, implies --no-tui
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10", conflicts_with_all = ["pull", "push"])]
+    timings: Option<usize>,
+
+    /// Report a repo as timed out when reading it, fetching included, takes longer than SECS, e.g. on a stale network mount. 0 waits forever
+    #[arg(long, global = true, value_name = "SECS", value_parser = parse_budget)]
+    repo_timeout: Option<Duration>,
+
+    /// Exit with 5 when the search for repos had to skip directories it couldn't list, e.g. for lack of permission
+    #[arg(long)]
+    strict: bool,
+
+    /// Leave this scan out of the history and don't update the repos remembered for "missing:" reports
+    #[arg(long, global = true)]
+    no_history: bool,
+
+    /// Order of the repos: changes (the default), name, branch, age (of the last commit), upstream (ahead/behind) or dirty (oldest change first)
+    #[arg(long, value_name = "ORDER", value_parser = SortOrder::parse)]
+    sort: Option<SortOrder>,
+
+    /// Reuse the status of clean repos from earlier runs while nothing in their .git changed, not used with --watch
+    #[arg(long, global = true)]
+    cache: bool,
+
+    /// Draw the TUI with ASCII borders and the 8 base colors only, for serial consoles and old terminals
+    #[arg(long)]
+    basic_tui: bool,
+
+    /// Colors of the TUI and printed output: dark, light, high-contrast or mono. NO_COLOR turns colors off
+    #[arg(long, global = true, value_enum, value_name = "THEME")]
+    theme: Option<Theme>,
+
+    /// Mark branches, commits ahead and behind, dirty ages and stashes with Nerd Font icons
+    #[arg(long, global = true, conflicts_with = "ascii")]
+    icons: bool,
+
+    /// Print nothing but ASCII, the TUI is drawn like with --basic-tui. The default when TERM is dumb
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Show commit, branch and dirty times as how long ago they were or as UTC dates and times
+    #[arg(long, global = true, value_enum, value_name = "DATES")]
+    dates: Option<Dates>,
+
+    /// Write disk sizes in powers of 1024 (KiB, MiB) or of 1000 (kB, MB)
+    #[arg(long, global = true, value_enum, value_name = "UNITS")]
+    units: Option<Units>,
+
+    /// List at most N files per category in verbose listings, followed by how many more there are. 0 lists them all, 50 by default
+    #[arg(long, value_name = "N")]
+    max_files: Option<usize>,
+
+    /// Cluster repos under a header per group, 'dir' groups them by the directory they are in
+    #[arg(long, value_enum, value_name = "GROUP")]
+    group_by: Option<GroupBy>,
+
+    /// Show open pull/merge requests and the last CI run of each branch on GitHub and GitLab in the TUI.
+    /// Tokens come from GITHUB_TOKEN or GH_TOKEN, GITLAB_TOKEN, or the config's [remote_status]
+    #[arg(long)]
+    remote_status: bool,
+
+    /// Disable TUI and print to stdout instead, repos with changes are printed as soon as they are read
     #[arg(long)]
     no_tui: bool,
 
+    /// With --no-tui, wait for every repo and print them in --sort order instead of as they are read
+    #[arg(long)]
+    sorted: bool,
+
+    /// With --no-tui, list the branch of each clean repo along with its name
+    #[arg(long)]
+    show_clean: bool,
+
+    /// Output format when printing instead of showing the TUI, json and ndjson imply --no-tui
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+enum Format {
+    /// Human readable listing
+    Text,
+    /// An array of all repos
+    Json,
+    /// One JSON object per line and repo, printed as soon as the repo is read
+    Ndjson,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+enum Pick {
+    /// Nothing but the paths
+    Plain,
+    /// "* " before dirty repos and two spaces before the others
+    Marked,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+enum Untracked {
+    /// Untracked directories as a whole
+    Normal,
+    /// Every untracked file
+    All,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Report which repos have unreleased commits and whether they are clean enough to release
+    Release {
+        /// Ref or tag pattern to compare HEAD against, defaults to the latest tag
+        #[arg(short, long, value_name = "PATTERN")]
+        base: Option<String>,
+    },
+    /// Check repos against the configured policy rules, exits with 1 on violations not in the baseline
+    Police {
+        /// Accepted violations, defaults to `policy.baseline` or .repolice-baseline.toml in the scanned directory
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<String>,
+
+        /// Accept every current violation by writing them to the baseline
+        #[arg(long)]
+        write_baseline: bool,
+    },
+    /// Move untracked files of every repo into a quarantine directory instead of deleting them
+    Clean {
+        /// Only show which files would be moved
+        #[arg(long)]
+        dry_run: bool,
+
+        /// List quarantined files that can be restored
+        #[arg(long, conflicts_with_all = ["dry_run", "restore"])]
+        list: bool,
+
+        /// Move the files of an earlier clean back into their repo
+        #[arg(long, value_name = "ID", conflicts_with = "dry_run")]
+        restore: Option<String>,
+    },
+    /// Write `git bundle` backups of every repo's full history, incremental after the first run
+    Backup {
+        /// Directory the bundles are written to
+        #[arg(long, value_name = "DIR")]
+        dest: String,
+    },
+    /// Summarize the scan: repos per host and language, dirty share, unpushed commits, oldest change, largest repo
+    Stats,
+    /// For cron: when a repo is dirty, ahead or conflicted, send a summary to the webhook and mailbox under [notify]
+    Report {
+        /// Print the message and webhook payload instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show how long repos have been dirty and when they were last clean, from the scans recorded so far
+    History {
+        /// Path or directory name of a repo, without one every repo dirty at its last scan is listed
+        #[arg(value_name = "REPO")]
+        repo: Option<String>,
+    },
+    /// Scan and list the repos whose changes differ from the last recorded scan: newly dirty or clean, more or fewer changes, new
+    Diff,
+    /// Rescan periodically and serve the results read-only over HTTP: a dashboard at /, JSON at /repos and /repos/NAME
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on, 0.0.0.0 makes the dashboard reachable from other machines
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1")]
+        bind: String,
+
+        /// Seconds between scans
+        #[arg(long, value_name = "SECS", default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Stop reporting repos that went missing since an earlier scan
+    Forget {
+        /// Paths as shown after "missing:"
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<String>,
+    },
+    /// Run a command in every repo, e.g. `repolice exec --lang rust -- cargo update --dry-run`
+    Exec {
+        /// Only run in repos of this language, same as --filter lang=LANG
+        #[arg(long, value_name = "LANG", value_parser = |l: &str| Language::parse(l).ok_or(format!("unknown language '{}'", l)))]
+        lang: Option<Language>,
+
+        /// The command and its arguments
+        #[arg(required = true, last = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+}
+
+/// Lets Ctrl-C stop a bulk operation before its next repo instead of killing it, so the results so far
+/// are still printed. A second Ctrl-C quits right away.
+fn cancel_on_ctrl_c(cancel: &CancellationToken) {
+    let cancel = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cancel.cancel();
+            eprintln!("Interrupted, skipping the remaining repos");
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+        }
+    });
+}
+
+/// Adds the scan to the history `repolice history` reads unless `--no-history` was given, a failure is only worth a warning
+fn record_history(args: &Args, repos: &[RepoInfo]) {
+    if args.no_history {
+        return;
+    }
+    if let Some(path) = History::default_path() && let Err(e) = History::record(&path, repos) {
+        eprintln!("{}", e);
+    }
+}
+
+fn exit_if_cancelled(cancel: &CancellationToken) {
+    if cancel.is_cancelled() {
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+}
+
+/// Lists the directories the search for repos couldn't list, on stderr when stdout is JSON,
+/// and exits with [`EXIT_SKIPPED`] under `--strict` when there were any
+fn report_skipped(skipped: &[SkippedDir], strict: bool, json: bool) {
+    if json {
+        for dir in skipped {
+            eprintln!("skipped: {} ({})", dir.path, dir.reason);
+        }
+    } else {
+        Printer::print_skipped(skipped);
+    }
+    if strict && !skipped.is_empty() {
+        std::process::exit(EXIT_SKIPPED);
+    }
+}
+
+/// Keeps panics of repos that crashed while being read off the terminal, they are listed as errors
+/// after the scan instead and would tear up the TUI
+fn quiet_scan_panics() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() != Some(SCAN_THREAD) {
+            default_hook(info);
+        }
+    }));
+}
+
+/// Runs `work` while a progress line on stderr counts the repos read, so long printed scans don't look stuck.
+/// Only on a terminal, redirected stderr gets nothing.
+fn with_progress<T>(progress: &ScanProgress, work: impl FnOnce() -> T) -> T {
+    if !io::stderr().is_terminal() {
+        return work();
+    }
+    let started = Instant::now();
+    let (done, finished) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut drawn = false;
+            // the sender is dropped once the work is done
+            while let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(PROGRESS_TICK) {
+                if started.elapsed() >= PROGRESS_DELAY {
+                    eprint!("\r\x1b[2K{}", Printer::progress_line(progress, started.elapsed()));
+                    drawn = true;
+                }
+            }
+            if drawn {
+                eprintln!("\r\x1b[2K{}", Printer::scan_summary(progress, started.elapsed()));
+            }
+        });
+        let result = work();
+        drop(done);
+        result
+    })
+}
+
+/// Checks a `--threshold`, it is applied on top of the config once that is read
+fn parse_threshold(setting: &str) -> Result<String, String> {
+    Thresholds::default().set(setting)?;
+    Ok(setting.to_string())
+}
+
+fn parse_budget(secs: &str) -> Result<Duration, String> {
+    secs.parse::<f64>().ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or(format!("'{}' is not a number of seconds", secs))
 }
 
 #[tokio::main]
 async fn main() {
+    let started = Instant::now();
     let args = Args::parse();
+    quiet_scan_panics();
     
-    let mut exec_path : PathBuf = env::current_dir().unwrap();  // cwd by default
-    let mut exec_depth : u8 = 10; 
-    let exec_no_tui : bool = args.no_tui;
+    let config = Config::load(args.config.as_deref());
+    let profile = match &args.profile {
+        Some(name) => match config.profile(name) {
+            Some(profile) => profile,
+            None => {
+                eprintln!("Unknown profile '{}'", name);
+                std::process::exit(2);
+            }
+        },
+        None => ProfileConfig::default(),
+    };
+    // subcommands read and change repos on this machine only
+    if !args.ssh.is_empty() && args.command.is_some() {
+        eprintln!("--ssh only works with the listings and the TUI, not with subcommands");
+        std::process::exit(2);
+    }
+
+    // cwd by default, then the profile's paths
+    let mut exec_paths : Vec<PathBuf> = match env::current_dir() {
+        Ok(dir) => vec![dir],
+        Err(_) if !args.path.is_empty() || !profile.paths.is_empty() => Vec::new(),
+        Err(e) => {
+            eprintln!("Cannot read the current directory, pass one with --path: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let exec_depth : u8 = args.depth.or(profile.depth).unwrap_or(10); 
+    let exec_no_tui : bool = args.no_tui || args.quick.is_some() || args.timings.is_some();
     let exec_verbose : bool = args.verbose; 
-    let exec_fetch : bool = false;
 
-    match args.path{
-        Some(p) => {exec_path = PathBuf::from(p)},
-        None => {},
+    if !args.path.is_empty() {
+        exec_paths = args.path.iter().map(PathBuf::from).collect();
+    } else if !profile.paths.is_empty() {
+        exec_paths = profile.paths.iter().map(|path| PathBuf::from(expand_home(path))).collect();
     }
+    // baselines live in and are keyed below the first path
+    let exec_path = exec_paths[0].clone();
+    let sort = match (args.sort, profile.sort.as_deref()) {
+        (Some(sort), _) => sort,
+        (None, Some(name)) => SortOrder::parse(name).unwrap_or_else(|e| {
+            eprintln!("Invalid profile sort: {}", e);
+            std::process::exit(2);
+        }),
+        (None, None) => SortOrder::default(),
+    };
 
-    match args.depth{
-        Some(d) => {exec_depth = d; println!("depth = {}, {}", d, exec_depth)},
-        None => {},
+    let theme = match (args.theme, config.theme.as_deref()) {
+        (Some(theme), _) => Some(theme),
+        (None, Some(name)) => Some(Theme::parse(name).unwrap_or_else(|e| {
+            eprintln!("Invalid theme config: {}", e);
+            std::process::exit(2);
+        })),
+        (None, None) => None,
+    };
+    let theme = Theme::detect(theme);
+    let glyphs = match (args.icons, args.ascii || args.basic_tui, config.glyphs.as_deref()) {
+        (true, _, _) => Some(Glyphs::Icons),
+        (_, true, _) => Some(Glyphs::Ascii),
+        (_, _, Some(name)) => Some(Glyphs::parse(name).unwrap_or_else(|e| {
+            eprintln!("Invalid glyphs config: {}", e);
+            std::process::exit(2);
+        })),
+        _ => None,
+    };
+    let glyphs = Glyphs::detect(glyphs);
+    Glyphs::use_glyphs(glyphs);
+    let basic_tui = args.basic_tui || glyphs == Glyphs::Ascii;
+    let dates = match (args.dates, config.dates.as_deref()) {
+        (Some(dates), _) => dates,
+        (None, Some(name)) => format::Format::parse_dates(name).unwrap_or_else(|e| {
+            eprintln!("Invalid dates config: {}", e);
+            std::process::exit(2);
+        }),
+        (None, None) => Dates::default(),
+    };
+    let units = match (args.units, config.units.as_deref()) {
+        (Some(units), _) => units,
+        (None, Some(name)) => format::Format::parse_units(name).unwrap_or_else(|e| {
+            eprintln!("Invalid units config: {}", e);
+            std::process::exit(2);
+        }),
+        (None, None) => Units::default(),
+    };
+    format::Format::use_format(format::Format::new(dates, units));
+    Printer::use_max_files(args.max_files.or(config.max_files).unwrap_or(DEFAULT_MAX_FILES));
+    // piped output stays free of escape codes
+    Printer::use_theme(if io::stdout().is_terminal() { theme } else { Theme::Mono });
+    let naming = config.names.naming().unwrap_or_else(|e| {
+        eprintln!("Invalid names config: {}", e);
+        std::process::exit(2);
+    });
+    let repo_timeout = match (args.repo_timeout, config.repo_timeout_secs) {
+        (Some(timeout), _) => timeout,
+        (None, Some(secs)) => Duration::try_from_secs_f64(secs).unwrap_or_else(|e| {
+            eprintln!("Invalid repo_timeout_secs config: {}", e);
+            std::process::exit(2);
+        }),
+        (None, None) => DEFAULT_REPO_TIMEOUT,
+    };
+    let mut thresholds = config.thresholds.clone();
+    for setting in &args.threshold {
+        thresholds.set(setting).expect("checked when parsed");
+    }
+    let mut scan_options = ScanOptions {
+        pinned: Notes::default_path().map(|path| Notes::load(&path).pinned()).unwrap_or_default(),
+        // owners are looked up per changed file
+        verbose: exec_verbose || args.mine,
+        subpaths: config.subpaths(),
+        extra_repos: config.extra_repos().into_iter().chain(args.extra_repo.iter().cloned()).collect(),
+        exclude: config.exclude.iter().chain(&profile.exclude).chain(&args.exclude).cloned().collect(),
+        max_depth: Some(exec_depth.into()),
+        follow_symlinks: args.follow_symlinks,
+        ignore_files: Config::ignore_path().into_iter().collect(),
+        filters: args.filter.iter().cloned()
+            .chain(args.dirty_only.then_some(RepoFilter::Dirty))
+            .chain(args.clean_only.then_some(RepoFilter::Clean))
+            .collect(),
+        deps: args.deps || profile.deps,
+        jobs: args.jobs.unwrap_or(0),
+        fetch: args.fetch || profile.fetch,
+        fetch_limits: config.fetch.limits(),
+        stashes: args.stashes || profile.stashes,
+        size: args.git_size || profile.size,
+        ignored: args.disk_usage || profile.disk_usage,
+        hygiene: (args.hygiene || profile.hygiene).then(|| match args.hygiene_pattern.is_empty() {
+            true => config.hygiene.patterns.clone(),
+            false => args.hygiene_pattern.clone(),
+        }),
+        verify_remote: args.verify_remote || profile.remote,
+        protected_branches: config.protected_branches.iter().chain(&args.protected_branch).cloned().collect(),
+        thresholds,
+        sort,
+        all_untracked: args.show_untracked == Untracked::All,
+        mine: args.mine,
+        cache: None,
+        naming,
+        cancel: CancellationToken::new(),
+        timeout: Some(repo_timeout).filter(|timeout| !timeout.is_zero()),
+        progress: ScanProgress::new(),
+        snapshots: Snapshots::new(),
+    };
+    // watching is about noticing edits, which the cache could hide for a while
+    let cache_path = config.cache.path().filter(|_| args.cache && args.watch.is_none());
+    if let Some(path) = &cache_path {
+        scan_options.cache = Some(Arc::new(Cache::load(path, Duration::from_secs(config.cache.max_age_secs))));
+    }
+    // snapshots never hide an edit, they are kept along with the cache even when watching
+    let snapshots_path = config.cache.snapshots_path().filter(|_| args.cache);
+    if let Some(path) = &snapshots_path {
+        scan_options.snapshots = Snapshots::load(path);
     }
+    let save_cache = || {
+        if let (Some(path), Some(cache)) = (&cache_path, &scan_options.cache) && let Err(e) = cache.save(path) {
+            eprintln!("{}", e);
+        }
+        if let Some(path) = &snapshots_path && let Err(e) = scan_options.snapshots.save(path) {
+            eprintln!("{}", e);
+        }
+    };
 
-    if args.fetch {
-        println!("fetch = {}", exec_fetch)
+    match args.command {
+        Some(Commands::Release { base }) => {
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            Printer::print_release_report(Release::collect_report(repos, base.as_deref(), &RemoteClient::new(&config.remote_status)), exec_verbose);
+            Printer::print_errors(&errors);
+            return;
+        }
+        Some(Commands::Police { baseline, write_baseline }) => {
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            let violations = Policy::check(&repos, &config.policy);
+            let explicit = baseline.or(config.policy.baseline.clone()).map(|path| PathBuf::from(expand_home(&path)));
+            let baseline_path = explicit.clone().unwrap_or_else(|| exec_path.join(".repolice-baseline.toml"));
+            if write_baseline {
+                let baseline = Baseline::from_violations(&violations, &exec_path);
+                if let Err(e) = baseline.save(&baseline_path) {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+                println!("Accepted {} policy violations in {}", baseline.accepted.len(), baseline_path.display());
+                return;
+            }
+            // only a baseline that was asked for has to exist
+            let baseline = if explicit.is_some() || baseline_path.exists() {
+                match Baseline::load(&baseline_path) {
+                    Ok(baseline) => baseline,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    }
+                }
+            } else {
+                Baseline::default()
+            };
+            let comparison = baseline.compare(violations, &exec_path);
+            Printer::print_violations(&comparison.new);
+            Printer::print_baseline(&comparison, &baseline_path);
+            Printer::print_errors(&errors);
+            if !comparison.new.is_empty() {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Backup { dest }) => {
+            let backup = match Backup::new(&dest) {
+                Ok(backup) => backup,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            cancel_on_ctrl_c(&scan_options.cancel);
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            let results = backup.run_all(&repos, &exec_paths, &scan_options.cancel);
+            Printer::print_exec_results(&results);
+            Printer::print_errors(&errors);
+            exit_if_cancelled(&scan_options.cancel);
+            if results.iter().any(|r| !r.success) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Stats) => {
+            // file lists and sizes feed the oldest change and the largest repo
+            scan_options.verbose = true;
+            scan_options.size = true;
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            Printer::print_stats(&Stats::collect(&repos));
+            Printer::print_errors(&errors);
+            return;
+        }
+        Some(Commands::Serve { port, bind, interval }) => {
+            let options = scan_options.clone();
+            let scan = move || Reader::collect_repos(Reader::get_repos_in(&exec_paths, &options), &options);
+            // IPv6 addresses need brackets before the port
+            let addr = if bind.contains(':') { format!("[{}]:{}", bind, port) } else { format!("{}:{}", bind, port) };
+            if let Err(e) = Server::run(&addr, Duration::from_secs(interval), scan).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Report { dry_run }) => {
+            let notifier = Notifier::new(&config.notify).unwrap_or_else(|e| {
+                eprintln!("Invalid notify config: {}", e);
+                std::process::exit(2);
+            });
+            if !dry_run && !notifier.has_targets() {
+                eprintln!("Nothing to send the report to, set webhook or smtp under [notify] in the config");
+                std::process::exit(2);
+            }
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            Printer::print_errors(&errors);
+            record_history(&args, &repos);
+            // all is well, cron stays quiet
+            if !repos.iter().any(Notifier::needs_attention) {
+                return;
+            }
+            if dry_run {
+                let message = notifier.message(&repos);
+                println!("{}", message);
+                println!("{}", notifier.payload(&message, &repos));
+                return;
+            }
+            let mut failed = false;
+            for (target, result) in notifier.send(&repos) {
+                if let Err(e) = result {
+                    eprintln!("Could not send the report to {}: {}", target, e);
+                    failed = true;
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::History { repo }) => {
+            let Some(history_path) = History::default_path() else {
+                eprintln!("No state directory, set HOME (USERPROFILE on Windows) or XDG_STATE_HOME");
+                std::process::exit(1);
+            };
+            let history = History::load(&history_path);
+            let Some(repo) = repo else {
+                Printer::print_dirty_history(&history.dirty());
+                return;
+            };
+            match history.find(&repo).as_slice() {
+                [] => {
+                    eprintln!("{} was never scanned, history is recorded by printed, JSON, --check, report and diff runs", repo);
+                    std::process::exit(1);
+                }
+                [path] => Printer::print_repo_history(&history.of(path).expect("found in the history")),
+                paths => {
+                    eprintln!("{} matches several repos, give its path: {}", repo, paths.join(", "));
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Diff) => {
+            let Some(history_path) = History::default_path() else {
+                eprintln!("No state directory, set HOME (USERPROFILE on Windows) or XDG_STATE_HOME");
+                std::process::exit(1);
+            };
+            let history = History::load(&history_path);
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            Printer::print_diff(history.scans.last().map(|scan| scan.time), &history.diff(&repos));
+            Printer::print_errors(&errors);
+            // the next diff starts from here
+            record_history(&args, &repos);
+            return;
+        }
+        Some(Commands::Forget { paths }) => {
+            let Some(state_path) = State::default_path() else {
+                eprintln!("No state directory, set HOME (USERPROFILE on Windows) or XDG_STATE_HOME");
+                std::process::exit(1);
+            };
+            let mut state = State::load(&state_path);
+            let mut failed = false;
+            for path in paths {
+                if !state.forget(&path) {
+                    eprintln!("{} was never seen", path);
+                    failed = true;
+                }
+            }
+            if let Err(e) = state.save(&state_path) {
+                eprintln!("{}", e);
+                failed = true;
+            }
+            if failed {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Exec { lang, command }) => {
+            if let Some(language) = lang {
+                scan_options.filters.push(RepoFilter::Lang(language));
+            }
+            cancel_on_ctrl_c(&scan_options.cancel);
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            let results = Exec::run(&repos, &command, &scan_options.cancel);
+            Printer::print_exec_results(&results);
+            Printer::print_errors(&errors);
+            exit_if_cancelled(&scan_options.cancel);
+            if results.iter().any(|r| !r.success) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Clean { dry_run, list, restore }) => {
+            let Some(cleaner) = Cleaner::new(&config.clean) else {
+                eprintln!("No quarantine directory, set HOME (USERPROFILE on Windows) or quarantine_dir under [clean]");
+                std::process::exit(1);
+            };
+            if list {
+                Printer::print_quarantine(&cleaner.list());
+                return;
+            }
+            if let Some(id) = restore {
+                match cleaner.restore(&id) {
+                    Ok(manifest) => println!("Restored {} files to {}", manifest.files.len(), manifest.repo),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
+            cancel_on_ctrl_c(&scan_options.cancel);
+            let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+            let mut failed = false;
+            for repo in repos.iter().filter(|r| r.new_files.amount > 0) {
+                if scan_options.cancel.is_cancelled() {
+                    break;
+                }
+                let result = if dry_run {
+                    Cleaner::untracked_files(&repo.path).map(|files| files.into_iter().map(|f| format!("would move {}", f)).collect())
+                } else {
+                    cleaner.clean(&repo.path, &repo.name).map(|batch| match batch {
+                        Some(batch) => vec![format!("moved {} files, restore with `repolice clean --restore {}`", batch.manifest.files.len(), batch.id)],
+                        None => Vec::new(),
+                    })
+                };
+                failed |= result.is_err();
+                Printer::print_clean_result(&repo.name, result);
+            }
+            Printer::print_errors(&errors);
+            exit_if_cancelled(&scan_options.cancel);
+            if failed {
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
     }
-    if exec_no_tui {
-        let repos: Vec<RepoInfo> = Reader::collect_repos(Reader::get_repos(exec_path.clone()), exec_verbose, exec_depth);
-        Printer::print_repos(repos, exec_verbose);
+
+    let print_timings = |repos: &[RepoInfo], errors: &[RepoliceError]| {
+        if let Some(count) = args.timings {
+            for line in Printer::timings(repos, errors, count) {
+                println!("{}", line);
+            }
+        }
+    };
+
+    // with nothing but --ssh, the other machines are all there is to scan
+    if !args.ssh.is_empty() && args.path.is_empty() && profile.paths.is_empty() {
+        exec_paths.clear();
+    }
+
+    // the listings below respect --quick, everything else reads all repos
+    let collect = |options: &ScanOptions| -> (Vec<RepoInfo>, Vec<RepoliceError>, Vec<String>) {
+        let (mut repos, mut errors, mut unscanned) = with_progress(&options.progress, || {
+            let repo_list = Reader::get_repos_in_until(&exec_paths, options, args.quick.map(|budget| started + budget));
+            match args.quick {
+                Some(budget) => Reader::collect_repos_until(repo_list, options, started + budget),
+                None => {
+                    let (repos, errors) = Reader::collect_repos(repo_list, options);
+                    (repos, errors, Vec::new())
+                }
+            }
+        });
+        save_cache();
+        if !args.ssh.is_empty() {
+            let (remote_repos, remote_errors, remote_unscanned) = SshTarget::collect_all(&args.ssh, options, args.quick.map(|budget| started + budget));
+            repos.extend(remote_repos);
+            errors.extend(remote_errors);
+            unscanned.extend(remote_unscanned);
+            options.sort.sort(&mut repos);
+        }
+        (repos, errors, unscanned)
+    };
+
+    if let Some(pick) = args.pick {
+        // fzf draws on the terminal stderr points at, a progress line or errors would garble it
+        let repo_list = Reader::get_repos_in_until(&exec_paths, &scan_options, args.quick.map(|budget| started + budget));
+        let repos = match args.quick {
+            Some(budget) => Reader::collect_repos_until(repo_list, &scan_options, started + budget).0,
+            None => Reader::collect_repos(repo_list, &scan_options).0,
+        };
+        save_cache();
+        // nobody reads anymore once fzf has its pick
+        let _ = Printer::print_pick(&repos, pick == Pick::Marked);
+        if args.strict && !scan_options.progress.skipped().is_empty() {
+            std::process::exit(EXIT_SKIPPED);
+        }
+    } else if args.check {
+        let (repos, errors, unscanned) = collect(&scan_options);
+        record_history(&args, &repos);
+        Printer::print_check_summary(&repos);
+        Printer::print_unscanned(&unscanned);
+        Printer::print_errors(&errors);
+        print_timings(&repos, &errors);
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
+        if repos.iter().any(|r| r.has_changes()) {
+            std::process::exit(EXIT_DIRTY);
+        } else if repos.iter().any(|r| r.upstream.as_ref().is_some_and(|u| u.ahead > 0)) {
+            std::process::exit(EXIT_AHEAD);
+        }
+    } else if args.branches {
+        let (repos, errors, unscanned) = collect(&scan_options);
+        Printer::print_branches(&repos);
+        Printer::print_unscanned(&unscanned);
+        Printer::print_errors(&errors);
+        print_timings(&repos, &errors);
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
+    } else if args.pull {
+        cancel_on_ctrl_c(&scan_options.cancel);
+        let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+        let results = Pull::run_all(&repos, &scan_options.cancel);
+        Printer::print_exec_results(&results);
+        Printer::print_errors(&errors);
+        exit_if_cancelled(&scan_options.cancel);
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
+        if results.iter().any(|r| !r.success) {
+            std::process::exit(1);
+        }
+    } else if args.push {
+        if !args.yes && !io::stdin().is_terminal() {
+            eprintln!("--push asks before every push, add --yes to push without asking");
+            std::process::exit(2);
+        }
+        cancel_on_ctrl_c(&scan_options.cancel);
+        let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+        let (results, skipped) = Push::run_all(&repos, |repo| args.yes || Push::ask(repo), &scan_options.cancel);
+        Printer::print_exec_results(&results);
+        if !skipped.is_empty() {
+            println!("{} skipped: {}", skipped.len(), skipped.join(", "));
+        }
+        Printer::print_errors(&errors);
+        exit_if_cancelled(&scan_options.cancel);
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
+        if results.iter().any(|r| !r.success) {
+            std::process::exit(1);
+        }
+    } else if args.prune_gone {
+        cancel_on_ctrl_c(&scan_options.cancel);
+        let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+        let results = Prune::run_all(&repos, &scan_options.cancel);
+        Printer::print_exec_results(&results);
+        Printer::print_errors(&errors);
+        exit_if_cancelled(&scan_options.cancel);
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
+        if results.iter().any(|r| !r.success) {
+            std::process::exit(1);
+        }
+    } else if args.format == Format::Json {
+        let (repos, errors, unscanned) = collect(&scan_options);
+        record_history(&args, &repos);
+        Printer::print_json(&repos);
+        for error in &errors {
+            eprintln!("error: {}", error);
+        }
+        // stdout stays valid JSON
+        if !unscanned.is_empty() {
+            eprintln!("partial: {} repos not read in time: {}", unscanned.len(), unscanned.join(", "));
+        }
+        if let Some(count) = args.timings {
+            for line in Printer::timings(&repos, &errors, count) {
+                eprintln!("{}", line);
+            }
+        }
+        report_skipped(&scan_options.progress.skipped(), args.strict, true);
+    } else if args.format == Format::Ndjson {
+        cancel_on_ctrl_c(&scan_options.cancel);
+        let deadline = args.quick.map(|budget| tokio::time::Instant::from_std(started + budget));
+        let mut stream = pin!(repolice_core::scan_all(exec_paths.clone(), scan_options.clone()).merge(SshTarget::scan_all(&args.ssh, &scan_options)));
+        let mut repos = Vec::new();
+        let mut errors = Vec::new();
+        // in the order repos are read, --sort can't apply without waiting for all of them
+        let finished = loop {
+            let next = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        eprintln!("partial: out of time after {} repos, the others were not read", repos.len());
+                        break false;
+                    }
+                },
+                None => stream.next().await,
+            };
+            match next {
+                Some(Ok(repo)) => {
+                    // nobody reads anymore, e.g. `| head`
+                    if Printer::print_json_line(&repo).is_err() {
+                        break false;
+                    }
+                    repos.push(repo);
+                }
+                Some(Err(e)) => {
+                    eprintln!("error: {}", e);
+                    errors.push(e);
+                }
+                None => break true,
+            }
+        };
+        save_cache();
+        record_history(&args, &repos);
+        if let Some(count) = args.timings {
+            for line in Printer::timings(&repos, &errors, count) {
+                eprintln!("{}", line);
+            }
+        }
+        report_skipped(&scan_options.progress.skipped(), args.strict, true);
+        if !finished {
+            // repos still being read would hold up the exit until they are done
+            std::process::exit(0);
+        }
+        exit_if_cancelled(&scan_options.cancel);
+    } else if exec_no_tui {
+        // grouping and --quick need every repo first, and so does the sort order
+        let streamed = !args.sorted && args.group_by.is_none() && args.quick.is_none();
+        let (repos, errors, unscanned) = if streamed {
+            let mut stream = pin!(repolice_core::scan_all(exec_paths.clone(), scan_options.clone()).merge(SshTarget::scan_all(&args.ssh, &scan_options)));
+            let (mut repos, mut errors) = (Vec::new(), Vec::new());
+            while let Some(next) = stream.next().await {
+                match next {
+                    Ok(repo) => {
+                        Printer::print_streamed(&repo, exec_verbose);
+                        repos.push(repo);
+                    }
+                    Err(e) => errors.push(e),
+                }
+            }
+            save_cache();
+            (repos, errors, Vec::new())
+        } else {
+            collect(&scan_options)
+        };
+        record_history(&args, &repos);
+        let violations = if profile.policy { Policy::check(&repos, &config.policy) } else { Vec::new() };
+        let migrations = match State::default_path().filter(|_| !args.no_history) {
+            Some(state_path) => {
+                let mut state = State::load(&state_path);
+                let migrations = exec_paths.iter().flat_map(|root| state.update(root, &repos)).collect();
+                if let Err(e) = state.save(&state_path) {
+                    eprintln!("{}", e);
+                }
+                migrations
+            }
+            None => Vec::new(),
+        };
+        let timings = args.timings.map(|count| Printer::timings(&repos, &errors, count)).unwrap_or_default();
+        if streamed {
+            Printer::finish_streamed(repos, exec_verbose, args.show_clean, scan_options.sort);
+        } else {
+            Printer::print_repos(repos, exec_verbose, args.show_clean, args.group_by);
+        }
+        Printer::print_migrations(&migrations);
+        if profile.policy {
+            Printer::print_violations(&violations);
+        }
+        Printer::print_unscanned(&unscanned);
+        Printer::print_errors(&errors);
+        for line in timings {
+            println!("{}", line);
+        }
+        report_skipped(&scan_options.progress.skipped(), args.strict, false);
     } else {
-        let repo_stream = Reader::stream_repos(exec_path.clone(), exec_verbose, exec_depth).await;
-        match tui::run_streaming_tui(repo_stream, exec_verbose).await {
-            Ok(_) => {},
+        // started without a path where there are no repos, an empty grid would only leave the user guessing
+        if args.path.is_empty() && profile.paths.is_empty() && args.ssh.is_empty() && io::stdout().is_terminal() && !Reader::has_repos_in(&exec_paths, &scan_options) {
+            match tui::picker::pick(&exec_paths[0], theme, basic_tui) {
+                Ok(Some(dir)) => exec_paths = vec![dir],
+                Ok(None) => return,
+                Err(e) => eprintln!("Could not show the directory picker: {}", e),
+            }
+        }
+        // the TUI reads every repo and narrows them by state itself, so the state can be changed with a key
+        let state_filter = scan_options.filters.iter().rfind(|filter| filter.is_state()).cloned();
+        scan_options.filters.retain(|filter| !filter.is_state());
+        let scan = |cancel| {
+            let options = ScanOptions { cancel, ..scan_options.clone() };
+            repolice_core::scan_all(exec_paths.clone(), options.clone()).merge(SshTarget::scan_all(&args.ssh, &options))
+        };
+        let tui_options = TuiOptions {
+            verbose: exec_verbose,
+            commit_config: config.commit.clone(),
+            policy_config: config.policy.clone(),
+            clean_config: config.clean.clone(),
+            tools_config: config.tools.clone(),
+            shortcuts: config.shortcuts.clone(),
+            basic: basic_tui,
+            theme,
+            scan_options: scan_options.clone(),
+            watch: args.watch.map(Duration::from_secs),
+            group_by: args.group_by,
+            remote_status: args.remote_status.then(|| RemoteClient::new(&config.remote_status)),
+            state_filter,
+            resume: args.resume,
+            sort_given: args.sort.is_some() || profile.sort.is_some(),
+        };
+        match tui::run_streaming_tui(scan, tui_options).await {
+            Ok(_) => {
+                save_cache();
+                // the TUI lists them itself, afterwards they only matter to --strict
+                if args.strict {
+                    report_skipped(&scan_options.progress.skipped(), true, false);
+                }
+            }
             Err(_) => {
                 println!("TUI failed, falling back to printed output...");
-                let repos: Vec<RepoInfo> = Reader::collect_repos(Reader::get_repos(exec_path), exec_verbose, exec_depth);
-                Printer::print_repos(repos, exec_verbose);
+                let (repos, errors) = Reader::collect_repos(Reader::get_repos_in(&exec_paths, &scan_options), &scan_options);
+                Printer::print_repos(repos, exec_verbose, args.show_clean, args.group_by);
+                Printer::print_errors(&errors);
+                report_skipped(&scan_options.progress.skipped(), args.strict, false);
             }
         }
     }
@@ -0,0 +1,141 @@
+use crate::config::home_dir;
+use repolice_core::{now, RepoInfo};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Notes and acknowledgements attached to repos in the TUI, kept in `~/.local/share/repolice/notes.toml`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Notes {
+    /// keyed by the canonical path of the repo
+    pub repos: BTreeMap<String, RepoNote>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RepoNote {
+    pub text: Option<String>,
+    pub acknowledged: Option<Acknowledged>,
+    /// listed first and shown whatever the filters
+    pub pinned: bool,
+}
+
+/// The repo was looked at and is fine as it is, until it changes or the time is up
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Acknowledged {
+    /// seconds since the unix epoch, none to last until the repo changes
+    pub until: Option<i64>,
+    /// what the repo looked like, see [`Notes::fingerprint`]
+    pub fingerprint: String,
+}
+
+impl Notes {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_DATA_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".local").join("share"),
+        };
+        Some(base.join("repolice").join("notes.toml"))
+    }
+
+    /// A missing file has no notes, an unreadable one a warning and no notes
+    pub fn load(path: &Path) -> Notes {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Notes::default();
+        };
+        match toml::from_str(&contents) {
+            Ok(notes) => notes,
+            Err(e) => {
+                eprintln!("Invalid notes {}: {}", path.display(), e);
+                Notes::default()
+            }
+        }
+    }
+
+    /// Writes the notes, dropping acknowledgements that ran out and repos left with nothing
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        let now = now();
+        for note in self.repos.values_mut() {
+            if note.acknowledged.as_ref().is_some_and(|ack| ack.until.is_some_and(|until| until <= now)) {
+                note.acknowledged = None;
+            }
+        }
+        self.repos.retain(|_, note| note.text.is_some() || note.acknowledged.is_some() || note.pinned);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, repo_path: &str) -> Option<&RepoNote> {
+        self.repos.get(&key(repo_path))
+    }
+
+    /// The note on a repo, none when it has none
+    pub fn text(&self, repo_path: &str) -> Option<&str> {
+        self.get(repo_path)?.text.as_deref()
+    }
+
+    /// Replaces the note on a repo, an empty text removes it
+    pub fn set_text(&mut self, repo_path: &str, text: &str) {
+        let text = text.trim();
+        self.repos.entry(key(repo_path)).or_default().text = (!text.is_empty()).then(|| text.to_string());
+    }
+
+    /// Whether the repo was acknowledged, the time is not up yet and it didn't change since
+    pub fn is_acknowledged(&self, repo: &RepoInfo) -> bool {
+        self.get(&repo.path)
+            .and_then(|note| note.acknowledged.as_ref())
+            .is_some_and(|ack| ack.until.is_none_or(|until| until > now()) && ack.fingerprint == Self::fingerprint(repo))
+    }
+
+    /// Acknowledges the repo as it is now, for `days` or until it changes
+    pub fn acknowledge(&mut self, repo: &RepoInfo, days: Option<u32>) {
+        let until = days.map(|days| now() + i64::from(days) * 86400);
+        self.repos.entry(key(&repo.path)).or_default().acknowledged = Some(Acknowledged { until, fingerprint: Self::fingerprint(repo) });
+    }
+
+    pub fn unacknowledge(&mut self, repo_path: &str) {
+        if let Some(note) = self.repos.get_mut(&key(repo_path)) {
+            note.acknowledged = None;
+        }
+    }
+
+    /// Pins or unpins a repo, returning whether it is pinned now
+    pub fn toggle_pinned(&mut self, repo_path: &str) -> bool {
+        let note = self.repos.entry(key(repo_path)).or_default();
+        note.pinned = !note.pinned;
+        note.pinned
+    }
+
+    /// Canonical paths of the pinned repos, as [`ScanOptions::pinned`](repolice_core::ScanOptions::pinned) takes them
+    pub fn pinned(&self) -> Vec<PathBuf> {
+        self.repos.iter().filter(|(_, note)| note.pinned).map(|(path, _)| PathBuf::from(path)).collect()
+    }
+
+    /// When an acknowledgement runs out, none for one that lasts until the repo changes
+    pub fn acknowledged_until(&self, repo_path: &str) -> Option<i64> {
+        self.get(repo_path)?.acknowledged.as_ref()?.until
+    }
+
+    /// What has to stay the same for an acknowledgement to hold: HEAD, the number of changes per kind,
+    /// the distance to the upstream, an operation in progress and changes in submodules and worktrees
+    fn fingerprint(repo: &RepoInfo) -> String {
+        let head = repo.last_commit.as_ref().map_or("unborn", |commit| commit.short_id.as_str());
+        let changes: Vec<String> = repo.trackers().iter().map(|tracker| tracker.amount.to_string()).collect();
+        let upstream = repo.upstream.as_ref().map_or(String::new(), |upstream| format!("{}/{}", upstream.ahead, upstream.behind));
+        format!("{} {} {} {} {} {} {}", head, repo.branch, changes.join(","), upstream, repo.repo_state.describe(),
+            repo.submodules_dirty(), repo.worktrees_dirty())
+    }
+}
+
+/// Notes follow the repo whatever path it was scanned by
+fn key(repo_path: &str) -> String {
+    Path::new(repo_path).canonicalize().map_or(repo_path.to_string(), |path| path.to_string_lossy().to_string())
+}
@@ -0,0 +1,289 @@
+use crate::config::{NotifyConfig, SmtpConfig};
+use crate::format::Format;
+use repolice_core::{host_name, now, RepoInfo, Totals};
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+use serde_json::json;
+
+/// Message sent when no `template` is configured
+const DEFAULT_TEMPLATE: &str = "repolice on {host}: {dirty} dirty, {ahead} ahead, {conflicted} conflicted\n{repos}";
+/// Discord refuses longer messages
+const DISCORD_LIMIT: usize = 2000;
+
+/// Shape of the body POSTed to the webhook
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WebhookFormat {
+    /// `{"text": ...}` for Slack incoming webhooks
+    Slack,
+    /// `{"content": ...}` for Discord webhooks
+    Discord,
+    /// the message along with the counts and every repo that needs attention
+    Json,
+}
+
+impl WebhookFormat {
+    pub fn parse(name: &str) -> Result<WebhookFormat, String> {
+        match name {
+            "slack" => Ok(WebhookFormat::Slack),
+            "discord" => Ok(WebhookFormat::Discord),
+            "json" => Ok(WebhookFormat::Json),
+            _ => Err(format!("unknown webhook format '{}', expected slack, discord or json", name)),
+        }
+    }
+}
+
+/// Sends the summary of a scan to the webhook and mailbox configured under `[notify]`, through `curl`.
+///
+/// The message comes from a template with the placeholders `{host}`, `{summary}` (the totals line of printed
+/// listings), `{dirty}`, `{ahead}`, `{conflicted}` and `{repos}`, a line per repo that needs attention.
+pub struct Notifier {
+    config: NotifyConfig,
+    format: WebhookFormat,
+}
+
+impl Notifier {
+    pub fn new(config: &NotifyConfig) -> Result<Notifier, String> {
+        let format = config.format.as_deref().map(WebhookFormat::parse).transpose()?.unwrap_or(WebhookFormat::Json);
+        Ok(Notifier { config: config.clone(), format })
+    }
+
+    /// Whether a webhook or a mailbox is configured
+    pub fn has_targets(&self) -> bool {
+        self.config.webhook.is_some() || self.config.smtp.is_some()
+    }
+
+    /// Repos worth a report: uncommitted changes, unpushed commits or conflicts
+    pub fn needs_attention(repo: &RepoInfo) -> bool {
+        repo.has_changes() || repo.has_conflicts() || repo.upstream.as_ref().is_some_and(|u| u.ahead > 0)
+    }
+
+    /// The message for a scan, `repos` being every repo read
+    pub fn message(&self, repos: &[RepoInfo]) -> String {
+        let totals = Totals::of(repos);
+        let lines: Vec<String> = repos.iter().filter(|repo| Self::needs_attention(repo)).map(Self::repo_line).collect();
+        let template = self.config.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+        template
+            .replace("{host}", &host_name())
+            .replace("{summary}", &totals.describe())
+            .replace("{dirty}", &totals.dirty.to_string())
+            .replace("{ahead}", &totals.ahead.to_string())
+            .replace("{conflicted}", &totals.conflicted.to_string())
+            .replace("{repos}", &lines.join("\n"))
+    }
+
+    /// e.g. "- api [main]: 3 changed, 2 ahead"
+    fn repo_line(repo: &RepoInfo) -> String {
+        let mut parts = Vec::new();
+        if repo.has_conflicts() {
+            parts.push(format!("{} conflicted", repo.conflicted_files.amount));
+        }
+        if repo.has_changes() {
+            parts.push(format!("{} changed", repo.total_changes()));
+        }
+        if let Some(upstream) = repo.upstream.as_ref().filter(|u| u.ahead > 0) {
+            parts.push(format!("{} ahead", upstream.ahead));
+        }
+        format!("- {} [{}]: {}", repo.name, repo.branch, parts.join(", "))
+    }
+
+    /// The JSON body for the webhook
+    pub fn payload(&self, message: &str, repos: &[RepoInfo]) -> String {
+        let payload = match self.format {
+            WebhookFormat::Slack => json!({ "text": message }),
+            WebhookFormat::Discord => json!({ "content": truncate(message, DISCORD_LIMIT) }),
+            WebhookFormat::Json => {
+                let attention: Vec<_> = repos.iter()
+                    .filter(|repo| Self::needs_attention(repo))
+                    .map(|repo| json!({
+                        "name": repo.name,
+                        "path": repo.path,
+                        "branch": repo.branch,
+                        "changes": repo.total_changes(),
+                        "conflicted": repo.conflicted_files.amount,
+                        "ahead": repo.upstream.as_ref().map_or(0, |u| u.ahead),
+                    }))
+                    .collect();
+                json!({ "text": message, "host": host_name(), "totals": Totals::of(repos), "repos": attention })
+            }
+        };
+        payload.to_string()
+    }
+
+    /// Sends the report to every target, with what happened per target
+    pub fn send(&self, repos: &[RepoInfo]) -> Vec<(String, Result<(), String>)> {
+        let message = self.message(repos);
+        let mut results = Vec::new();
+        if let Some(webhook) = &self.config.webhook {
+            let payload = self.payload(&message, repos);
+            let options = [("url", Some(webhook.as_str())), ("header", Some("Content-Type: application/json")), ("data-binary", Some(payload.as_str()))];
+            results.push(("the webhook".to_string(), curl(&options)));
+        }
+        if let Some(smtp) = &self.config.smtp {
+            results.push((smtp.to.join(", "), Self::mail(smtp, &message)));
+        }
+        results
+    }
+
+    /// The mail as curl uploads it, headers included. Servers and spam filters reject or mark mail without a date
+    /// and an id, which curl doesn't add.
+    fn mail_text(smtp: &SmtpConfig, message: &str, time: i64) -> String {
+        let subject = smtp.subject.as_deref().unwrap_or("repolice report");
+        format!("Date: {}\r\nMessage-ID: <repolice.{}.{}@{}>\r\nFrom: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            Format::rfc2822(time), time, process::id(), host_name(), smtp.from, smtp.to.join(", "), subject, message.replace('\n', "\r\n"))
+    }
+
+    fn mail(smtp: &SmtpConfig, message: &str) -> Result<(), String> {
+        let mail = Self::mail_text(smtp, message, now());
+        // curl reads its options from stdin, so the mail goes through a file
+        let file = env::temp_dir().join(format!("repolice-report-{}.eml", process::id()));
+        write_private(&file, &mail).map_err(|e| format!("Could not write {}: {}", file.display(), e))?;
+
+        let password = smtp.password.clone().or_else(|| env::var("REPOLICE_SMTP_PASSWORD").ok());
+        let user = smtp.user.as_ref().map(|user| format!("{}:{}", user, password.unwrap_or_default()));
+        let file_name = file.to_string_lossy().to_string();
+        // curl greets the server with the URL's path, and without one it appends the file's name
+        let url = match smtp.url.split_once("://") {
+            Some((_, rest)) if !rest.trim_end_matches('/').contains('/') => format!("{}/{}", smtp.url.trim_end_matches('/'), host_name()),
+            _ => smtp.url.clone(),
+        };
+        let mut options = vec![("url", Some(url.as_str())), ("mail-from", Some(smtp.from.as_str())), ("upload-file", Some(file_name.as_str()))];
+        options.extend(smtp.to.iter().map(|to| ("mail-rcpt", Some(to.as_str()))));
+        if let Some(user) = &user {
+            options.push(("user", Some(user.as_str())));
+        }
+        if smtp.starttls {
+            options.push(("ssl-reqd", None));
+        }
+        let result = curl(&options);
+        let _ = fs::remove_file(&file);
+        result
+    }
+}
+
+/// Writes `contents` to a new file only the user can read. A file already at `path`, or a link placed there by
+/// someone else sharing the temp directory, is an error rather than written through.
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let result = options.open(path)?.write_all(contents.as_bytes());
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// Runs curl with `options` given as a config on stdin, so webhook URLs and passwords don't show up in the process list
+fn curl(options: &[(&str, Option<&str>)]) -> Result<(), String> {
+    let config: String = options.iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("{} = \"{}\"\n", name, value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r")),
+            None => format!("{}\n", name),
+        })
+        .collect();
+    let mut child = Command::new("curl")
+        .args(["--config", "-", "--silent", "--show-error", "--fail", "--max-time", "30"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).map_err(|e| format!("Could not run curl: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("Could not run curl: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string())
+    }
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::repo;
+    use repolice_core::Upstream;
+
+    fn notifier(config: &str) -> Notifier {
+        Notifier::new(&toml::from_str(config).unwrap()).unwrap()
+    }
+
+    /// "clean", "api" with 3 changes and "web" with 2 unpushed commits
+    fn repos() -> Vec<RepoInfo> {
+        let mut api = repo("api");
+        api.modified_files.amount = 3;
+        let mut web = repo("web");
+        web.upstream = Some(Upstream { name: "origin/main".to_string(), ahead: 2, behind: 0 });
+        vec![repo("clean"), api, web]
+    }
+
+    #[test]
+    fn reads_its_targets_from_the_config() {
+        assert!(!notifier("").has_targets());
+        let notifier = notifier(r#"
+            webhook = "https://hooks.example.com/T0"
+            format = "slack"
+            [smtp]
+            url = "smtps://smtp.example.com"
+            from = "repolice@example.com"
+            to = ["ops@example.com"]
+        "#);
+        assert!(notifier.has_targets() && notifier.format == WebhookFormat::Slack);
+        assert!(!notifier.config.smtp.unwrap().starttls);
+
+        let config = toml::from_str(r#"format = "teams""#).unwrap();
+        assert_eq!(Notifier::new(&config).err().unwrap(), "unknown webhook format 'teams', expected slack, discord or json");
+    }
+
+    #[test]
+    fn reports_the_repos_that_need_attention() {
+        let notifier = notifier(r#"template = "{dirty} dirty, {ahead} ahead, {conflicted} conflicted\n{repos}""#);
+        assert_eq!(notifier.message(&repos()), "1 dirty, 1 ahead, 0 conflicted\n- api [main]: 3 changed\n- web [main]: 2 ahead");
+
+        let payload: serde_json::Value = serde_json::from_str(&notifier.payload("report", &repos())).unwrap();
+        assert_eq!(payload["text"], "report");
+        assert_eq!(payload["repos"].as_array().unwrap().iter().map(|repo| &repo["name"]).collect::<Vec<_>>(), ["api", "web"]);
+        assert_eq!((&payload["repos"][0]["changes"], &payload["repos"][1]["ahead"]), (&json!(3), &json!(2)));
+    }
+
+    #[test]
+    fn shortens_messages_for_discord() {
+        let notifier = notifier(r#"format = "discord""#);
+        let payload: serde_json::Value = serde_json::from_str(&notifier.payload(&"é".repeat(3000), &[])).unwrap();
+        let content = payload["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), DISCORD_LIMIT);
+        assert!(content.ends_with("é…"));
+    }
+
+    #[test]
+    fn builds_mails_servers_accept() {
+        let smtp: SmtpConfig = toml::from_str(r#"
+            url = "smtp://localhost"
+            from = "repolice@example.com"
+            to = ["ops@example.com", "dev@example.com"]
+        "#).unwrap();
+        let mail = Notifier::mail_text(&smtp, "2 dirty\n- api", 1714572189);
+        let (head, body) = mail.split_once("\r\n\r\n").unwrap();
+        let headers: Vec<&str> = head.split("\r\n").collect();
+        assert_eq!(headers[0], "Date: Wed, 01 May 2024 14:03:09 +0000");
+        assert_eq!(headers[1], format!("Message-ID: <repolice.1714572189.{}@{}>", process::id(), host_name()));
+        assert_eq!(headers[2..], ["From: repolice@example.com", "To: ops@example.com, dev@example.com", "Subject: repolice report",
+            "Content-Type: text/plain; charset=utf-8"]);
+        assert_eq!(body, "2 dirty\r\n- api\r\n");
+    }
+}
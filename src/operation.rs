@@ -0,0 +1,63 @@
+use repolice_core::RepoState;
+
+use std::process::{Command, Stdio};
+
+/// What to do with a merge, rebase or similar that stopped halfway
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Step {
+    Continue,
+    Abort,
+}
+
+impl Step {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Step::Continue => "continue",
+            Step::Abort => "abort",
+        }
+    }
+}
+
+/// Continues or aborts the operation a repo is in the middle of
+pub struct Operation {}
+
+impl Operation {
+    /// The git command for `step` in `state`, none when there is nothing to continue or abort
+    pub fn args(state: RepoState, step: Step) -> Option<[&'static str; 2]> {
+        let command = match state {
+            RepoState::Merging => "merge",
+            RepoState::Rebasing => "rebase",
+            RepoState::CherryPicking => "cherry-pick",
+            RepoState::Reverting => "revert",
+            RepoState::ApplyingMailbox => "am",
+            // a bisect is only ever ended, the good/bad steps are up to the user
+            RepoState::Bisecting if step == Step::Abort => return Some(["bisect", "reset"]),
+            _ => return None,
+        };
+        Some([command, if step == Step::Continue { "--continue" } else { "--abort" }])
+    }
+
+    /// Runs the step, accepting git's default commit messages instead of opening an editor.
+    /// Returns a one line summary or git's error, e.g. when a rebase stops on the next conflict.
+    pub fn run(path: &str, state: RepoState, step: Step) -> Result<String, String> {
+        let args = Self::args(state, step).ok_or(format!("nothing to {}", step.verb()))?;
+        let output = Command::new("git")
+            .arg("-C").arg(path)
+            .args(args)
+            .env("GIT_EDITOR", "true")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(format!("git {} done", args.join(" ")))
+        } else {
+            // git explains conflicts on stdout and everything else on stderr
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Err(stderr.lines().chain(stdout.lines())
+                .find(|l| !l.trim().is_empty() && !l.starts_with("hint:"))
+                .unwrap_or("git failed")
+                .to_string())
+        }
+    }
+}
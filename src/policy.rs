@@ -0,0 +1,135 @@
+use crate::commit::is_signed_off;
+use crate::config::PolicyConfig;
+use repolice_core::RepoInfo;
+
+use std::fs;
+use std::path::{Component, Path};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+
+/// A repo breaking one of the configured rules
+pub struct Violation {
+    pub repo: String,
+    pub path: String,
+    pub rule: String,
+    pub message: String,
+}
+
+/// Violations accepted as they are, so `repolice police` only fails on new ones
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Baseline {
+    pub accepted: Vec<Accepted>,
+}
+
+/// One accepted violation. Messages name commits and change with every push, so only the repo and rule count.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Accepted {
+    /// path of the repo relative to the scanned directory, with `/` on every platform
+    pub repo: String,
+    pub rule: String,
+}
+
+/// Violations checked against a baseline
+pub struct Comparison {
+    /// not in the baseline, these fail the check
+    pub new: Vec<Violation>,
+    /// in the baseline and still broken
+    pub known: usize,
+    /// in the baseline but no longer broken
+    pub fixed: Vec<Accepted>,
+}
+
+pub struct Policy {}
+
+impl Policy {
+    pub fn check(repos: &[RepoInfo], config: &PolicyConfig) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for repo in repos {
+            if config.requires_signoff(&repo.path) {
+                violations.extend(Self::check_signoff(repo));
+            }
+        }
+        violations
+    }
+
+    /// Every commit that isn't on a remote yet needs a Signed-off-by trailer
+    fn check_signoff(repo: &RepoInfo) -> Option<Violation> {
+        let output = Command::new("git")
+            .args(["-C", &repo.path, "log", "--format=%h %s%x00%B%x1e", "HEAD", "--not", "--remotes"])
+            .output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let log = String::from_utf8_lossy(&output.stdout);
+        let unsigned: Vec<&str> = log.split('\x1e')
+            .filter_map(|entry| entry.trim_start().split_once('\0'))
+            .filter(|(_, message)| !is_signed_off(message))
+            .map(|(summary, _)| summary)
+            .collect();
+        if unsigned.is_empty() {
+            return None;
+        }
+
+        Some(Violation {
+            repo: repo.name.clone(),
+            path: repo.path.clone(),
+            rule: "signoff".to_string(),
+            message: format!("{} unpushed commit(s) without Signed-off-by: {}", unsigned.len(), unsigned.join(", ")),
+        })
+    }
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Could not read baseline {}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("Invalid baseline {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Accepts every violation found in a scan of `root`
+    pub fn from_violations(violations: &[Violation], root: &Path) -> Baseline {
+        let mut accepted: Vec<Accepted> = violations.iter().map(|v| Self::entry(v, root)).collect();
+        accepted.sort();
+        accepted.dedup();
+        Baseline { accepted }
+    }
+
+    pub fn compare(&self, violations: Vec<Violation>, root: &Path) -> Comparison {
+        let mut seen: Vec<Accepted> = Vec::new();
+        let mut new = Vec::new();
+        for violation in violations {
+            let entry = Self::entry(&violation, root);
+            if self.accepted.contains(&entry) {
+                seen.push(entry);
+            } else {
+                new.push(violation);
+            }
+        }
+        let fixed = self.accepted.iter().filter(|entry| !seen.contains(entry)).cloned().collect();
+        Comparison { new, known: seen.len(), fixed }
+    }
+
+    /// Keyed by the path below `root` rather than the absolute path, so a baseline checked into a
+    /// shared directory matches on every machine
+    fn entry(violation: &Violation, root: &Path) -> Accepted {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let path = Path::new(&violation.path).canonicalize().unwrap_or_else(|_| violation.path.clone().into());
+        let relative = path.strip_prefix(&root).unwrap_or(&path);
+        let repo = relative.components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        // the scanned directory itself is a repo
+        let repo = if repo.is_empty() { ".".to_string() } else { repo };
+        Accepted { repo, rule: violation.rule.clone() }
+    }
+}
@@ -1,63 +1,664 @@
-use crate::reader::RepoInfo;
+use repolice_core::{now, Branch, IgnoredUsage, LatestTag, Level, Reader, RepoInfo, RepoState, RepoliceError, ScanProgress, SkippedDir, SortOrder, Totals, Upstream};
+use crate::release::ReleaseInfo;
+use crate::remote::CiState;
+use crate::policy::{Comparison, Violation};
+use crate::exec::ExecResult;
+use crate::clean::Batch;
+use crate::state::Migration;
+use crate::history::{RepoDiff, RepoHistory};
+use crate::stats::Stats;
+use crate::config::home_dir;
+use crate::group::GroupBy;
+use crate::theme::Theme;
+use crate::glyphs::Glyphs;
+use crate::format::Format;
+use crate::render::{self, Segment};
+use ratatui::style::Color;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
 
+/// Colors of printed output, dark until `Printer::use_theme` picks another
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Files listed per category in verbose listings, all until `Printer::use_max_files` limits them
+static MAX_FILES: OnceLock<usize> = OnceLock::new();
+
+/// characters in the bar of [`Printer::progress_line`]
+const PROGRESS_WIDTH: usize = 20;
 
 pub struct Printer {}
 
 impl Printer {
 
-    pub fn print_repos(repos: Vec<RepoInfo>, verbose: bool) {
+    /// Colors everything printed afterwards with `theme`, only the first call counts
+    pub fn use_theme(theme: Theme) {
+        let _ = THEME.set(theme);
+    }
+
+    /// Lists at most `count` files per category in verbose listings afterwards, 0 lists them all.
+    /// Only the first call counts.
+    pub fn use_max_files(count: usize) {
+        let _ = MAX_FILES.set(if count == 0 { usize::MAX } else { count });
+    }
+
+    fn max_files() -> usize {
+        MAX_FILES.get().copied().unwrap_or(usize::MAX)
+    }
+
+    /// Lists every repo followed by the totals, under a header per group when grouped.
+    /// `show_clean` adds the branch of each unchanged repo.
+    pub fn print_repos(repos: Vec<RepoInfo>, verbose: bool, show_clean: bool, group_by: Option<GroupBy>) {
+        let summary = Self::summary(&repos);
+        match group_by {
+            Some(group_by) => {
+                for (dir, repos) in group_by.group(repos) {
+                    println!("{}", Self::paint(&format!("{}/", Self::tilde(&dir)), Color::Cyan));
+                    Self::print_listing(&repos, verbose, show_clean);
+                }
+            }
+            None => Self::print_listing(&repos, verbose, show_clean),
+        }
+        for line in summary {
+            println!("{}", line);
+        }
+    }
+
+    /// Prints a repo right after it was read when it has changes, see [`Printer::finish_streamed`]
+    pub fn print_streamed(repo: &RepoInfo, verbose: bool) {
+        if repo.has_changes() {
+            Self::print_changed(repo, verbose);
+        }
+    }
+
+    /// Ends a listing printed with [`Printer::print_streamed`]: the unchanged repos, in `sort` order, and the totals
+    pub fn finish_streamed(mut repos: Vec<RepoInfo>, verbose: bool, show_clean: bool, sort: SortOrder) {
+        sort.sort(&mut repos);
+        Self::print_unchanged(&repos, verbose, show_clean);
+        for line in Self::summary(&repos) {
+            println!("{}", line);
+        }
+    }
+
+    /// Repos without a remote, disk usage and the totals, printed below the listing
+    fn summary(repos: &[RepoInfo]) -> Vec<String> {
+        let mut lines = Vec::new();
+        // sub-path entries share their repo's remotes
+        let local_only: Vec<String> = repos.iter()
+            .filter(|repo| repo.local_only && !repo.is_subpath())
+            .map(|repo| match &repo.host {
+                Some(host) => format!("{}:{}", host, repo.name),
+                None => repo.name.clone(),
+            })
+            .collect();
+        if !local_only.is_empty() {
+            lines.push(format!("{} {}", Self::red("Local only repos (no remote):"), local_only.join(", ")));
+        }
+        let disk_usage = Self::disk_usage(repos);
+        if !disk_usage.is_empty() {
+            lines.push("Ignored files by size:".to_string());
+            lines.extend(disk_usage);
+        }
+        lines.push(Totals::of(repos).describe());
+        lines
+    }
+
+    /// A line per repo with ignored files, the most space taken first. Sub-path entries share their repo's.
+    fn disk_usage(repos: &[RepoInfo]) -> Vec<String> {
+        let mut usage: Vec<(&str, &IgnoredUsage)> = repos.iter()
+            .filter(|repo| !repo.is_subpath())
+            .filter_map(|repo| repo.ignored.as_ref().filter(|ignored| ignored.total > 0).map(|ignored| (repo.name.as_str(), ignored)))
+            .collect();
+        usage.sort_by(|a, b| b.1.total.cmp(&a.1.total).then_with(|| a.0.cmp(b.0)));
+        usage.iter()
+            .map(|(name, ignored)| format!("{:>10}  {} ({})", Format::current().size(ignored.total), Self::paint(name, Color::Yellow), Self::largest_ignored(ignored)))
+            .collect()
+    }
+
+    /// The biggest ignored paths of a repo, e.g. "target/ 1.2 GiB, .env 120 B"
+    pub fn largest_ignored(ignored: &IgnoredUsage) -> String {
+        ignored.largest.iter()
+            .map(|(path, size)| format!("{} {}", path, Format::current().size(*size)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Repos with changes one by one, then the unchanged ones on a line
+    fn print_listing(repos: &[RepoInfo], verbose: bool, show_clean: bool) {
+        for repo in repos.iter().filter(|repo| repo.has_changes()) {
+            Self::print_changed(repo, verbose);
+        }
+        Self::print_unchanged(repos, verbose, show_clean);
+    }
+
+    fn print_changed(repo: &RepoInfo, verbose: bool) {
+        let state = if repo.repo_state != RepoState::Normal {
+            format!(" {}", Self::red(&format!("!{}!", repo.repo_state.describe().to_uppercase())))
+        } else {
+            String::new()
+        };
+        let notes: String = render::notes(repo).iter().map(|note| format!(" {}", Self::braced(note))).collect();
+        println!("| {}: {}{}{}", Self::paint_row(&render::title(repo)), Self::paint_row(&render::branch_row(repo)), state, notes);
+        if verbose {
+            if let Some(commit) = render::last_commit(repo) {
+                println!("| {}", Self::paint_row(&commit));
+            }
+            if let Some(tag) = &repo.latest_tag {
+                println!("| {}", Self::tag_summary(tag));
+            }
+        }
+        if repo.has_conflicts() {
+            // the count alone unless verbose, like on TUI cards
+            let shown = if verbose { Self::max_files() } else { 0 };
+            for row in render::files(&repo.conflicted_files, Color::Red, shown, usize::MAX).iter().take(if verbose { usize::MAX } else { 1 }) {
+                println!("| {}", Self::paint_row(row));
+            }
+        }
+        if let Some(staging) = render::staging(repo) {
+            println!("| {}", Self::paint_row(&staging));
+        }
+        if verbose {
+            Self::get_verbose_format(repo);
+        } else {
+            println!("| {}", Self::paint_row(&render::counts(repo)).trim_end());
+        }
+    }
+
+    /// The repos without changes on one line, with what else there is to know about them and their branch when `show_clean`
+    fn print_unchanged(repos: &[RepoInfo], verbose: bool, show_clean: bool) {
         let mut unchanged: Vec<String> = vec![];
-        for repo in repos {
-            if repo.has_changes() {
-                if verbose {
-                    println!("| {}: [{}]", repo.name, repo.branch);
-                    Self::get_verbose_format(repo);
-                } else {
-                    println!("| {}: [{}]", repo.name, repo.branch);
-                    println!("| ?{} | +{} | ~{} | -{} |", 
-                        repo.new_files.amount, 
-                        repo.added_files.amount, 
-                        repo.modified_files.amount, 
-                        repo.deleted_files.amount);
-                } 
+        for repo in repos.iter().filter(|repo| !repo.has_changes()) {
+            let mut notes = Vec::new();
+            if repo.repo_state != RepoState::Normal {
+                notes.push(Self::red(repo.repo_state.describe()));
+            }
+            notes.extend(render::markers(repo).iter().map(|marker| Self::paint_segment(marker).trim_start().to_string()));
+            if verbose && let Some(tag) = &repo.latest_tag {
+                notes.push(tag.describe());
+            }
+            notes.extend(render::notes(repo).iter().map(Self::paint_segment));
+            let pin = if repo.pinned { format!("{} ", Self::paint(Glyphs::current().pin(), Color::Cyan)) } else { String::new() };
+            let host = repo.host.as_ref().map_or(String::new(), |host| Self::paint(&format!("{}:", host), Color::Blue));
+            let branch = if show_clean { format!(" {}", Self::paint_segment(&render::branch(repo))) } else { String::new() };
+            if notes.is_empty() {
+                unchanged.push(format!("{}{}{}{}", pin, host, Self::paint(&repo.name, Color::Green), branch));
             } else {
-                unchanged.push(repo.name);
+                unchanged.push(format!("{}{}{}{} ({})", pin, host, Self::paint(&repo.name, Color::Green), branch, notes.join(", ")));
             }
         }
-        if unchanged.len() >= 1usize {
+        if !unchanged.is_empty() {
             let joined: String = unchanged.iter().map(|s| format!(", {}", s)).collect();
             let joined = joined.trim_start_matches(", ").to_string();
             println!("Unchanged repos: {}", joined)
         }
     }
 
-    fn get_verbose_format(repo: RepoInfo) {
-        // print new, added, modified, and deleted only if there are matches
-        if repo.has_changes() { 
-            if repo.new_files.files.is_some() {
-                println!("New");
-                Self::formatted_list(&repo.new_files.files.unwrap());
+    /// Local branches of every repo, sub-path entries are covered by their repo
+    pub fn print_branches(repos: &[RepoInfo]) {
+        for repo in repos.iter().filter(|r| !r.is_subpath()) {
+            println!("| {}", repo.name);
+            let branches = match Reader::branches(&repo.path) {
+                Ok(branches) => branches,
+                Err(e) => {
+                    println!("|   {}", Self::red(&e.reason()));
+                    continue;
+                }
+            };
+            for branch in branches {
+                let marker = if branch.current { "*" } else { " " };
+                let state = Self::branch_state(&branch);
+                let state = if branch.is_local_only() && branch.unpushed > 0 { Self::red(&state) } else { state };
+                println!("|   {} {} {} ({})", marker, branch.name, state, Format::current().time(branch.time));
             }
-            if repo.added_files.files.is_some() {
-                println!("Added");
-                Self::formatted_list(&repo.added_files.files.unwrap());
+        }
+    }
+
+    /// Where a branch stands: "origin/main ↑1", "upstream gone" once its remote branch was deleted,
+    /// or "local only, 3 unpushed" when it has no upstream
+    pub fn branch_state(branch: &Branch) -> String {
+        let local = if branch.gone { "upstream gone" } else { "local only" };
+        match &branch.upstream {
+            Some(upstream) => format!("{}{}", upstream.name, Self::ahead_behind(upstream)),
+            None if branch.unpushed > 0 => format!("{}, {} unpushed", local, branch.unpushed),
+            None => local.to_string(),
+        }
+    }
+
+    /// How far a scan got, redrawn on stderr while printed output is being collected
+    pub fn progress_line(progress: &ScanProgress, elapsed: Duration) -> String {
+        let found = progress.found();
+        if found == 0 {
+            return format!("looking for repos... ({:.1}s)", elapsed.as_secs_f64());
+        }
+        let read = progress.read().min(found);
+        let filled = read * PROGRESS_WIDTH / found;
+        let failed = match progress.failed() {
+            0 => String::new(),
+            failed => format!(", {} unreadable", failed),
+        };
+        format!("[{}{}] {}/{} repos read, {} dirty{} ({:.1}s)", "#".repeat(filled), "-".repeat(PROGRESS_WIDTH - filled),
+            read, found, progress.dirty(), failed, elapsed.as_secs_f64())
+    }
+
+    /// What a scan that showed progress came to, so the time it took isn't lost with the progress line
+    pub fn scan_summary(progress: &ScanProgress, elapsed: Duration) -> String {
+        format!("read {} repos in {:.1}s, {} dirty", progress.read(), elapsed.as_secs_f64(), progress.dirty())
+    }
+
+    /// One line per problem kind, for scripts that mostly care about the exit code
+    pub fn print_check_summary(repos: &[RepoInfo]) {
+        let dirty: Vec<String> = repos.iter()
+            .filter(|r| r.has_changes())
+            .map(|r| format!("{} ({})", r.name, r.total_changes()))
+            .collect();
+        let ahead: Vec<String> = repos.iter()
+            .filter_map(|r| r.upstream.as_ref().filter(|u| u.ahead > 0).map(|u| format!("{} ({}{})", r.name, Glyphs::current().ahead(), u.ahead)))
+            .collect();
+        if !dirty.is_empty() {
+            println!("uncommitted: {}", dirty.join(", "));
+        }
+        if !ahead.is_empty() {
+            println!("unpushed: {}", ahead.join(", "));
+        }
+        println!("{} repos, {} with uncommitted changes, {} with unpushed commits", repos.len(), dirty.len(), ahead.len());
+    }
+
+    pub fn print_release_report(report: Vec<ReleaseInfo>, verbose: bool) {
+        let mut up_to_date: Vec<String> = vec![];
+        for info in report {
+            if info.unreleased.is_empty() {
+                up_to_date.push(info.repo.name);
+                continue;
             }
-            if repo.modified_files.files.is_some() {
-                println!("Modified");
-                Self::formatted_list(&repo.modified_files.files.unwrap());
+            let base = info.base.as_deref().unwrap_or("no tag");
+            let verdict = if info.is_ready() {
+                "ready".to_string()
+            } else if info.repo.has_conflicts() {
+                Self::red("blocked: conflicts")
+            } else if info.repo.has_changes() {
+                Self::red(&format!("blocked: {} uncommitted", info.repo.total_changes()))
+            } else {
+                // by the failed CI run that follows
+                Self::red("blocked")
+            };
+            let ci = match &info.ci {
+                Ok(Some(CiState::Failure)) => format!(" | {}", Self::red("CI failed")),
+                Ok(Some(state)) => format!(" | CI {}", state.describe()),
+                Ok(None) => String::new(),
+                Err(e) => format!(" | CI unknown: {}", e),
+            };
+            println!("| {}: [{}] {} unreleased since {} | {}{}",
+                info.repo.name, info.repo.branch, info.unreleased.len(), base, verdict, ci);
+            if verbose {
+                Self::formatted_list(&info.unreleased);
             }
-            if repo.deleted_files.files.is_some() {
-                println!("Deleted");
-                Self::formatted_list(&repo.deleted_files.files.unwrap());
+        }
+        if !up_to_date.is_empty() {
+            println!("Nothing to release: {}", up_to_date.join(", "));
+        }
+    }
+
+    pub fn print_stats(stats: &Stats) {
+        println!("{} repos, {} with uncommitted changes ({}%)", stats.repos, stats.dirty, stats.dirty_percent());
+        println!("unpushed commits: {}", stats.unpushed);
+        if let Some((repo, file, time)) = &stats.oldest_change {
+            println!("oldest uncommitted change: {} in {}, {}", file, repo, Format::date(*time));
+        }
+        if let Some((repo, size)) = &stats.largest {
+            println!("largest repo: {} ({})", repo, Format::current().size(*size));
+        }
+        let counts = |counts: &[(String, usize)]| counts.iter().map(|(label, n)| format!("{} {}", label, n)).collect::<Vec<_>>().join(", ");
+        println!("hosts: {}", counts(&stats.hosts));
+        println!("languages: {}", counts(&stats.languages));
+    }
+
+    /// Repos a time-boxed scan didn't get to, nothing when the scan is complete
+    pub fn print_unscanned(unscanned: &[String]) {
+        if !unscanned.is_empty() {
+            println!("{} {}", Self::red(&format!("partial: {} repos not read in time:", unscanned.len())), unscanned.join(", "));
+        }
+    }
+
+    /// The `count` repos that took longest to read, slowest first, after those that timed out.
+    /// Sub-path entries are read with their repo and left out.
+    pub fn timings(repos: &[RepoInfo], errors: &[RepoliceError], count: usize) -> Vec<String> {
+        let mut read: Vec<&RepoInfo> = repos.iter().filter(|repo| !repo.is_subpath()).collect();
+        read.sort_by(|a, b| b.read_millis.cmp(&a.read_millis).then_with(|| a.name.cmp(&b.name)));
+        let total: u64 = read.iter().map(|repo| repo.read_millis).sum();
+        let timed_out: Vec<(&str, u64)> = errors.iter()
+            .filter_map(|error| match error {
+                RepoliceError::Timeout { path, secs } => Some((path.as_str(), *secs)),
+                _ => None,
+            })
+            .collect();
+
+        let shown = count.min(read.len());
+        let mut lines = vec![Self::paint(&format!("timings: slowest {} of {} repos, {} reading in total", shown, read.len(), Self::millis(total)), Color::Cyan)];
+        lines.extend(timed_out.iter().map(|(path, secs)| Self::red(&format!("{:>8}  {} (timed out)", format!(">{}s", secs), Self::tilde(path)))));
+        lines.extend(read.iter().take(shown).map(|repo| format!("{:>8}  {}", Self::millis(repo.read_millis), repo.name)));
+        lines
+    }
+
+    /// "180ms" below a second, "12.3s" from there on
+    fn millis(millis: u64) -> String {
+        if millis < 1000 { format!("{}ms", millis) } else { format!("{:.1}s", millis as f64 / 1000.0) }
+    }
+
+    /// Repos that couldn't be read, they are left out of everything above
+    pub fn print_errors(errors: &[RepoliceError]) {
+        if errors.is_empty() {
+            return;
+        }
+        println!("{}", Self::red(&format!("errors: {} repos could not be read", errors.len())));
+        for error in errors {
+            println!("| {}: {}", Self::tilde(error.path()), error.reason());
+        }
+    }
+
+    /// Directories the search for repos couldn't list, so a repo missing from the listing isn't a mystery
+    pub fn print_skipped(skipped: &[SkippedDir]) {
+        if skipped.is_empty() {
+            return;
+        }
+        println!("{}", Self::red(&format!("skipped: {} directories could not be searched", skipped.len())));
+        for dir in skipped {
+            println!("| {}: {}", Self::tilde(&dir.path), dir.reason);
+        }
+    }
+
+    pub fn print_migrations(migrations: &[Migration]) {
+        for migration in migrations {
+            match migration {
+                Migration::Missing { path, last_seen } => {
+                    println!("{} {}, last seen {}", Self::red("missing:"), Self::tilde(path), Format::date(*last_seen));
+                }
+                Migration::Moved { from, to } => println!("moved: {} -> {}", Self::tilde(from), Self::tilde(to)),
             }
-        } else {
-            println!("Nothing new!");
-        } 
+        }
+    }
+
+    /// How long one repo has been dirty, when it was last clean and how often scans found it dirty
+    pub fn print_repo_history(repo: &RepoHistory) {
+        println!("{}", Self::paint(&Self::tilde(&repo.path), Color::Yellow));
+        match repo.dirty_since {
+            Some(since) => println!("  {} for {}, since {} ({} files changed at the last scan)",
+                Self::red("dirty"), Self::span(now() - since), Format::date(since), repo.changes),
+            None => println!("  {} at the last scan", Self::paint("clean", Color::Green)),
+        }
+        match repo.last_clean {
+            Some(time) => println!("  last clean {}", Format::date(time)),
+            None => println!("  never clean since {}", Format::date(repo.first_seen)),
+        }
+        println!("  dirty in {} of {} scans from {} to {}", repo.dirty_scans, repo.scans, Format::date(repo.first_seen), Format::date(repo.last_seen));
     }
 
-    fn formatted_list(list: &Vec<String>) {
+    /// Repos that were dirty at their last scan, the longest dirty first
+    pub fn print_dirty_history(repos: &[RepoHistory]) {
+        if repos.is_empty() {
+            println!("No repo was dirty at its last scan");
+            return;
+        }
+        for repo in repos {
+            let since = repo.dirty_since.unwrap_or(repo.last_seen);
+            let last_clean = repo.last_clean.map_or("never clean".to_string(), |time| format!("last clean {}", Format::date(time)));
+            println!("{:>6}  {} ({} files changed, {})", Self::span(now() - since), Self::paint(&Self::tilde(&repo.path), Color::Yellow),
+                repo.changes, last_clean);
+        }
+    }
+
+    /// What changed since the last scan at `since`: repos that turned dirty or clean, have more or fewer changes, or are new
+    pub fn print_diff(since: Option<i64>, diffs: &[RepoDiff]) {
+        let Some(since) = since else {
+            println!("No earlier scan to compare with, this one is recorded for next time");
+            return;
+        };
+        let ago = Self::span(now() - since);
+        if diffs.is_empty() {
+            println!("Nothing changed since the last scan {} ago", ago);
+            return;
+        }
+        println!("Since the last scan {} ago:", ago);
+        for diff in diffs {
+            let (label, color, detail) = match (diff.before, diff.after) {
+                (None, 0) => ("new", Color::Blue, "clean".to_string()),
+                (None, after) => ("new", Color::Blue, format!("{} files changed", after)),
+                (Some(_), 0) => ("clean now", Color::Green, "all committed or discarded".to_string()),
+                (Some(0), after) => ("dirty now", Color::Yellow, format!("{} files changed", after)),
+                (Some(before), after) if after > before => ("more changes", Color::Yellow, format!("{} files changed, was {}", after, before)),
+                (Some(before), after) => ("fewer changes", Color::Cyan, format!("{} files changed, was {}", after, before)),
+            };
+            println!("{}  {} ({})", Self::paint(&format!("{:>13}", label), color), Self::tilde(&diff.path), detail);
+        }
+    }
+
+    /// A length of time like "45m", "5h" or "94d"
+    fn span(secs: i64) -> String {
+        match secs.max(0) {
+            s if s < 3600 => format!("{}m", s / 60),
+            s if s < 86400 => format!("{}h", s / 3600),
+            s => format!("{}d", s / 86400),
+        }
+    }
+
+    pub fn print_json(repos: &[RepoInfo]) {
+        match serde_json::to_string_pretty(repos) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Could not serialize repos: {}", e),
+        }
+    }
+
+    /// A repo as a single line of JSON, fails once stdout is closed
+    pub fn print_json_line(repo: &RepoInfo) -> io::Result<()> {
+        let json = serde_json::to_string(repo).map_err(io::Error::other)?;
+        writeln!(io::stdout(), "{}", json)
+    }
+
+    /// The path of each repo on a line of its own and nothing else, `marked` puts "* " before dirty ones and
+    /// two spaces before the others. Fails once stdout is closed.
+    pub fn print_pick(repos: &[RepoInfo], marked: bool) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        for repo in repos {
+            let mark = match (marked, repo.has_changes()) {
+                (false, _) => "",
+                (true, true) => "* ",
+                (true, false) => "  ",
+            };
+            writeln!(out, "{}{}", mark, repo.path)?;
+        }
+        Ok(())
+    }
+
+    pub fn print_exec_results(results: &[ExecResult]) {
+        let mut failed: Vec<&str> = vec![];
+        for result in results {
+            if result.success {
+                println!("| {}", result.repo);
+            } else {
+                println!("| {} {}", result.repo, Self::red("failed"));
+                failed.push(&result.repo);
+            }
+            for line in result.output.lines() {
+                println!("|   {}", line);
+            }
+        }
+        println!("{} ok, {} failed{}", results.len() - failed.len(), failed.len(),
+            if failed.is_empty() { String::new() } else { format!(": {}", failed.join(", ")) });
+    }
+
+    pub fn print_clean_result(repo: &str, result: Result<Vec<String>, String>) {
+        match result {
+            Ok(lines) => {
+                println!("| {}", repo);
+                for line in lines {
+                    println!("|   {}", line);
+                }
+            }
+            Err(e) => println!("| {} {}", repo, Self::red(&e)),
+        }
+    }
+
+    pub fn print_quarantine(batches: &[Batch]) {
+        if batches.is_empty() {
+            println!("Nothing quarantined");
+        }
+        for batch in batches {
+            println!("| {}: {} files from {}", batch.id, batch.manifest.files.len(), batch.manifest.repo);
+            for file in &batch.manifest.files {
+                println!("|   {}", file);
+            }
+        }
+    }
+
+    pub fn print_violations(violations: &[Violation]) {
+        for violation in violations {
+            println!("| {}: {} [{}]", violation.repo, Self::red(&violation.message), violation.rule);
+        }
+        if violations.is_empty() {
+            println!("No policy violations");
+        }
+    }
+
+    /// What the baseline hid, and which of its entries could be dropped
+    pub fn print_baseline(comparison: &Comparison, path: &Path) {
+        if comparison.known > 0 {
+            println!("{} known violations accepted in {}", comparison.known, Self::tilde(&path.to_string_lossy()));
+        }
+        if !comparison.fixed.is_empty() {
+            let fixed: Vec<String> = comparison.fixed.iter().map(|entry| format!("{} [{}]", entry.repo, entry.rule)).collect();
+            println!("fixed since the baseline: {}, they can be dropped from the baseline", fixed.join(", "));
+        }
+    }
+
+    /// "tag v1.2.0, 3 commits since", with lightweight tags marked as such
+    fn tag_summary(tag: &LatestTag) -> String {
+        let kind = if tag.annotated { "tag" } else { "lightweight tag" };
+        match tag.commits_since {
+            0 => format!("{} {}, nothing since", kind, tag.name),
+            1 => format!("{} {}, 1 commit since", kind, tag.name),
+            commits => format!("{} {}, {} commits since", kind, tag.name, commits),
+        }
+    }
+
+    /// Every changed file with whether it is staged, then submodules, worktrees and hygiene warnings
+    fn get_verbose_format(repo: &RepoInfo) {
+        for (tracker, color) in repo.trackers().into_iter().zip(render::TRACKER_COLORS).skip(1) {
+            if tracker.amount > 0 {
+                for row in render::files(tracker, color, Self::max_files(), usize::MAX) {
+                    println!("| {}", Self::paint_row(&row));
+                }
+            }
+        }
+        if !repo.submodules.is_empty() {
+            println!("| Submodules");
+            for submodule in &repo.submodules {
+                println!("| - {} ({})", submodule.path, submodule.describe());
+            }
+        }
+        if !repo.worktrees.is_empty() {
+            println!("| Worktrees");
+            for worktree in &repo.worktrees {
+                println!("| - {} ({})", Self::tilde(&worktree.path), worktree.describe());
+            }
+        }
+        if let Some(hygiene) = repo.hygiene.as_ref().filter(|hygiene| hygiene.warnings() > 0) {
+            println!("| {}", Self::paint("Hygiene", Color::Yellow));
+            for file in &hygiene.tracked_ignored {
+                println!("| - {} (tracked but ignored)", file);
+            }
+            for file in &hygiene.unignored {
+                println!("| - {} (not ignored)", file);
+            }
+        }
+    }
+
+    fn formatted_list(list: &[String]) {
         for item in list {
             println!("| _ {}", item);
         }
     }
+
+    /// "staged: 2, unstaged: 3", what a commit would take and what still needs adding
+    pub fn staging_summary(repo: &RepoInfo) -> String {
+        format!("staged: {}, unstaged: {}", repo.staged_changes(), repo.unstaged_changes())
+    }
+
+    /// " ↑2 ↓1" relative to the upstream, empty when both are in sync
+    pub fn ahead_behind(upstream: &Upstream) -> String {
+        let mut text = String::new();
+        if upstream.ahead > 0 {
+            text.push_str(&format!(" {}{}", Glyphs::current().ahead(), upstream.ahead));
+        }
+        if upstream.behind > 0 {
+            text.push_str(&format!(" {}{}", Glyphs::current().behind(), upstream.behind));
+        }
+        text
+    }
+
+    pub fn level_color(level: Level) -> Color {
+        match level {
+            Level::Warn => Color::Yellow,
+            Level::Critical => Color::Red,
+        }
+    }
+
+    /// "origin/main moved on by 42 commits" when the default branch has commits the checked out branch lacks
+    pub fn drift_note(repo: &RepoInfo) -> Option<String> {
+        match repo.default_branch.as_ref()? {
+            default if default.behind == 0 => None,
+            default if default.behind == 1 => Some(format!("{} moved on by 1 commit", default.name)),
+            default => Some(format!("{} moved on by {} commits", default.name, default.behind)),
+        }
+    }
+
+    /// "2 submodules changed" when any submodule is dirty or out of sync
+    pub fn submodule_note(repo: &RepoInfo) -> Option<String> {
+        match repo.submodules_dirty() {
+            0 => None,
+            1 => Some("1 submodule changed".to_string()),
+            n => Some(format!("{} submodules changed", n)),
+        }
+    }
+
+    /// "2 worktrees dirty" when any linked worktree has uncommitted work
+    pub fn worktree_note(repo: &RepoInfo) -> Option<String> {
+        match repo.worktrees_dirty() {
+            0 => None,
+            1 => Some("1 worktree dirty".to_string()),
+            n => Some(format!("{} worktrees dirty", n)),
+        }
+    }
+
+    /// `path` with the home directory shortened to "~"
+    pub fn tilde(path: &str) -> String {
+        match home_dir().map(|home| home.to_string_lossy().to_string()) {
+            Some(home) if path.starts_with(&home) => format!("~{}", &path[home.len()..]),
+            _ => path.to_string(),
+        }
+    }
+
+    /// `row` in its colors, see [`render::Row`]
+    fn paint_row(row: &[Segment]) -> String {
+        row.iter().map(Self::paint_segment).collect()
+    }
+
+    fn paint_segment(segment: &Segment) -> String {
+        match segment.color {
+            Some(color) => Self::paint(&segment.text, color),
+            None => segment.text.clone(),
+        }
+    }
+
+    /// A note as a "{2 submodules changed}" badge
+    fn braced(note: &Segment) -> String {
+        Self::paint_segment(&Segment { text: format!("{{{}}}", note.text), ..*note })
+    }
+
+    fn red(text: &str) -> String {
+        Self::paint(text, Color::Red)
+    }
+
+    fn paint(text: &str, color: Color) -> String {
+        THEME.get().copied().unwrap_or_default().paint(text, color)
+    }
 }
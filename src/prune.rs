@@ -0,0 +1,51 @@
+use crate::exec::ExecResult;
+use repolice_core::{CancellationToken, RepoInfo};
+
+use std::process::{Command, Stdio};
+
+pub struct Prune {}
+
+impl Prune {
+    /// Deletes the branches of every repo whose upstream is gone, one repo after the other until `cancel` is cancelled.
+    /// Like `git branch -d`, branches that aren't merged into the checked out one are kept.
+    pub fn run_all(repos: &[RepoInfo], cancel: &CancellationToken) -> Vec<ExecResult> {
+        repos.iter()
+            .filter(|repo| !repo.gone_branches.is_empty())
+            .take_while(|_| !cancel.is_cancelled())
+            .map(|repo| {
+                let mut success = true;
+                let lines: Vec<String> = repo.gone_branches.iter()
+                    .map(|branch| match Self::run(&repo.path, branch) {
+                        Ok(line) => line,
+                        Err(e) => {
+                            success = false;
+                            format!("{}: {}", branch, e)
+                        }
+                    })
+                    .collect();
+                ExecResult { repo: repo.name.clone(), success, output: lines.join("\n") }
+            })
+            .collect()
+    }
+
+    /// Runs `git branch -d` on `branch`, returning what became of it or git's error
+    fn run(path: &str, branch: &str) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(["-C", path, "branch", "-d", "--", branch])
+            // the reason a branch is kept is matched below
+            .env("LC_ALL", "C")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| e.to_string())?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() {
+            Ok(format!("deleted {}", branch))
+        } else if stderr.contains("not fully merged") {
+            Ok(format!("kept {}, not merged", branch))
+        } else if stderr.contains("checked out") || stderr.contains("cannot delete branch") {
+            Ok(format!("kept {}, checked out", branch))
+        } else {
+            Err(stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("git branch -d failed").trim_start_matches("error: ").to_string())
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::exec::ExecResult;
+use repolice_core::{CancellationToken, RepoInfo};
+
+use std::process::{Command, Stdio};
+
+pub struct Pull {}
+
+impl Pull {
+    /// Repos that can be fast-forwarded safely: nothing local to lose and nothing local to merge
+    pub fn is_candidate(repo: &RepoInfo) -> bool {
+        !repo.has_changes() && repo.upstream.as_ref().is_some_and(|u| u.behind > 0 && u.ahead == 0)
+    }
+
+    /// Fast-forward pulls every candidate, one repo after the other until `cancel` is cancelled
+    pub fn run_all(repos: &[RepoInfo], cancel: &CancellationToken) -> Vec<ExecResult> {
+        repos.iter()
+            .filter(|repo| Self::is_candidate(repo))
+            .take_while(|_| !cancel.is_cancelled())
+            .map(|repo| {
+                let (success, output) = match Self::run(&repo.path) {
+                    Ok(summary) => (true, summary),
+                    Err(e) => (false, e),
+                };
+                ExecResult { repo: repo.name.clone(), success, output }
+            })
+            .collect()
+    }
+
+    /// Runs `git pull --ff-only`, returning a one line summary or git's error
+    pub fn run(path: &str) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(["-C", path, "pull", "--ff-only", "--quiet"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok("fast-forwarded".to_string())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("git pull failed").to_string())
+        }
+    }
+}
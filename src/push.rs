@@ -0,0 +1,79 @@
+use crate::exec::ExecResult;
+use repolice_core::{CancellationToken, RepoInfo};
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+pub struct Push {}
+
+impl Push {
+    /// Repos whose branch is strictly ahead of its upstream, so a push is a fast-forward
+    pub fn is_candidate(repo: &RepoInfo) -> bool {
+        repo.upstream.as_ref().is_some_and(|u| u.ahead > 0 && u.behind == 0)
+    }
+
+    /// Pushes every candidate that `confirm` agrees to, one repo after the other until `cancel` is cancelled.
+    /// Returns what happened to each pushed repo and the names of the repos that were skipped.
+    pub fn run_all(repos: &[RepoInfo], mut confirm: impl FnMut(&RepoInfo) -> bool, cancel: &CancellationToken) -> (Vec<ExecResult>, Vec<String>) {
+        let mut results = Vec::new();
+        let mut skipped = Vec::new();
+        // sub-path entries share their repo's branch, it is pushed once
+        for repo in repos.iter().filter(|repo| !repo.is_subpath() && Self::is_candidate(repo)) {
+            if cancel.is_cancelled() {
+                break;
+            }
+            if !confirm(repo) {
+                skipped.push(repo.name.clone());
+                continue;
+            }
+            let (success, output) = match Self::run(&repo.path, &repo.branch) {
+                Ok(summary) => (true, summary),
+                Err(e) => (false, e),
+            };
+            results.push(ExecResult { repo: repo.name.clone(), success, output });
+        }
+        (results, skipped)
+    }
+
+    /// Asks on the terminal whether to push `repo`, anything but y or yes skips it
+    pub fn ask(repo: &RepoInfo) -> bool {
+        let Some(upstream) = &repo.upstream else {
+            return false;
+        };
+        print!("Push {} commit{} of {} [{}] to {}? [y/N] ", upstream.ahead, if upstream.ahead == 1 { "" } else { "s" }, repo.name, repo.branch, upstream.name);
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).is_ok_and(|_| matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Pushes `branch` to its upstream branch, returning a one line summary or git's error.
+    /// The refspec has no '+', so git refuses anything but a fast-forward.
+    pub fn run(path: &str, branch: &str) -> Result<String, String> {
+        let output = git(path, &["for-each-ref", "--format=%(upstream:remotename) %(upstream:remoteref)", &format!("refs/heads/{}", branch)])?;
+        let Some((remote, remote_ref)) = output.trim().split_once(' ').filter(|(remote, remote_ref)| !remote.is_empty() && !remote_ref.is_empty()) else {
+            return Err(format!("{} has no upstream branch", branch));
+        };
+        git(path, &["push", "--quiet", remote, &format!("refs/heads/{}:{}", branch, remote_ref)])?;
+        Ok(format!("pushed to {}/{}", remote, remote_ref.trim_start_matches("refs/heads/")))
+    }
+}
+
+/// Runs git in the repo at `path` without prompting for credentials, returning its output or its first error line
+fn git(path: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C").arg(path)
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        // a rejected push starts with the remote's URL, the reason is on the " ! [rejected]" line
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut lines = stderr.lines().map(str::trim).filter(|l| !l.is_empty());
+        let reason = lines.clone().find(|l| l.starts_with('!') || l.starts_with("error:") || l.starts_with("fatal:")).or_else(|| lines.next());
+        Err(reason.unwrap_or("git push failed").to_string())
+    }
+}
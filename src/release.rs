@@ -0,0 +1,113 @@
+use crate::remote::{CiState, RemoteClient};
+use repolice_core::RepoInfo;
+
+use std::process::Command;
+use std::thread;
+
+/// Repos asked about their CI at the same time
+const CI_WORKERS: usize = 4;
+
+pub struct ReleaseInfo {
+    pub repo: RepoInfo,
+    pub base: Option<String>,
+    pub unreleased: Vec<String>,
+    /// last CI run of the checked out branch, none when no CI ran or the repo isn't on GitHub or GitLab.
+    /// Only asked for repos with unreleased commits.
+    pub ci: Result<Option<CiState>, String>,
+}
+
+impl ReleaseInfo {
+    /// A repo can be released when it has unreleased commits, nothing left uncommitted and no failed CI run
+    pub fn is_ready(&self) -> bool {
+        !self.unreleased.is_empty() && !self.is_blocked()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        !self.unreleased.is_empty() && (self.repo.has_changes() || self.ci_failed())
+    }
+
+    pub fn ci_failed(&self) -> bool {
+        matches!(self.ci, Ok(Some(CiState::Failure)))
+    }
+}
+
+pub struct Release {}
+
+impl Release {
+    /// Builds the release-readiness report for already scanned repos, asking `client` about their CI.
+    /// `base` is either a ref (`origin/main`, `v1.2.0`) or a tag glob, defaulting to the latest tag.
+    pub fn collect_report(repos: Vec<RepoInfo>, base: Option<&str>, client: &RemoteClient) -> Vec<ReleaseInfo> {
+        let mut report: Vec<ReleaseInfo> = repos.into_iter()
+            .map(|repo| {
+                let base = Self::find_base(&repo.path, base);
+                let unreleased = Self::unreleased_commits(&repo.path, base.as_deref());
+                ReleaseInfo { repo, base, unreleased, ci: Ok(None) }
+            })
+            .collect();
+        Self::check_ci(&mut report, client);
+
+        // blocked repos first, then ready ones, then the ones with nothing to release
+        report.sort_by(|a, b| {
+            b.is_blocked().cmp(&a.is_blocked())
+                .then_with(|| b.is_ready().cmp(&a.is_ready()))
+                .then_with(|| a.repo.name.cmp(&b.repo.name))
+        });
+
+        report
+    }
+
+    /// Fills in the CI state of the repos with something to release, a few API requests at a time
+    fn check_ci(report: &mut [ReleaseInfo], client: &RemoteClient) {
+        let mut pending: Vec<&mut ReleaseInfo> = report.iter_mut()
+            .filter(|info| !info.unreleased.is_empty() && RemoteClient::applies_to(&info.repo))
+            .collect();
+        let per_worker = pending.len().div_ceil(CI_WORKERS).max(1);
+        thread::scope(|scope| {
+            for chunk in pending.chunks_mut(per_worker) {
+                scope.spawn(move || {
+                    for info in chunk {
+                        info.ci = client.fetch(&info.repo).map(|status| status.ci);
+                    }
+                });
+            }
+        });
+    }
+
+    fn find_base(path: &str, base: Option<&str>) -> Option<String> {
+        let pattern = base.unwrap_or("*");
+
+        // an exact ref wins over a tag pattern
+        if base.is_some() {
+            let exact = Command::new("git")
+                .args(["-C", path, "rev-parse", "--verify", "--quiet", pattern])
+                .output().ok()?;
+            if exact.status.success() {
+                return Some(pattern.to_string());
+            }
+        }
+
+        let output = Command::new("git")
+            .args(["-C", path, "describe", "--tags", "--abbrev=0", "--match", pattern])
+            .output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if tag.is_empty() { None } else { Some(tag) }
+    }
+
+    fn unreleased_commits(path: &str, base: Option<&str>) -> Vec<String> {
+        let range = match base {
+            Some(b) => format!("{}..HEAD", b),
+            None => "HEAD".to_string(),     // nothing was ever released, everything counts
+        };
+        let output = Command::new("git")
+            .args(["-C", path, "log", "--format=%h %s", &range])
+            .output();
+
+        match output {
+            Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).lines().map(String::from).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::config::RemoteStatusConfig;
+use repolice_core::{Provider, RepoInfo};
+
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde_json::Value;
+
+/// Requests running at the same time, each waits on a single HTTP call at a time
+const WORKERS: usize = 4;
+/// seconds one API request may take
+const REQUEST_TIMEOUT: &str = "10";
+
+/// Open pull or merge request of a repo's branch and the state of its last CI run, as the hosting provider sees it
+#[derive(Clone, Debug, Default)]
+pub struct RemoteStatus {
+    pub pull_request: Option<PullRequest>,
+    /// none when no CI ran on the branch, or the branch was never pushed
+    pub ci: Option<CiState>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PullRequest {
+    /// `#12` on GitHub, `!12` on GitLab
+    pub reference: String,
+    pub title: String,
+    pub url: String,
+    pub draft: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CiState {
+    Success,
+    Failure,
+    /// queued or still running
+    Pending,
+}
+
+impl CiState {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            CiState::Success => "passed",
+            CiState::Failure => "failed",
+            CiState::Pending => "running",
+        }
+    }
+}
+
+/// Asks the GitHub and GitLab APIs about the branches of scanned repos, through `curl`
+#[derive(Clone, Debug, Default)]
+pub struct RemoteClient {
+    github_token: Option<String>,
+    gitlab_token: Option<String>,
+}
+
+impl RemoteClient {
+    /// Tokens come from `GITHUB_TOKEN` or `GH_TOKEN` and `GITLAB_TOKEN`, else from the config.
+    /// Without one only public repos can be asked about, and GitHub allows few requests an hour.
+    pub fn new(config: &RemoteStatusConfig) -> RemoteClient {
+        let var = |names: &[&str]| names.iter().filter_map(|name| env::var(name).ok()).find(|token| !token.is_empty());
+        RemoteClient {
+            github_token: var(&["GITHUB_TOKEN", "GH_TOKEN"]).or_else(|| config.github_token.clone()),
+            gitlab_token: var(&["GITLAB_TOKEN"]).or_else(|| config.gitlab_token.clone()),
+        }
+    }
+
+    /// Whether the repo's origin is on GitHub or GitLab and a branch is checked out
+    pub fn applies_to(repo: &RepoInfo) -> bool {
+        matches!(repo.provider, Some(Provider::GitHub | Provider::GitLab))
+            && repo.remote_url.as_deref().and_then(project_path).is_some()
+            && repo.branch != "HEAD"
+    }
+
+    pub fn fetch(&self, repo: &RepoInfo) -> Result<RemoteStatus, String> {
+        let url = repo.remote_url.as_deref().ok_or("no origin")?;
+        let host = Provider::host(url).ok_or("origin is a local path")?;
+        let project = project_path(url).ok_or_else(|| format!("cannot tell the project from {}", url))?;
+        match repo.provider {
+            Some(Provider::GitHub) => self.fetch_github(&host, &project, &repo.branch),
+            Some(Provider::GitLab) => self.fetch_gitlab(&host, &project, &repo.branch),
+            _ => Err("origin is neither on GitHub nor on GitLab".to_string()),
+        }
+    }
+
+    fn fetch_github(&self, host: &str, project: &str, branch: &str) -> Result<RemoteStatus, String> {
+        // GitHub Enterprise serves the API below the host itself
+        let api = if host == "github.com" { "https://api.github.com".to_string() } else { format!("https://{}/api/v3", host) };
+        let base = format!("{}/repos/{}", api, project);
+        let owner = project.split('/').next().unwrap_or_default();
+        let headers = self.github_token.iter().map(|token| format!("Authorization: Bearer {}", token))
+            .chain(["Accept: application/vnd.github+json".to_string()])
+            .collect::<Vec<_>>();
+
+        let pulls = get(&format!("{}/pulls?state=open&per_page=1&head={}", base, encode(&format!("{}:{}", owner, branch))), &headers)?;
+        let pull_request = pulls.as_ref().and_then(|pulls| pulls.get(0)).map(|pr| PullRequest {
+            reference: format!("#{}", pr["number"]),
+            title: pr["title"].as_str().unwrap_or_default().to_string(),
+            url: pr["html_url"].as_str().unwrap_or_default().to_string(),
+            draft: pr["draft"].as_bool().unwrap_or(false),
+        });
+
+        // checks of GitHub Actions and other apps, else the older commit statuses
+        let branch = encode(branch);
+        let runs = get(&format!("{}/commits/{}/check-runs?per_page=100", base, branch), &headers)?;
+        let runs = runs.as_ref().and_then(|runs| runs["check_runs"].as_array()).cloned().unwrap_or_default();
+        let ci = if runs.is_empty() {
+            get(&format!("{}/commits/{}/status", base, branch), &headers)?
+                .filter(|status| status["total_count"].as_u64().is_some_and(|count| count > 0))
+                .and_then(|status| match status["state"].as_str() {
+                    Some("success") => Some(CiState::Success),
+                    Some("failure" | "error") => Some(CiState::Failure),
+                    Some("pending") => Some(CiState::Pending),
+                    _ => None,
+                })
+        } else if runs.iter().any(|run| matches!(run["conclusion"].as_str(), Some("failure" | "timed_out" | "cancelled" | "action_required"))) {
+            Some(CiState::Failure)
+        } else if runs.iter().any(|run| run["status"].as_str() != Some("completed")) {
+            Some(CiState::Pending)
+        } else {
+            Some(CiState::Success)
+        };
+        Ok(RemoteStatus { pull_request, ci })
+    }
+
+    fn fetch_gitlab(&self, host: &str, project: &str, branch: &str) -> Result<RemoteStatus, String> {
+        let base = format!("https://{}/api/v4/projects/{}", host, encode(project));
+        let headers: Vec<String> = self.gitlab_token.iter().map(|token| format!("PRIVATE-TOKEN: {}", token)).collect();
+        let branch = encode(branch);
+
+        let requests = get(&format!("{}/merge_requests?state=opened&per_page=1&source_branch={}", base, branch), &headers)?;
+        let pull_request = requests.as_ref().and_then(|requests| requests.get(0)).map(|mr| PullRequest {
+            reference: format!("!{}", mr["iid"]),
+            title: mr["title"].as_str().unwrap_or_default().to_string(),
+            url: mr["web_url"].as_str().unwrap_or_default().to_string(),
+            // older GitLab versions only know work in progress
+            draft: mr["draft"].as_bool().or_else(|| mr["work_in_progress"].as_bool()).unwrap_or(false),
+        });
+
+        let pipelines = get(&format!("{}/pipelines?per_page=1&ref={}", base, branch), &headers)?;
+        let ci = pipelines.as_ref().and_then(|pipelines| pipelines.get(0)).and_then(|pipeline| match pipeline["status"].as_str() {
+            Some("success") => Some(CiState::Success),
+            Some("failed" | "canceled") => Some(CiState::Failure),
+            Some("created" | "waiting_for_resource" | "preparing" | "pending" | "running" | "scheduled") => Some(CiState::Pending),
+            _ => None,
+        });
+        Ok(RemoteStatus { pull_request, ci })
+    }
+}
+
+/// Runs a client's requests on a few background threads, so a slow API doesn't hold up the TUI
+pub struct RemoteFetcher {
+    jobs: Sender<RepoInfo>,
+    results: Receiver<(String, Result<RemoteStatus, String>)>,
+}
+
+impl RemoteFetcher {
+    pub fn spawn(client: RemoteClient) -> RemoteFetcher {
+        let (jobs, queue) = mpsc::channel::<RepoInfo>();
+        let (done, results) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..WORKERS {
+            let (client, queue, done) = (client.clone(), Arc::clone(&queue), done.clone());
+            thread::spawn(move || loop {
+                // the lock is released before the request, the other workers keep taking jobs
+                let Ok(repo) = queue.lock().map_err(|_| ()).and_then(|queue| queue.recv().map_err(|_| ())) else {
+                    return;
+                };
+                if done.send((repo.path.clone(), client.fetch(&repo))).is_err() {
+                    return;
+                }
+            });
+        }
+        RemoteFetcher { jobs, results }
+    }
+
+    /// Queues the repo, its status arrives through [`RemoteFetcher::try_recv`] keyed by its path
+    pub fn request(&self, repo: &RepoInfo) {
+        let _ = self.jobs.send(repo.clone());
+    }
+
+    pub fn try_recv(&self) -> Option<(String, Result<RemoteStatus, String>)> {
+        self.results.try_recv().ok()
+    }
+}
+
+/// GETs a JSON document, none when the API doesn't know it, e.g. a branch that was never pushed.
+/// Headers are handed to curl on stdin, tokens don't show up in the process list.
+fn get(url: &str, headers: &[String]) -> Result<Option<Value>, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--location", "--max-time", REQUEST_TIMEOUT, "--header", "@-", "--write-out", "\n%{http_code}", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(headers.join("\n").as_bytes()).map_err(|e| format!("Could not run curl: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("Could not run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, code) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    let body: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+    match code.trim() {
+        code if code.starts_with('2') => Ok(Some(body)),
+        "404" | "422" => Ok(None),
+        code => {
+            let reason = body["message"].as_str().unwrap_or("request failed");
+            Err(format!("HTTP {}: {}", code, reason))
+        }
+    }
+}
+
+/// `owner/name` of a remote URL, GitLab's nested groups included, without a trailing `.git`
+fn project_path(url: &str) -> Option<String> {
+    Provider::host(url)?;
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    path.contains('/').then(|| path.to_string())
+}
+
+/// Percent-encodes everything but unreserved characters, for paths and query values alike
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
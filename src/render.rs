@@ -0,0 +1,209 @@
+use crate::format::Format;
+use crate::glyphs::Glyphs;
+use crate::printer::Printer;
+use repolice_core::{DepSync, FileTracker, Reader, RemoteCheck, RepoInfo};
+use ratatui::style::Color;
+use std::ptr;
+
+/// Color of each category of `RepoInfo::trackers`, in listings, on cards and in the detail view
+pub const TRACKER_COLORS: [Color; 9] = [Color::Red, Color::Blue, Color::Green, Color::Yellow, Color::LightYellow, Color::Red, Color::LightRed, Color::Magenta, Color::Cyan];
+
+/// Branch of repos with changes right on a protected branch
+pub const PROTECTED_COLOR: Color = Color::LightMagenta;
+
+/// A piece of a row, in the terminal's default color without one
+pub struct Segment {
+    pub text: String,
+    pub color: Option<Color>,
+    /// only the TUI shows it, printed listings keep to colors
+    pub bold: bool,
+}
+
+impl Segment {
+    pub fn plain(text: impl Into<String>) -> Segment {
+        Segment { text: text.into(), color: None, bold: false }
+    }
+
+    pub fn colored(text: impl Into<String>, color: Color) -> Segment {
+        Segment { text: text.into(), color: Some(color), bold: false }
+    }
+
+    pub fn bold(self) -> Segment {
+        Segment { bold: true, ..self }
+    }
+}
+
+/// A line about a repo, the printer paints it after "| " and the TUI turns it into a `Line`,
+/// so both show the same words in the same colors
+pub type Row = Vec<Segment>;
+
+/// The pin of pinned repos, the host of remote ones, the name and the languages
+pub fn title(repo: &RepoInfo) -> Row {
+    let mut row = Vec::new();
+    if repo.pinned {
+        row.push(Segment::colored(format!("{} ", Glyphs::current().pin()), Color::Cyan));
+    }
+    if let Some(host) = &repo.host {
+        row.push(Segment::colored(format!("{}:", host), Color::Blue));
+    }
+    row.push(Segment::colored(repo.name.clone(), Color::Yellow).bold());
+    row.extend(repo.languages.iter().map(|language| Segment::colored(format!(" {}", language.badge()), Color::Magenta)));
+    row
+}
+
+/// "[main]", in the warning color and marked when changes are made right on a protected branch
+pub fn branch(repo: &RepoInfo) -> Segment {
+    if repo.edits_protected_branch() {
+        Segment::colored(format!("{} {{protected branch}}", Glyphs::current().branch(&repo.branch)), PROTECTED_COLOR).bold()
+    } else {
+        Segment::colored(Glyphs::current().branch(&repo.branch), Color::Green)
+    }
+}
+
+/// The branch followed by the host, commits ahead and behind, stashes, sizes and whether there is a remote at all
+pub fn branch_row(repo: &RepoInfo) -> Row {
+    let mut row = vec![branch(repo)];
+    if let Some(provider) = repo.provider {
+        row.push(Segment::colored(format!(" @{}", provider.badge()), Color::Blue));
+    }
+    row.extend(markers(repo));
+    if let Some(size) = repo.size {
+        row.push(Segment::colored(format!(" {}", Format::current().size(size)), Color::DarkGray));
+    }
+    if let Some(ignored) = repo.ignored.as_ref().filter(|ignored| ignored.total > 0) {
+        row.push(Segment::colored(format!(" ign:{}", Format::current().size(ignored.total)), Color::DarkGray));
+    }
+    if repo.local_only {
+        row.push(Segment::colored(" local only", Color::Red));
+    }
+    row
+}
+
+/// Commits ahead and behind the upstream and stash entries, each with a leading space, which clean repos show too
+pub fn markers(repo: &RepoInfo) -> Vec<Segment> {
+    let mut markers = Vec::new();
+    if let Some(upstream) = repo.upstream.as_ref().filter(|upstream| upstream.ahead > 0 || upstream.behind > 0) {
+        markers.push(Segment::colored(Printer::ahead_behind(upstream), Color::Cyan));
+    }
+    if let Some(stashes) = repo.stashes.filter(|n| *n > 0) {
+        markers.push(Segment::colored(format!(" {}{}", Glyphs::current().stash(), stashes), Color::Yellow));
+    }
+    markers
+}
+
+/// What else there is to know about a repo, one note each: an unreachable origin, shallow and partial clones, changed
+/// submodules and worktrees, lock files out of sync, ignore rules, attention, drift from the default branch, branches
+/// whose upstream is gone and nesting
+pub fn notes(repo: &RepoInfo) -> Vec<Segment> {
+    let mut notes = Vec::new();
+    if let Some(RemoteCheck::Unreachable(reason)) = &repo.remote_check {
+        notes.push(Segment::colored(format!("origin unreachable: {}", reason), Color::Red));
+    }
+    if repo.shallow {
+        notes.push(Segment::colored("shallow clone", Color::Yellow));
+    }
+    if repo.partial {
+        notes.push(Segment::colored("partial clone", Color::Yellow));
+    }
+    if let Some(note) = Printer::submodule_note(repo) {
+        notes.push(Segment::colored(note, Color::Red));
+    }
+    if let Some(note) = Printer::worktree_note(repo) {
+        notes.push(Segment::colored(note, Color::Red));
+    }
+    match repo.dep_sync {
+        Some(DepSync::InSync) | None => {}
+        Some(DepSync::LockDirty) => notes.push(Segment::colored(DepSync::LockDirty.describe(), Color::Yellow)),
+        Some(sync) => notes.push(Segment::colored(sync.describe(), Color::Red)),
+    }
+    if let Some(hygiene) = repo.hygiene.as_ref().filter(|hygiene| hygiene.warnings() > 0) {
+        notes.push(Segment::colored(hygiene.describe(), Color::Yellow));
+    }
+    if let Some(attention) = &repo.attention {
+        notes.push(Segment::colored(attention.describe(), Printer::level_color(attention.level)));
+    }
+    if let Some(note) = Printer::drift_note(repo) {
+        notes.push(Segment::colored(note, Color::Yellow));
+    }
+    match repo.gone_branches.len() {
+        0 => {}
+        1 => notes.push(Segment::colored(format!("branch {} gone upstream", repo.gone_branches[0]), Color::Yellow)),
+        gone => notes.push(Segment::colored(format!("{} branches gone upstream", gone), Color::Yellow)),
+    }
+    if let Some(parent) = &repo.nested_in {
+        notes.push(Segment::colored(format!("nested in {}", Reader::repo_name(parent)), Color::Yellow));
+    }
+    notes
+}
+
+/// "a1b2c3d Fix the parser (Alice, 2 days ago)"
+pub fn last_commit(repo: &RepoInfo) -> Option<Row> {
+    let commit = repo.last_commit.as_ref()?;
+    Some(vec![
+        Segment::colored(format!("{} ", commit.short_id), Color::DarkGray),
+        Segment::plain(commit.subject.clone()),
+        Segment::colored(format!(" ({}, {})", commit.author, Format::current().time(commit.time)), Color::DarkGray),
+    ])
+}
+
+/// "staged:2 unstaged:3" and how long the repo has been dirty, none without changes
+pub fn staging(repo: &RepoInfo) -> Option<Row> {
+    if !repo.has_changes() {
+        return None;
+    }
+    let mut row = vec![
+        Segment::colored(format!("staged:{} ", repo.staged_changes()), Color::Green),
+        Segment::colored(format!("unstaged:{}", repo.unstaged_changes()), Color::DarkGray),
+    ];
+    if let (Some(since), Some(age)) = (repo.dirty_since, repo.dirty_for()) {
+        row.push(Segment::colored(format!(" {}{}", Glyphs::current().dirty(), Format::current().since(since, &age)), Color::DarkGray));
+    }
+    Some(row)
+}
+
+/// How many files of each category changed, conflicts aside. Type changes and missing files are rare,
+/// they only take room when there are some.
+pub fn counts(repo: &RepoInfo) -> Row {
+    let rare = |tracker: &FileTracker| ptr::eq(tracker, &repo.typechanged_files) || ptr::eq(tracker, &repo.missing_files);
+    repo.trackers().into_iter().zip(TRACKER_COLORS).skip(1)
+        .filter(|(tracker, _)| tracker.amount > 0 || !rare(tracker))
+        .map(|(tracker, color)| Segment::colored(format!("{}:{} ", tracker.status, tracker.amount), color))
+        .collect()
+}
+
+/// A category: its count, then its first `shown` files cut to `width` from the left, with whether they are staged,
+/// and how many more there are
+pub fn files(tracker: &FileTracker, color: Color, shown: usize, width: usize) -> Vec<Row> {
+    let mut rows = vec![vec![Segment::colored(format!("{}: {}", tracker.status, tracker.amount), color)]];
+    let files = tracker.files.as_deref().unwrap_or_default();
+    for file in files.iter().take(shown) {
+        // file names keep their end, where the file itself is
+        let length = file.chars().count();
+        let name = if length > width && width > 1 {
+            format!("…{}", file.chars().skip(length - width + 1).collect::<String>())
+        } else {
+            file.clone()
+        };
+        // cards trim leading spaces, so the files are bulleted
+        let mut row = vec![Segment::plain("- "), Segment::colored(name, color)];
+        match tracker.staging(file) {
+            Some((true, true)) => row.push(Segment::colored(" partly staged", Color::DarkGray)),
+            Some((true, false)) => row.push(Segment::colored(" staged", Color::Green)),
+            _ => {}
+        }
+        rows.push(row);
+    }
+    if files.len() > shown {
+        rows.push(vec![Segment::colored(format!("+{} more", files.len() - shown), Color::DarkGray)]);
+    }
+    rows
+}
+
+/// Rows `files` takes for `tracker`, none when nothing in it changed
+pub fn files_height(tracker: &FileTracker, shown: usize) -> usize {
+    if tracker.amount == 0 {
+        return 0;
+    }
+    let files = tracker.files.as_ref().map_or(0, Vec::len);
+    1 + files.min(shown) + usize::from(files > shown)
+}
@@ -0,0 +1,63 @@
+use crate::launch::Launcher;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub struct Resolver {}
+
+impl Resolver {
+    /// Paths with unresolved conflicts, relative to `path`
+    pub fn conflicted_files(path: &str) -> Vec<String> {
+        let output = Command::new("git")
+            .args(["-C", path, "diff", "--name-only", "--relative", "--diff-filter=U"])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Conflicted files that still contain conflict markers
+    pub fn files_with_markers(path: &str) -> Vec<String> {
+        Self::conflicted_files(path).into_iter()
+            .filter(|file| Self::has_markers(path, file))
+            .collect()
+    }
+
+    fn has_markers(path: &str, file: &str) -> bool {
+        fs::read(Path::new(path).join(file))
+            .is_ok_and(|contents| String::from_utf8_lossy(&contents).lines().any(|l| l.starts_with("<<<<<<< ")))
+    }
+
+    /// Stages the files of `marked` whose markers are gone, like `git mergetool` does after a merge.
+    /// Conflicts without markers, e.g. deleted on one side, are left for the user to decide.
+    pub fn stage_resolved(path: &str, marked: &[String]) {
+        let resolved: Vec<&String> = marked.iter().filter(|file| !Self::has_markers(path, file)).collect();
+        if !resolved.is_empty() {
+            let _ = Command::new("git").args(["-C", path, "add", "--"]).args(resolved).output();
+        }
+    }
+
+    /// `git mergetool` when the repo has a `merge.tool` configured, otherwise `$EDITOR` on the conflicted files
+    pub fn command(path: &str) -> Result<Command, String> {
+        let files = Self::conflicted_files(path);
+        if files.is_empty() {
+            return Err("No conflicted files".to_string());
+        }
+
+        let merge_tool = Command::new("git")
+            .args(["-C", path, "config", "merge.tool"])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if merge_tool {
+            let mut command = Command::new("git");
+            command.args(["-C", path, "mergetool"]);
+            return Ok(command);
+        }
+
+        let mut command = Launcher::command_line(&Launcher::editor())?;
+        command.args(&files).current_dir(path);
+        Ok(command)
+    }
+}
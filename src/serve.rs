@@ -0,0 +1,317 @@
+use crate::format::Format;
+use crate::printer::Printer;
+use repolice_core::{host_name, now, Level, RepoInfo, RepoliceError, Totals};
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Longest request head read, anything past it is answered with 431
+const MAX_REQUEST: usize = 8 * 1024;
+/// How long a client gets to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The repos and errors of the last finished scan
+struct Results {
+    repos: Vec<RepoInfo>,
+    errors: Vec<RepoliceError>,
+    /// seconds since the unix epoch
+    scanned_at: i64,
+}
+
+/// A response before it is written out
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn new(status: &'static str, content_type: &'static str, body: String) -> Response {
+        Response { status, content_type, body }
+    }
+
+    fn text(status: &'static str, body: &str) -> Response {
+        Response::new(status, "text/plain; charset=utf-8", format!("{}\n", body))
+    }
+
+    fn json(body: Result<String, serde_json::Error>) -> Response {
+        match body {
+            Ok(body) => Response::new("200 OK", "application/json", body),
+            Err(e) => Response::text("500 Internal Server Error", &e.to_string()),
+        }
+    }
+}
+
+/// Serves the results of scans repeated every `interval` over read-only HTTP: a dashboard at `/`,
+/// every repo as JSON at `/repos` and a single one at `/repos/<name>`
+pub struct Server {}
+
+impl Server {
+    /// Listens on `addr` until the process is stopped. `scan` runs on a blocking thread, one scan at a time.
+    pub async fn run<F>(addr: &str, interval: Duration, scan: F) -> Result<(), String>
+    where
+        F: Fn() -> (Vec<RepoInfo>, Vec<RepoliceError>) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(addr).await.map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+        println!("Serving scan results on http://{}/, rescanning every {}s", addr, interval.as_secs());
+
+        let results: Arc<RwLock<Option<Results>>> = Arc::new(RwLock::new(None));
+        let scanned = results.clone();
+        let scan = Arc::new(scan);
+        tokio::spawn(async move {
+            loop {
+                let scan = scan.clone();
+                if let Ok((repos, errors)) = tokio::task::spawn_blocking(move || scan()).await {
+                    *scanned.write().unwrap() = Some(Results { repos, errors, scanned_at: now() });
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                // e.g. out of file descriptors, the next client may get through
+                Err(e) => {
+                    eprintln!("Could not accept a connection: {}", e);
+                    continue;
+                }
+            };
+            let results = results.clone();
+            tokio::spawn(async move {
+                let _ = respond(stream, &results, interval).await;
+            });
+        }
+    }
+}
+
+/// Reads one request from `stream` and answers it, closing the connection after
+async fn respond(mut stream: TcpStream, results: &RwLock<Option<Results>>, interval: Duration) -> std::io::Result<()> {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Ok(Some(head))) => route(&head, results, interval),
+        Ok(Ok(None)) => Response::text("431 Request Header Fields Too Large", "request too large"),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => Response::text("408 Request Timeout", "request timed out"),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status, response.content_type, response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The request line and headers, none when they run over [`MAX_REQUEST`]
+async fn read_head(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    loop {
+        // the last read may have brought more than the head
+        let end = head.windows(4).position(|window| window == b"\r\n\r\n").map(|start| start + 4);
+        if end.unwrap_or(head.len()) > MAX_REQUEST {
+            return Ok(None);
+        }
+        if let Some(end) = end {
+            head.truncate(end);
+            break;
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(Some(String::from_utf8_lossy(&head).to_string()))
+}
+
+fn route(head: &str, results: &RwLock<Option<Results>>, interval: Duration) -> Response {
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or_default());
+    if method != "GET" {
+        return Response::text("405 Method Not Allowed", "only GET is supported, this server is read-only");
+    }
+    let path = percent_decode(target.split('?').next().unwrap_or_default());
+
+    let results = results.read().unwrap();
+    let Some(results) = results.as_ref() else {
+        return Response::text("503 Service Unavailable", "the first scan is still running");
+    };
+    match path.trim_end_matches('/') {
+        "" => Response::new("200 OK", "text/html; charset=utf-8", dashboard(results, interval)),
+        "/repos" => Response::json(serde_json::to_string(&results.repos)),
+        // names like "work/api" contain slashes themselves
+        path => match path.strip_prefix("/repos/") {
+            Some(name) => match results.repos.iter().find(|repo| repo.name == name) {
+                Some(repo) => Response::json(serde_json::to_string(repo)),
+                None => Response::text("404 Not Found", &format!("no repo named {}", name)),
+            },
+            None => Response::text("404 Not Found", "not found, try / or /repos"),
+        },
+    }
+}
+
+/// A page listing every repo, reloading itself after each scan
+fn dashboard(results: &Results, interval: Duration) -> String {
+    let mut rows = String::new();
+    for repo in &results.repos {
+        let class = match &repo.attention {
+            Some(attention) if attention.level == Level::Critical => "critical",
+            Some(_) => "warn",
+            None if repo.has_conflicts() => "critical",
+            None if repo.has_changes() => "dirty",
+            None => "",
+        };
+        let upstream = repo.upstream.as_ref().map_or(String::new(), |upstream| Printer::ahead_behind(upstream).trim().to_string());
+        rows.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            class,
+            escape(&repo.name),
+            escape(&repo.branch),
+            repo.total_changes(),
+            if repo.has_conflicts() { format!("{} conflicted", repo.conflicted_files.amount) } else { String::new() },
+            escape(&upstream),
+            repo.dirty_for().unwrap_or_default(),
+            repo.attention.as_ref().map_or(String::new(), |attention| escape(&attention.describe())),
+        ));
+    }
+    let errors: String = results.errors.iter()
+        .map(|error| format!("<li>{}</li>\n", escape(&error.to_string())))
+        .collect();
+    let errors = if errors.is_empty() { String::new() } else { format!("<h2>Could not be read</h2>\n<ul>\n{}</ul>\n", errors) };
+    let host = escape(&host_name());
+    let scanned = format!("{} UTC", Format::timestamp(results.scanned_at));
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="{refresh}">
+<title>repolice on {host}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; background: #111; color: #ddd; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.3em 1em; text-align: left; border-bottom: 1px solid #333; }}
+tr.dirty td:first-child {{ color: #fc3; }}
+tr.warn {{ background: #332b00; }}
+tr.critical {{ background: #401515; }}
+.muted {{ color: #888; }}
+</style>
+</head>
+<body>
+<h1>repolice on {host}</h1>
+<p>{totals} <span class="muted">scanned {scanned}</span></p>
+<table>
+<tr><th>Repo</th><th>Branch</th><th>Changes</th><th>Conflicts</th><th>Upstream</th><th>Dirty for</th><th>Attention</th></tr>
+{rows}</table>
+{errors}</body>
+</html>
+"#,
+        refresh = interval.as_secs().max(5),
+        host = host,
+        totals = escape(&Totals::of(&results.repos).describe()),
+        scanned = scanned,
+        rows = rows,
+        errors = errors,
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Decodes `%2F` and the like, invalid escapes are kept as they are
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::repo;
+
+    fn scanned(names: &[&str]) -> RwLock<Option<Results>> {
+        RwLock::new(Some(Results { repos: names.iter().map(|name| repo(name)).collect(), errors: Vec::new(), scanned_at: 0 }))
+    }
+
+    fn get(results: &RwLock<Option<Results>>, target: &str) -> Response {
+        route(&format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target), results, Duration::from_secs(60))
+    }
+
+    #[test]
+    fn serves_the_dashboard_and_every_repo() {
+        let results = scanned(&["api", "work/web<1>"]);
+        let page = get(&results, "/");
+        assert_eq!((page.status, page.content_type), ("200 OK", "text/html; charset=utf-8"));
+        assert!(page.body.contains("<td>work/web&lt;1&gt;</td>") && page.body.contains("content=\"60\""));
+
+        let all = get(&results, "/repos?fresh=1");
+        assert_eq!((all.status, all.content_type), ("200 OK", "application/json"));
+        let repos: Vec<RepoInfo> = serde_json::from_str(&all.body).unwrap();
+        assert_eq!(repos.iter().map(|repo| repo.name.as_str()).collect::<Vec<_>>(), ["api", "work/web<1>"]);
+
+        let one = get(&results, "/repos/work%2Fweb%3C1%3E/");
+        let repo: RepoInfo = serde_json::from_str(&one.body).unwrap();
+        assert_eq!(repo.path, "/src/work/web<1>");
+    }
+
+    #[test]
+    fn answers_what_it_cannot_serve() {
+        let results = scanned(&["api"]);
+        assert_eq!(get(&results, "/repos/web").status, "404 Not Found");
+        assert_eq!(get(&results, "/settings").status, "404 Not Found");
+        let post = route("POST /repos HTTP/1.1\r\n\r\n", &results, Duration::from_secs(60));
+        assert_eq!(post.status, "405 Method Not Allowed");
+        assert_eq!(get(&RwLock::new(None), "/").status, "503 Service Unavailable");
+    }
+
+    #[test]
+    fn decodes_escaped_paths() {
+        assert_eq!(percent_decode("/repos/work%2Fapi"), "/repos/work/api");
+        assert_eq!(percent_decode("/repos/100%"), "/repos/100%");
+        assert_eq!(percent_decode("/repos/%zz%C3%A9"), "/repos/%zzé");
+    }
+
+    #[tokio::test]
+    async fn answers_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let request = |request: String| async move {
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            client.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let results = scanned(&["api"]);
+
+        let client = tokio::spawn(request("GET /repos/api HTTP/1.1\r\n\r\n".to_string()));
+        respond(listener.accept().await.unwrap().0, &results, Duration::from_secs(60)).await.unwrap();
+        let response = client.await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())) && head.contains("Connection: close"));
+
+        let client = tokio::spawn(request(format!("GET / HTTP/1.1\r\nCookie: {}\r\n\r\n", "x".repeat(MAX_REQUEST))));
+        respond(listener.accept().await.unwrap().0, &results, Duration::from_secs(60)).await.unwrap();
+        assert!(client.await.unwrap().starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"));
+    }
+}
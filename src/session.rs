@@ -0,0 +1,56 @@
+use crate::config::home_dir;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// Where the TUI was left when it quit, kept in `~/.local/share/repolice/session.toml` and taken up again with `--resume`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    /// name of the sort order, see `SortOrder::name`
+    pub sort: Option<String>,
+    /// text the cards were narrowed to
+    pub filter: String,
+    /// "dirty" or "clean" when only those were shown
+    pub state: Option<String>,
+    /// paths of the repos hidden with `d`, the most recently hidden last
+    pub dismissed: Vec<String>,
+    /// groups folded into their header
+    pub collapsed: Vec<String>,
+    /// path of the selected repo
+    pub selected: Option<String>,
+}
+
+impl Session {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_DATA_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".local").join("share"),
+        };
+        Some(base.join("repolice").join("session.toml"))
+    }
+
+    /// A missing file is a fresh session, an unreadable one a warning and a fresh session
+    pub fn load(path: &Path) -> Session {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Session::default();
+        };
+        match toml::from_str(&contents) {
+            Ok(session) => session,
+            Err(e) => {
+                eprintln!("Invalid session {}: {}", path.display(), e);
+                Session::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+}
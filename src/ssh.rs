@@ -0,0 +1,308 @@
+use repolice_core::{RepoInfo, RepoliceError, ScanOptions};
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// A directory on another machine scanned over SSH, `user@host:/path` on the command line.
+/// repolice has to be installed there, it reads the repos and sends them back as NDJSON.
+#[derive(Clone, Debug)]
+pub struct SshTarget {
+    /// what ssh connects to, e.g. "ci@build1"
+    destination: String,
+    path: String,
+}
+
+impl SshTarget {
+    pub fn parse(spec: &str) -> Result<SshTarget, String> {
+        match spec.split_once(':') {
+            Some((destination, path)) if !destination.is_empty() && !path.is_empty() => {
+                Ok(SshTarget { destination: destination.to_string(), path: path.to_string() })
+            }
+            _ => Err(format!("expected [user@]host:/path, got '{}'", spec)),
+        }
+    }
+
+    /// The machine without the user, shown on each of its repos
+    pub fn host(&self) -> &str {
+        self.destination.rsplit('@').next().unwrap_or(&self.destination)
+    }
+
+    /// Reads every repo of every target on a thread of its own, yielding them as they arrive.
+    /// Filters of `options` are applied here, the other machines report every repo.
+    pub fn scan_all(targets: &[SshTarget], options: &ScanOptions) -> ReceiverStream<Result<RepoInfo, RepoliceError>> {
+        let (tx, rx) = mpsc::channel(100);
+        for target in targets {
+            let (target, tx, options) = (target.clone(), tx.clone(), options.clone());
+            thread::spawn(move || target.read(&options, |result| tx.blocking_send(result).is_ok()));
+        }
+        ReceiverStream::new(rx)
+    }
+
+    /// Like [`SshTarget::scan_all`], waiting for every target to finish or for `deadline`. Returns what was read
+    /// by then, followed by the `host:path` of the targets that didn't finish in time.
+    pub fn collect_all(targets: &[SshTarget], options: &ScanOptions, deadline: Option<Instant>) -> (Vec<RepoInfo>, Vec<RepoliceError>, Vec<String>) {
+        // a token of their own, the reads still running at the deadline are stopped without stopping anything else
+        let options = ScanOptions { cancel: options.cancel.child(), ..options.clone() };
+        let (tx, rx) = std::sync::mpsc::channel();
+        for (index, target) in targets.iter().enumerate() {
+            let (target, tx, options) = (target.clone(), tx.clone(), options.clone());
+            // not joined, an ssh that hangs must not hold up the caller past the deadline
+            thread::spawn(move || {
+                target.read(&options, |result| tx.send((index, Some(result))).is_ok());
+                let _ = tx.send((index, None));
+            });
+        }
+        drop(tx);
+
+        let (mut repos, mut errors) = (Vec::new(), Vec::new());
+        let mut finished = vec![false; targets.len()];
+        loop {
+            let received = match deadline {
+                Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())).ok(),
+                None => rx.recv().ok(),
+            };
+            match received {
+                Some((_, Some(Ok(repo)))) => repos.push(repo),
+                Some((_, Some(Err(e)))) => errors.push(e),
+                Some((index, None)) => finished[index] = true,
+                None => break,
+            }
+        }
+        options.cancel.cancel();
+        options.sort.sort(&mut repos);
+        let unscanned = targets.iter().zip(finished)
+            .filter(|(_, finished)| !finished)
+            .map(|(target, _)| format!("{}:{}", target.host(), target.path))
+            .collect();
+        (repos, errors, unscanned)
+    }
+
+    /// Runs repolice on the other machine, handing each repo or error to `send` until it returns false
+    /// or `options.cancel` is cancelled
+    fn read(&self, options: &ScanOptions, mut send: impl FnMut(Result<RepoInfo, RepoliceError>) -> bool) {
+        let failed = |reason: String| RepoliceError::Remote { path: format!("{}:{}", self.host(), self.path), reason };
+        let mut child = match Command::new("ssh")
+            // never ask for a password, there is no terminal to type it into
+            .args(["-o", "BatchMode=yes", "--", &self.destination, &self.command(options)])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                send(Err(failed(format!("could not run ssh: {}", e))));
+                return;
+            }
+        };
+        // read alongside, a chatty stderr would otherwise fill its pipe and stall the other side
+        let stderr = child.stderr.take().map(|mut stderr| thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            text
+        }));
+
+        let mut listening = true;
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if options.cancel.is_cancelled() || !listening {
+                    break;
+                }
+                let result = self.parse_line(&line);
+                if result.as_ref().is_ok_and(|repo| !options.filters.iter().all(|filter| filter.matches(repo))) {
+                    continue;
+                }
+                listening = send(result);
+            }
+        }
+        if options.cancel.is_cancelled() || !listening {
+            let _ = child.kill();
+            let _ = child.wait();
+            return;
+        }
+
+        let status = child.wait();
+        let stderr = stderr.and_then(|stderr| stderr.join().ok()).unwrap_or_default();
+        for line in stderr.lines() {
+            // one for each repo the other machine couldn't read
+            if let Some(error) = self.parse_error(line) {
+                listening = send(Err(error));
+                if !listening {
+                    return;
+                }
+            }
+        }
+        match status {
+            Ok(status) if status.success() => {}
+            // 255 is ssh's own, the connection or the login failed
+            Ok(status) => {
+                let last = stderr.lines().rfind(|line| !line.trim().is_empty() && !line.starts_with("error: "));
+                let reason = match (status.code(), last) {
+                    (Some(255), Some(line)) => format!("ssh failed: {}", line),
+                    (Some(127), _) => "repolice is not installed there, or not on the PATH of non-interactive shells".to_string(),
+                    (_, Some(line)) => line.to_string(),
+                    (_, None) => format!("exited with {}", status),
+                };
+                send(Err(failed(reason)));
+            }
+            Err(e) => {
+                send(Err(failed(e.to_string())));
+            }
+        }
+    }
+
+    /// The command line run on the other machine, passing on every option that changes what a repo reports.
+    /// The scan is left out of that machine's history, it is this one's.
+    fn command(&self, options: &ScanOptions) -> String {
+        let mut command = format!("repolice --format ndjson --no-history -p {}", quote_path(&self.path));
+        let switches = [
+            ("verbose", options.verbose),
+            ("fetch", options.fetch),
+            ("follow-symlinks", options.follow_symlinks),
+            ("deps", options.deps),
+            ("hygiene", options.hygiene.is_some()),
+            ("disk-usage", options.ignored),
+            ("git-size", options.size),
+            ("stashes", options.stashes),
+            ("verify-remote", options.verify_remote),
+            ("mine", options.mine),
+        ];
+        for (name, _) in switches.iter().filter(|(_, on)| *on) {
+            command.push_str(&format!(" --{}", name));
+        }
+        let mut values: Vec<(&str, String)> = Vec::new();
+        if let Some(depth) = options.max_depth {
+            values.push(("depth", depth.min(u8::MAX as usize).to_string()));
+        }
+        if options.all_untracked {
+            values.push(("show-untracked", "all".to_string()));
+        }
+        // 0 waits forever there too
+        values.push(("repo-timeout", options.timeout.map_or(0.0, |timeout| timeout.as_secs_f64()).to_string()));
+        values.extend(options.exclude.iter().map(|glob| ("exclude", glob.clone())));
+        values.extend(options.hygiene.iter().flatten().map(|glob| ("hygiene-pattern", glob.clone())));
+        values.extend(options.protected_branches.iter().map(|glob| ("protected-branch", glob.clone())));
+        values.extend(options.thresholds.settings().into_iter().map(|setting| ("threshold", setting)));
+        for (name, value) in values {
+            command.push_str(&format!(" --{} {}", name, quote(&value)));
+        }
+        command
+    }
+
+    /// A line of the NDJSON sent back, as the repo it describes
+    fn parse_line(&self, line: &str) -> Result<RepoInfo, RepoliceError> {
+        serde_json::from_str::<RepoInfo>(line)
+            .map(|repo| self.claim(repo))
+            .map_err(|e| RepoliceError::Remote {
+                path: format!("{}:{}", self.host(), self.path),
+                reason: format!("unreadable answer, is repolice there as new as here? {}", e),
+            })
+    }
+
+    /// The repo a line of the other side's stderr says couldn't be read, "error: /srv/api: cannot open: ..."
+    fn parse_error(&self, line: &str) -> Option<RepoliceError> {
+        let (path, reason) = line.strip_prefix("error: ")?.split_once(": ")?;
+        Some(RepoliceError::Remote { path: format!("{}:{}", self.host(), path), reason: reason.to_string() })
+    }
+
+    /// Marks `repo` as one of this machine's, its paths can't be mistaken for local ones
+    fn claim(&self, mut repo: RepoInfo) -> RepoInfo {
+        repo.path = format!("{}:{}", self.host(), repo.path);
+        repo.root = format!("{}:{}", self.host(), repo.root);
+        repo.host = Some(self.host().to_string());
+        repo
+    }
+}
+
+/// `text` single-quoted for the remote shell, which passes it on as it is
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// `path` single-quoted for the remote shell, a leading `~` is left outside so it still expands to the home directory there
+fn quote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", quote(rest)),
+        None if path == "~" => path.to_string(),
+        None => quote(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use repolice_core::{Limits, Reader, Thresholds};
+    use std::{env, fs, process};
+    use std::time::Duration;
+
+    #[test]
+    fn passes_on_every_option_that_changes_a_repo() {
+        let target = SshTarget::parse("ci@build1:~/src/it's").unwrap();
+        let options = ScanOptions {
+            verbose: true,
+            exclude: vec!["**/vendor/**".to_string()],
+            max_depth: Some(3),
+            follow_symlinks: true,
+            deps: true,
+            fetch: true,
+            stashes: true,
+            size: true,
+            ignored: true,
+            hygiene: Some(vec!["*.log".to_string()]),
+            verify_remote: true,
+            protected_branches: vec!["release/*".to_string()],
+            thresholds: Thresholds { warn: Limits { changes: Some(20), ..Limits::default() }, critical: Limits { behind: Some(50), ..Limits::default() } },
+            all_untracked: true,
+            mine: true,
+            timeout: Some(Duration::from_millis(1500)),
+            ..ScanOptions::default()
+        };
+        assert_eq!(target.command(&options), "repolice --format ndjson --no-history -p ~/'src/it'\\''s' \
+            --verbose --fetch --follow-symlinks --deps --hygiene --disk-usage --git-size --stashes --verify-remote --mine \
+            --depth '3' --show-untracked 'all' --repo-timeout '1.5' --exclude '**/vendor/**' --hygiene-pattern '*.log' \
+            --protected-branch 'release/*' --threshold 'warn.changes=20' --threshold 'critical.behind=50'");
+    }
+
+    #[test]
+    fn waits_as_long_as_asked() {
+        let target = SshTarget::parse("build1:/src").unwrap();
+        let options = ScanOptions { timeout: None, ..ScanOptions::default() };
+        assert_eq!(target.command(&options), "repolice --format ndjson --no-history -p '/src' --repo-timeout '0'");
+    }
+
+    #[test]
+    fn claims_the_repos_sent_back() {
+        let dir = env::temp_dir().join(format!("repolice-ssh-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let status = Command::new("git").args(["init", "--quiet"]).current_dir(&dir).env("GIT_CONFIG_GLOBAL", "/dev/null").status().unwrap();
+        assert!(status.success());
+        fs::write(dir.join("new"), "new").unwrap();
+        let path = dir.to_string_lossy().to_string();
+        let sent = Reader::read_repo(&path, &ScanOptions::default()).unwrap().remove(0);
+
+        // as the other side prints it with --format ndjson
+        let line = serde_json::to_string(&sent).unwrap();
+        let target = SshTarget::parse("ci@build1:/srv").unwrap();
+        let repo = target.parse_line(&line).unwrap();
+        assert_eq!((repo.path, repo.host.as_deref()), (format!("build1:{}", path), Some("build1")));
+        assert_eq!((repo.name, repo.new_files.amount), (sent.name, 1));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reports_what_the_other_side_could_not_read() {
+        let target = SshTarget::parse("ci@build1:/srv").unwrap();
+        let Err(error) = target.parse_line("{\"name\": \"api\"}") else { panic!("read a repo without its fields") };
+        assert_eq!(error.path(), "build1:/srv");
+        assert!(error.reason().starts_with("unreadable answer"));
+
+        let error = target.parse_error("error: /srv/api: cannot open: not a git repository").unwrap();
+        assert_eq!((error.path(), error.reason().as_str()), ("build1:/srv/api", "cannot open: not a git repository"));
+        assert!(target.parse_error("Warning: Permanently added 'build1' to the list of known hosts.").is_none());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::config::{expand_home, home_dir};
+use repolice_core::{now, RepoInfo};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// Where earlier scans found each repo, kept in `~/.local/state/repolice/state.toml`
+/// to notice repos that were moved or deleted since
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct State {
+    /// keyed by the canonical path of the repo
+    pub repos: BTreeMap<String, SeenRepo>,
+}
+
+/// What is remembered about a repo to recognize it at another path
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SeenRepo {
+    /// seconds since the unix epoch
+    pub last_seen: i64,
+    pub origin: Option<String>,
+    /// short id of the commit HEAD pointed at
+    pub head: Option<String>,
+}
+
+/// A repo that is no longer where an earlier scan found it
+pub enum Migration {
+    Missing { path: String, last_seen: i64 },
+    Moved { from: String, to: String },
+}
+
+impl State {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var("XDG_STATE_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".local").join("state"),
+        };
+        Some(base.join("repolice").join("state.toml"))
+    }
+
+    /// A missing file is an empty state, an unreadable one a warning and an empty state
+    pub fn load(path: &Path) -> State {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return State::default();
+        };
+        match toml::from_str(&contents) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Invalid state {}: {}", path.display(), e);
+                State::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Compares a scan of `root` with what earlier scans saw below it and records the scanned repos.
+    /// A known repo that is gone counts as moved when a newly seen repo has the same origin or HEAD commit.
+    pub fn update(&mut self, root: &Path, repos: &[RepoInfo]) -> Vec<Migration> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let now = now();
+        let current: Vec<(String, SeenRepo)> = repos.iter()
+            // sub-path entries live and move with their repo
+            .filter(|repo| !repo.is_subpath())
+            .map(|repo| {
+                let path = Path::new(&repo.path).canonicalize().unwrap_or_else(|_| PathBuf::from(&repo.path));
+                (path.to_string_lossy().to_string(), SeenRepo {
+                    last_seen: now,
+                    origin: repo.remote_url.clone(),
+                    head: repo.last_commit.as_ref().map(|c| c.short_id.clone()),
+                })
+            })
+            .collect();
+
+        // a repo only counts as missing when its directory is gone, not when it was excluded or filtered out
+        let gone: Vec<String> = self.repos.keys()
+            .filter(|path| Path::new(path).starts_with(&root) && !Path::new(path).join(".git").exists())
+            .cloned()
+            .collect();
+        let mut migrations = Vec::new();
+        let mut claimed: Vec<String> = Vec::new();
+        for path in gone {
+            let seen = &self.repos[&path];
+            let same = |new: &SeenRepo| {
+                (seen.origin.is_some() && new.origin == seen.origin) || (seen.head.is_some() && new.head == seen.head)
+            };
+            let moved_to = current.iter()
+                .find(|(new_path, new)| !self.repos.contains_key(new_path) && !claimed.contains(new_path) && same(new));
+            match moved_to {
+                Some((to, _)) => {
+                    claimed.push(to.clone());
+                    migrations.push(Migration::Moved { from: path.clone(), to: to.clone() });
+                    self.repos.remove(&path);
+                }
+                None => migrations.push(Migration::Missing { path, last_seen: seen.last_seen }),
+            }
+        }
+
+        self.repos.extend(current);
+        migrations
+    }
+
+    /// Stops reporting a repo as missing, returns whether it was known at all
+    pub fn forget(&mut self, path: &str) -> bool {
+        let path = PathBuf::from(expand_home(path.trim_end_matches('/')));
+        let path = path.canonicalize().ok()
+            .or_else(|| env::current_dir().ok().map(|cwd| cwd.join(&path)))
+            .unwrap_or(path);
+        self.repos.remove(path.to_string_lossy().as_ref()).is_some()
+    }
+}
@@ -0,0 +1,93 @@
+use repolice_core::{Provider, RepoInfo};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Figures over a whole scan, for `repolice stats` and the stats view of the TUI
+pub struct Stats {
+    pub repos: usize,
+    pub dirty: usize,
+    /// repos per host of their origin, most first
+    pub hosts: Vec<(String, usize)>,
+    /// repos per detected language, most first
+    pub languages: Vec<(String, usize)>,
+    /// commits of checked out branches that their upstream doesn't have
+    pub unpushed: usize,
+    /// the changed file that was modified longest ago: repo name, file and unix time.
+    /// Needs file lists, so only found when the repos were read verbosely.
+    pub oldest_change: Option<(String, String, i64)>,
+    /// repo with the biggest `.git` directory, only known when sizes were measured
+    pub largest: Option<(String, u64)>,
+}
+
+impl Stats {
+    pub fn collect(repos: &[RepoInfo]) -> Stats {
+        let mut hosts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut languages: BTreeMap<String, usize> = BTreeMap::new();
+        for repo in repos {
+            let host = repo.remote_url.as_deref().and_then(Provider::host).unwrap_or_else(|| "no origin".to_string());
+            *hosts.entry(host).or_default() += 1;
+            if repo.languages.is_empty() {
+                *languages.entry("other".to_string()).or_default() += 1;
+            }
+            for language in &repo.languages {
+                *languages.entry(language.name().to_string()).or_default() += 1;
+            }
+        }
+
+        Stats {
+            repos: repos.len(),
+            dirty: repos.iter().filter(|r| r.has_changes()).count(),
+            hosts: Self::by_count(hosts),
+            languages: Self::by_count(languages),
+            unpushed: repos.iter().filter_map(|r| r.upstream.as_ref()).map(|u| u.ahead).sum(),
+            oldest_change: repos.iter().filter_map(Self::oldest_change).min_by_key(|(_, _, time)| *time),
+            largest: repos.iter()
+                .filter_map(|r| r.size.map(|size| (r.name.clone(), size)))
+                .max_by_key(|(_, size)| *size),
+        }
+    }
+
+    pub fn dirty_percent(&self) -> usize {
+        (self.dirty * 100).checked_div(self.repos).unwrap_or(0)
+    }
+
+    /// One line per count with a bar of '#' scaled so the biggest count fills `width`
+    pub fn bars(counts: &[(String, usize)], width: usize) -> Vec<String> {
+        let label_width = counts.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+        let max = counts.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+        counts.iter()
+            .map(|(label, count)| {
+                // every non-zero count gets at least one '#'
+                let bar = (count * width).div_ceil(max);
+                format!("{:<label_width$} {} {}", label, "#".repeat(bar), count)
+            })
+            .collect()
+    }
+
+    fn by_count(counts: BTreeMap<String, usize>) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        // stable, so ties stay in name order
+        counts.sort_by_key(|(_, count)| Reverse(*count));
+        counts
+    }
+
+    /// The changed file of `repo` with the oldest modification time, deleted files have none
+    fn oldest_change(repo: &RepoInfo) -> Option<(String, String, i64)> {
+        // file paths are relative to the repo's root, also for sub-path entries
+        let root = Path::new(&repo.root);
+        let trackers = [&repo.conflicted_files, &repo.new_files, &repo.added_files, &repo.modified_files,
+            &repo.typechanged_files, &repo.renamed_files, &repo.copied_files];
+        trackers.iter()
+            .flat_map(|tracker| tracker.files.iter().flatten())
+            .filter_map(|file| {
+                let file = file.rsplit(" -> ").next().unwrap_or(file);
+                let modified = root.join(file).metadata().ok()?.modified().ok()?;
+                let time = modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+                Some((repo.name.clone(), file.to_string(), time))
+            })
+            .min_by_key(|(_, _, time)| *time)
+    }
+}
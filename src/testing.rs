@@ -0,0 +1,13 @@
+use repolice_core::RepoInfo;
+
+/// A clean repo called `name` at `/src/<name>`, as an older repolice would have sent it, for tests to change
+pub fn repo(name: &str) -> RepoInfo {
+    let tracker = |status: &str| format!(r#"{{"status": "{}", "amount": 0, "staged": 0, "unstaged": 0, "files": null, "staged_files": null, "unstaged_files": null}}"#, status);
+    serde_json::from_str(&format!(
+        r#"{{"name": "{}", "path": "/src/{}", "branch": "main", "repo_state": "normal",
+            "conflicted_files": {}, "new_files": {}, "added_files": {}, "modified_files": {}, "deleted_files": {},
+            "renamed_files": {}, "copied_files": {}, "last_commit": null, "languages": [], "dep_sync": null,
+            "remote_url": null, "provider": null, "remote_check": null, "upstream": null, "stashes": null, "size": null}}"#,
+        name, name, tracker("U"), tracker("??"), tracker("A"), tracker("M"), tracker("D"), tracker("R"), tracker("C"),
+    )).unwrap()
+}
@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+use std::env;
+
+/// Built-in color themes of the TUI and printed listings. Colors are picked for dark backgrounds
+/// throughout the code, a theme swaps each of them for the one it shows instead.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Theme {
+    /// the colors as picked, for dark backgrounds
+    #[default]
+    Dark,
+    /// darker shades that stay readable on light backgrounds
+    Light,
+    /// bright shades only, for washed-out screens and projectors
+    HighContrast,
+    /// no colors at all, bold and reversed text still mark what stands out
+    Mono,
+}
+
+impl Theme {
+    pub fn parse(name: &str) -> Result<Theme, String> {
+        Theme::from_str(name, true).map_err(|_| format!("unknown theme '{}', expected dark, light, high-contrast or mono", name))
+    }
+
+    /// `chosen`, or the default theme, unless NO_COLOR is set to anything but an empty string (see no-color.org)
+    pub fn detect(chosen: Option<Theme>) -> Theme {
+        match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => Theme::Mono,
+            _ => chosen.unwrap_or_default(),
+        }
+    }
+
+    /// What `color` is shown as
+    pub fn color(self, color: Color) -> Color {
+        match self {
+            Theme::Dark => color,
+            Theme::Mono => Color::Reset,
+            Theme::Light => match color {
+                Color::Red | Color::LightRed => Color::Indexed(124),
+                Color::Green | Color::LightGreen => Color::Indexed(28),
+                Color::Yellow | Color::LightYellow => Color::Indexed(130),
+                Color::Blue | Color::LightBlue => Color::Indexed(25),
+                Color::Magenta | Color::LightMagenta => Color::Indexed(90),
+                Color::Cyan | Color::LightCyan => Color::Indexed(30),
+                Color::Gray => Color::Indexed(240),
+                Color::White => Color::Black,
+                _ => color,
+            },
+            Theme::HighContrast => match color {
+                Color::Red => Color::LightRed,
+                Color::Green => Color::LightGreen,
+                Color::Yellow => Color::LightYellow,
+                Color::Blue => Color::LightBlue,
+                Color::Magenta => Color::LightMagenta,
+                Color::Cyan => Color::LightCyan,
+                Color::DarkGray => Color::Gray,
+                Color::Gray => Color::White,
+                _ => color,
+            },
+        }
+    }
+
+    /// Recolors a rendered frame
+    pub fn apply(self, buffer: &mut Buffer) {
+        if self == Theme::Dark {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            cell.fg = self.color(cell.fg);
+            cell.bg = self.color(cell.bg);
+        }
+    }
+
+    /// `text` wrapped in the ANSI escapes for `color`, unchanged for mono
+    pub fn paint(self, text: &str, color: Color) -> String {
+        match ansi_code(self.color(color)) {
+            Some(code) => format!("\x1b[{}m{}\x1b[0m", code, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// SGR parameters setting `color` as the foreground, none for the terminal's default
+fn ansi_code(color: Color) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(index) => return Some(format!("38;5;{}", index)),
+        Color::Rgb(r, g, b) => return Some(format!("38;2;{};{};{}", r, g, b)),
+    };
+    Some(code.to_string())
+}
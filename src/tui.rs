@@ -1,23 +1,55 @@
-use crate::reader::RepoInfo;
+use repolice_core::{Branch, CancellationToken, FileTracker, Level, Reader, ReflogEntry, RemoteCheck, RepoFilter, RepoInfo, RepoState, RepoliceError, ScanOptions, SkippedDir, SortOrder, Totals};
+use crate::format::Format;
+use crate::commit::Committer;
+use crate::clean::Cleaner;
+use crate::resolve::Resolver;
+use crate::printer::Printer;
+use crate::render::{self, Row};
+use crate::pull::Pull;
+use crate::push::Push;
+use crate::operation::{Operation, Step};
+use crate::clipboard::Clipboard;
+use crate::config::{CleanConfig, CommitConfig, PolicyConfig, ShortcutConfig, ToolsConfig};
+use crate::exec::{Exec, ExecResult};
+use crate::launch::{Launcher, Tool};
+use crate::theme::Theme;
+use crate::stats::Stats;
+use crate::group::GroupBy;
+use crate::notes::Notes;
+use crate::session::Session;
+use crate::remote::{CiState, RemoteClient, RemoteFetcher, RemoteStatus};
 
+use std::collections::{HashMap, HashSet};
 use std::io;
-use std::time::Duration;
+use std::ops::Range;
+use std::sync::mpsc;
+use std::thread;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    buffer::Buffer,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame, Terminal,
 };
+use clap::ValueEnum;
 use tokio_stream::StreamExt;
 use futures::stream::Stream;
 
+pub mod picker;
+
+/// How many HEAD reflog entries the detail view shows
+const DETAIL_REFLOG_ENTRIES: usize = 5;
+/// Narrowest a card gets before the grid drops a column
+const MIN_CARD_WIDTH: u16 = 36;
 
 pub struct App {
     pub repos: Vec<RepoInfo>,
@@ -28,62 +60,1173 @@ pub struct App {
     pub loading: bool,
     pub total_found: usize,
     pub clean_scroll_offset: usize,
+    pub selected: usize,
+    pub prompt: Option<CommitPrompt>,
+    pub message: Option<String>,
+    pub commit_config: CommitConfig,
+    pub policy_config: PolicyConfig,
+    pub clean_config: CleanConfig,
+    /// untracked files of the selected repo waiting for a y/n before they are quarantined
+    pub confirm_clean: Option<CleanConfirm>,
+    /// the selected repo's branch waiting for a y/n before it is pushed
+    pub confirm_push: Option<PushConfirm>,
+    /// path of a repo whose conflicts should be opened in the merge tool, the loop hands the terminal over
+    pub resolve: Option<String>,
+    /// tool to open on the repo at the path once the main loop can hand over the terminal
+    pub launch: Option<(Tool, String)>,
+    pub tools_config: ToolsConfig,
+    /// continue or abort of the selected repo's merge, rebase or similar, waiting for a y/n
+    pub confirm_step: Option<Step>,
+    /// cards are narrowed to repos whose name, branch or path contain this
+    pub filter: String,
+    /// the filter input is open and receives key presses
+    pub filter_input: bool,
+    /// used to re-read single repos after an action changed them
+    pub scan_options: ScanOptions,
+    /// the selected repo read again with file lists, shown over the grid while set
+    pub detail: Option<RepoInfo>,
+    /// latest HEAD reflog entries of the repo in the detail view
+    pub detail_activity: Vec<ReflogEntry>,
+    /// list every submodule in the detail view, not only the count
+    pub show_submodules: bool,
+    /// local branches of the repo in the detail view, only read while shown
+    pub detail_branches: Option<Vec<Branch>>,
+    /// figures over all repos, shown over the grid while set
+    pub stats: Option<Stats>,
+    /// stats still being gathered in the background, with the token that stops them
+    pub stats_loading: Option<(mpsc::Receiver<Stats>, CancellationToken)>,
+    /// the repo `r` is reading again in the background
+    pub rescanning: Option<Rescan>,
+    /// every key and the current settings, shown over the grid while set
+    pub show_help: bool,
+    /// directories the search for repos couldn't list, shown over the grid while set
+    pub show_skipped: bool,
+    pub shortcuts: Vec<ShortcutConfig>,
+    pub basic: bool,
+    pub theme: Theme,
+    /// the shortcut command still running, with the path of its repo
+    pub running: Option<(String, String, mpsc::Receiver<ExecResult>)>,
+    /// output of the last shortcut command, shown in the detail view of its repo
+    pub shortcut_output: Option<ShortcutOutput>,
+    pub detail_scroll: u16,
+    /// changed file under the cursor in the detail view, an index into `changed_files` of the repo
+    pub detail_file: usize,
+    /// scroll the detail view to the cursor when it is drawn next, until scrolled by hand
+    detail_follow: bool,
+    pub watch: Option<Duration>,
+    /// a watch re-scan or one asked for with `R` is running, repos it doesn't report again are dropped when it ends
+    pub refreshing: bool,
+    /// `R` was pressed, the loop starts a new scan of every repo
+    pub rescan_requested: bool,
+    pub seen: HashSet<String>,
+    /// stops the running scan, replaced for every scan
+    pub scan_cancel: CancellationToken,
+    /// cards are clustered under a header per group while set
+    pub group_by: Option<GroupBy>,
+    /// group of each card in `repos_with_changes`, empty when not grouping
+    pub card_groups: Vec<String>,
+    /// groups whose cards are folded into their header
+    pub collapsed: HashSet<String>,
+    /// only repos with or without uncommitted changes are shown while set
+    pub state_filter: Option<RepoFilter>,
+    /// paths of repos hidden with `d` until the TUI quits, or until it is resumed, the most recently hidden last
+    pub dismissed: Vec<String>,
+    /// where the session is saved when the TUI quits, see [`Session`]
+    pub session_path: Option<PathBuf>,
+    /// path of the repo selected when the resumed session was saved, selected once its card arrives
+    resume_selection: Option<String>,
+    /// notes and acknowledgements, saved to `notes_path` whenever one changes
+    pub notes: Notes,
+    pub notes_path: Option<PathBuf>,
+    /// the note or acknowledgement being typed for the selected repo
+    pub note_input: Option<NoteInput>,
+    /// asks GitHub and GitLab about the branches of cards, only with `--remote-status`
+    pub remote: Option<RemoteFetcher>,
+    /// what the provider said about each repo by path, or why it couldn't be asked
+    pub remote_status: HashMap<String, Result<RemoteStatus, String>>,
+    /// branch and HEAD each repo was asked about last, it is asked again once they move
+    remote_requested: HashMap<String, String>,
+    /// repos that couldn't be read, listed in the header
+    pub errors: Vec<RepoliceError>,
+    /// where the last frame drew the grid, its cards and the footer, for the mouse
+    hitboxes: Hitboxes,
+}
+
+/// Screen areas of the last frame that react to the mouse
+#[derive(Default)]
+struct Hitboxes {
+    grid: Rect,
+    footer: Rect,
+    /// each card drawn, with its index into `repos_with_changes`
+    cards: Vec<(Rect, usize)>,
+    /// each group header drawn, with the index of the group's first card
+    headers: Vec<(Rect, usize)>,
+}
+
+/// A row of the card grid
+enum GridRow {
+    /// a group's header, `first` is the index of its first card, which stands for the group while it is folded
+    Header { dir: String, cards: usize, first: usize, collapsed: bool },
+    /// up to one row of cards, as indices into `repos_with_changes`
+    Cards(Range<usize>),
+}
+
+/// Settings the TUI is started with
+pub struct TuiOptions {
+    pub verbose: bool,
+    pub commit_config: CommitConfig,
+    pub policy_config: PolicyConfig,
+    pub clean_config: CleanConfig,
+    pub tools_config: ToolsConfig,
+    pub shortcuts: Vec<ShortcutConfig>,
+    /// ASCII only rendering with the 8 base colors
+    pub basic: bool,
+    pub theme: Theme,
+    pub scan_options: ScanOptions,
+    /// re-scan interval, the TUI keeps refreshing cards in place while set
+    pub watch: Option<Duration>,
+    pub group_by: Option<GroupBy>,
+    /// show open pull requests and CI runs of cards, see [`RemoteClient`]
+    pub remote_status: Option<RemoteClient>,
+    /// only dirty or only clean repos at first, `o` changes it
+    pub state_filter: Option<RepoFilter>,
+    /// take up the session saved when the TUI last quit
+    pub resume: bool,
+    /// the sort order was asked for with `--sort` or by the profile, a resumed session doesn't change it
+    pub sort_given: bool,
+}
+
+/// A repo being read again in the background, see [`App::rescan_selected`]
+pub struct Rescan {
+    pub name: String,
+    pub root: String,
+    /// the repo and its sub-path entries once read
+    pub result: mpsc::Receiver<Result<Vec<RepoInfo>, RepoliceError>>,
+}
+
+/// Commit message input for the selected repo
+pub struct CommitPrompt {
+    pub repo_name: String,
+    pub repo_path: String,
+    pub buffer: String,
+    /// remainder of the commit template below the subject line
+    pub body: String,
+    pub signoff: bool,
+    pub error: Option<String>,
+}
+
+/// What the last shortcut command printed
+pub struct ShortcutOutput {
+    pub command: String,
+    /// repo the command ran in
+    pub path: String,
+    pub result: ExecResult,
+}
+
+/// What is typed at the bottom for a repo's note or acknowledgement
+pub struct NoteInput {
+    pub repo_path: String,
+    pub repo_name: String,
+    pub kind: NoteKind,
+    pub buffer: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum NoteKind {
+    /// the note's text, empty removes the note
+    Text,
+    /// days the acknowledgement lasts, empty for until the repo changes
+    Acknowledge,
+}
+
+/// A pending clean of the selected repo
+pub struct CleanConfirm {
+    pub repo_name: String,
+    pub repo_path: String,
+    pub files: usize,
+}
+
+pub struct PushConfirm {
+    pub repo_name: String,
+    pub repo_path: String,
+    pub branch: String,
+    pub upstream: String,
+    pub commits: usize,
 }
 
 impl App {
+    /// Adds a scanned repo, replacing the card of the same path if it was already scanned
     pub fn add_repo(&mut self, repo: RepoInfo) {
-        self.repos.push(repo.clone());
-        self.sort_repos();
-        self.update_repo_separation();
-        self.total_found = self.repos.len();
+        self.seen.insert(repo.path.clone());
+        self.errors.retain(|e| e.path() != repo.path);
+        match self.repos.iter_mut().find(|r| r.path == repo.path) {
+            Some(existing) => *existing = repo,
+            None => self.repos.push(repo),
+        }
+        self.refresh_order();
+    }
+
+    /// Queues cards for `--remote-status` that weren't asked about yet, or whose branch moved since
+    fn request_remote_status(&mut self) {
+        let Some(remote) = &self.remote else {
+            return;
+        };
+        for repo in self.repos_with_changes.iter().filter(|repo| RemoteClient::applies_to(repo)) {
+            let head = format!("{} {}", repo.branch, repo.last_commit.as_ref().map_or("", |commit| commit.short_id.as_str()));
+            if self.remote_requested.get(&repo.path) != Some(&head) {
+                self.remote_requested.insert(repo.path.clone(), head);
+                remote.request(repo);
+            }
+        }
+    }
+
+    pub fn poll_remote_status(&mut self) {
+        let Some(remote) = &self.remote else {
+            return;
+        };
+        while let Some((path, status)) = remote.try_recv() {
+            self.remote_status.insert(path, status);
+        }
+    }
+
+    /// Records a repo that couldn't be read, replacing an earlier error of the same repo.
+    /// A card it had from an earlier scan stays.
+    pub fn add_error(&mut self, error: RepoliceError) {
+        self.seen.insert(error.path().to_string());
+        self.errors.retain(|e| e.path() != error.path());
+        self.errors.push(error);
     }
 
     pub fn set_loading_complete(&mut self) {
         self.loading = false;
+        if self.refreshing {
+            // repos that vanished since the last scan
+            self.refreshing = false;
+            let seen = std::mem::take(&mut self.seen);
+            self.repos.retain(|r| seen.contains(&r.path));
+            self.errors.retain(|e| seen.contains(e.path()));
+            self.refresh_order();
+            if self.watch.is_none() {
+                self.message = Some(format!("Rescanned {} repos", self.repos.len()));
+            }
+        }
+    }
+
+    /// Asks for a new scan of every repo, replacing one still running. Cards are updated in place.
+    pub fn request_rescan(&mut self) {
+        self.rescan_requested = true;
+        self.message = Some("Rescanning every repo...".to_string());
+    }
+
+    /// Reads the selected repo again in the background, its sub-path entries too, see [`App::poll_rescan`].
+    /// A fetch or remote check can take a while, the TUI keeps going meanwhile.
+    pub fn rescan_selected(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        if let Some(rescan) = &self.rescanning {
+            self.message = Some(format!("Still rescanning {}", rescan.name));
+            return;
+        }
+        let (name, root) = (repo.name.clone(), repo.root.clone());
+        let (tx, rx) = mpsc::channel();
+        let (path, options) = (root.clone(), self.scan_options.clone());
+        thread::spawn(move || {
+            let _ = tx.send(Reader::read_repo(&path, &options));
+        });
+        self.message = Some(format!("Rescanning {}...", name));
+        self.rescanning = Some(Rescan { name, root, result: rx });
+    }
+
+    /// Picks up the entries of a finished rescan. Entries that no longer pass the filters lose their card,
+    /// a repo that can't be read keeps its cards.
+    pub fn poll_rescan(&mut self) {
+        let Some(rescan) = &self.rescanning else {
+            return;
+        };
+        let result = match rescan.result.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.rescanning = None;
+                return;
+            }
+        };
+        let Some(Rescan { name, root, .. }) = self.rescanning.take() else {
+            return;
+        };
+        match result {
+            Ok(repos) => {
+                self.repos.retain(|r| r.root != root);
+                repos.into_iter().for_each(|repo| self.add_repo(repo));
+                self.refresh_order();
+                self.message = Some(format!("Rescanned {}", name));
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                self.add_error(e);
+            }
+        }
+        self.refresh_detail();
+    }
+
+    pub fn start_refresh(&mut self) {
+        self.refreshing = true;
+        self.seen.clear();
+    }
+
+    /// Stops the running scan and keeps the repos read so far, repos it didn't get to aren't dropped
+    pub fn stop_scan(&mut self) {
+        self.scan_cancel.cancel();
+        self.loading = false;
+        self.refreshing = false;
+        self.message = Some(format!("Scan stopped after {} repos", self.seen.len()));
+    }
+
+    /// Stops everything still running in the background, called when quitting
+    pub fn cancel_all(&mut self) {
+        self.scan_cancel.cancel();
+        self.close_stats();
+    }
+
+    /// Re-sorts and re-separates the repos while keeping the same repo selected
+    fn refresh_order(&mut self) {
+        let selected_path = self.selected_repo().map(|r| r.path.clone());
+        self.scan_options.sort.sort(&mut self.repos);
+        self.update_repo_separation();
+        self.total_found = self.repos.len();
+        if let Some(path) = selected_path {
+            self.selected = self.repos_with_changes.iter().position(|r| r.path == path).unwrap_or(self.selected);
+        }
+        self.selected = self.selected.min(self.repos_with_changes.len().saturating_sub(1));
+        self.request_remote_status();
     }
 
     fn update_repo_separation(&mut self) {
         self.repos_with_changes.clear();
         self.clean_repos.clear();
         
-        for repo in &self.repos {
-            if repo.has_changes() {
+        let filter = self.filter.to_lowercase();
+        let state = self.state_filter.as_ref();
+        for repo in self.repos.iter()
+            .filter(|r| Self::matches_filter(r, &filter) && !self.dismissed.contains(&r.path) && (r.pinned || state.is_none_or(|state| state.matches(r)))) {
+            // repos behind their upstream get a card too, they can be pulled from the detail view,
+            // and so do repos stuck in a merge or rebase even when nothing is left to resolve
+            // and repos whose only changes are inside submodules or linked worktrees,
+            // and repos ahead of their upstream so they can be pushed, or with hygiene warnings when those are checked,
+            // or over one of the configured thresholds, or nested inside another repo without being its submodule,
+            // and pinned repos always
+            if repo.pinned || repo.has_changes() || repo.is_behind() || repo.is_ahead() || repo.hygiene.as_ref().is_some_and(|h| h.warnings() > 0) || repo.repo_state.in_progress() || repo.submodules_dirty() > 0
+                || repo.worktrees_dirty() > 0 || repo.attention.is_some() || repo.nested_in.is_some() {
                 self.repos_with_changes.push(repo.clone());
             } else {
                 self.clean_repos.push(repo.clone());
             }
         }
+        // acknowledged repos keep their cards, but after all the others unless pinned
+        let notes = &self.notes;
+        self.repos_with_changes.sort_by_cached_key(|repo| !repo.pinned && notes.is_acknowledged(repo));
+        // cards of a group stay in sort order among themselves
+        self.card_groups.clear();
+        if let Some(group_by) = self.group_by {
+            self.repos_with_changes.sort_by_cached_key(|repo| group_by.key(repo));
+            self.card_groups = self.repos_with_changes.iter().map(|repo| group_by.key(repo)).collect();
+        }
+    }
+
+    /// The rows of the card grid from top to bottom, `cols` cards wide
+    fn grid_rows(&self, cols: usize) -> Vec<GridRow> {
+        let len = self.repos_with_changes.len();
+        let card_rows = |cards: Range<usize>| (cards.start..cards.end).step_by(cols)
+            .map(move |start| GridRow::Cards(start..(start + cols).min(cards.end)));
+        if self.card_groups.is_empty() {
+            return card_rows(0..len).collect();
+        }
+        let mut rows = Vec::new();
+        let mut start = 0;
+        while start < len {
+            let dir = &self.card_groups[start];
+            let end = (start..len).find(|i| self.card_groups[*i] != *dir).unwrap_or(len);
+            let collapsed = self.collapsed.contains(dir);
+            rows.push(GridRow::Header { dir: dir.clone(), cards: end - start, first: start, collapsed });
+            if !collapsed {
+                rows.extend(card_rows(start..end));
+            }
+            start = end;
+        }
+        rows
+    }
+
+    /// Lines a grid row takes: one for a group header, the tallest of its cards for a row of cards
+    fn row_height(&self, row: &GridRow) -> u16 {
+        match row {
+            GridRow::Header { .. } => 1,
+            // minimum height (name + branch + border)
+            GridRow::Cards(cards) => self.repos_with_changes[cards.clone()].iter()
+                .map(|repo| calculate_repo_height(repo, self.verbose, self.notes.text(&repo.path)))
+                .fold(3, u16::max),
+        }
+    }
+
+    /// Number of rows from `first` on that fit in `available_height`, at least one
+    fn rows_fitting(&self, rows: &[GridRow], first: usize, available_height: usize) -> usize {
+        let mut height = 0;
+        let fitting = rows.iter().skip(first)
+            .take_while(|row| {
+                height += self.row_height(row) as usize;
+                height <= available_height
+            })
+            .count();
+        fitting.max(1)
+    }
+
+    /// Whether the card at `index` is folded into its group's header
+    fn is_folded(&self, index: usize) -> bool {
+        self.card_groups.get(index).is_some_and(|dir| self.collapsed.contains(dir))
+    }
+
+    /// Folds the group of the selected card into its header, or unfolds it when folded.
+    /// The selection stays on the group.
+    pub fn toggle_group(&mut self, cols: usize, available_height: usize) {
+        let Some(dir) = self.card_groups.get(self.selected).cloned() else {
+            return;
+        };
+        if !self.collapsed.remove(&dir) {
+            self.collapsed.insert(dir.clone());
+            self.selected = self.card_groups.iter().position(|group| *group == dir).unwrap_or(self.selected);
+        }
+        self.move_selection(0, cols, available_height);
+    }
+
+    /// Folds every group, or unfolds them all when any is folded
+    pub fn toggle_all_groups(&mut self, cols: usize, available_height: usize) {
+        if self.collapsed.is_empty() {
+            self.collapsed = self.card_groups.iter().cloned().collect();
+            if let Some(dir) = self.card_groups.get(self.selected) {
+                self.selected = self.card_groups.iter().position(|group| group == dir).unwrap_or(self.selected);
+            }
+        } else {
+            self.collapsed.clear();
+        }
+        self.scroll_offset = 0;
+        self.move_selection(0, cols, available_height);
+    }
+
+    fn matches_filter(repo: &RepoInfo, filter: &str) -> bool {
+        filter.is_empty() || [&repo.name, &repo.branch, &repo.path].iter().any(|field| field.to_lowercase().contains(filter))
+    }
+
+    /// Asks whether to continue or abort the operation the selected repo is in the middle of.
+    /// The detail view opens first so its conflicted files are on screen before deciding.
+    pub fn request_step(&mut self, step: Step) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        if Operation::args(repo.repo_state, step).is_none() {
+            self.message = Some(format!("{} has nothing to {}", repo.name, step.verb()));
+            return;
+        }
+        if step == Step::Continue && repo.has_conflicts() {
+            self.message = Some(format!("Resolve the {} conflicted files of {} first, 'm' to merge", repo.conflicted_files.amount, repo.name));
+            return;
+        }
+        if self.detail.is_none() {
+            self.open_detail();
+        }
+        self.confirm_step = Some(step);
+    }
+
+    /// Runs the confirmed continue or abort and re-checks the repo
+    pub fn step_confirmed(&mut self) {
+        let (Some(step), Some(repo)) = (self.confirm_step.take(), self.selected_repo()) else {
+            return;
+        };
+        let (name, path, state) = (repo.name.clone(), repo.path.clone(), repo.repo_state);
+        let result = Operation::run(&path, state, step);
+        self.recheck_repo(&path);
+        self.message = Some(match result {
+            Ok(summary) => format!("{}: {}", name, summary),
+            Err(e) => format!("Could not {} the {} of {}: {}", step.verb(), state.describe().trim_end_matches(" in progress"), name, e),
+        });
+    }
+
+    /// Copies the selected repo's absolute path, or a `cd` into it, to the clipboard
+    pub fn copy_selected(&mut self, as_cd: bool) {
+        let Some(repo) = self.detail.as_ref().or(self.selected_repo()) else {
+            return;
+        };
+        let path = Path::new(&repo.path).canonicalize().map_or(repo.path.clone(), |p| p.to_string_lossy().to_string());
+        let text = if as_cd { Clipboard::cd_command(&path) } else { path };
+        let tool = Clipboard::copy(&text);
+        self.message = Some(format!("Copied `{}` ({})", text, tool));
+    }
+
+    /// Starts the shortcut bound to function key `number` in the background, for the repo in the detail view
+    pub fn run_shortcut(&mut self, number: u8) {
+        let Some(repo) = &self.detail else {
+            return;
+        };
+        let Some(shortcut) = self.shortcuts.iter().find(|s| s.function_key() == Some(number) && s.applies_to(repo)) else {
+            self.message = Some(format!("No shortcut on F{} for {}", number, repo.name));
+            return;
+        };
+        if let Some((command, _, _)) = &self.running {
+            self.message = Some(format!("Still running `{}`", command));
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let (name, path, command) = (repo.name.clone(), repo.path.clone(), shortcut.command.clone());
+        thread::spawn({
+            let command = command.clone();
+            move || {
+                let _ = tx.send(Exec::run_shell(&name, &path, &command));
+            }
+        });
+        self.message = Some(format!("Running `{}` in {}...", command, repo.name));
+        self.running = Some((command, repo.path.clone(), rx));
+    }
+
+    /// Picks up the result of a finished shortcut command and re-checks its repo
+    pub fn poll_shortcut(&mut self) {
+        let Some((command, path, rx)) = &self.running else {
+            return;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => ExecResult { repo: String::new(), success: false, output: "the command thread died".to_string() },
+        };
+        let (command, path) = (command.clone(), path.clone());
+        self.running = None;
+        self.message = Some(format!("`{}` {}", command, if result.success { "succeeded" } else { "failed" }));
+        self.shortcut_output = Some(ShortcutOutput { command, path: path.clone(), result });
+        self.recheck_repo(&path);
+    }
+
+    /// Re-reads every repo with file lists and sizes in the background, which the oldest change and
+    /// the largest repo need, and shows the stats view once done
+    pub fn open_stats(&mut self) {
+        let mut options = self.scan_options.clone();
+        options.verbose = true;
+        options.size = true;
+        options.cancel = CancellationToken::new();
+        // sub-path entries are read again with their repo
+        let paths: Vec<String> = self.repos.iter()
+            .filter(|r| !r.is_subpath())
+            .map(|r| r.path.clone())
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        let cancel = options.cancel.clone();
+        thread::spawn(move || {
+            let (repos, _) = Reader::collect_repos(paths, &options);
+            if !options.cancel.is_cancelled() {
+                let _ = tx.send(Stats::collect(&repos));
+            }
+        });
+        self.stats_loading = Some((rx, cancel));
+    }
+
+    pub fn poll_stats(&mut self) {
+        let Some((rx, _)) = &self.stats_loading else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(stats) => self.stats = Some(stats),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => self.message = Some("Could not gather stats".to_string()),
+        }
+        self.stats_loading = None;
+    }
+
+    pub fn close_stats(&mut self) {
+        self.stats = None;
+        if let Some((_, cancel)) = self.stats_loading.take() {
+            cancel.cancel();
+        }
+    }
+
+    /// Switches to the next sort order, the repos are re-sorted right away
+    pub fn cycle_sort(&mut self) {
+        self.scan_options.sort = self.scan_options.sort.next();
+        self.refresh_order();
+        self.message = Some(format!("Sorted by {}", self.scan_options.sort.name()));
+    }
+
+    /// Switches between all repos, only dirty ones and only clean ones
+    pub fn cycle_state_filter(&mut self) {
+        self.state_filter = match self.state_filter {
+            None => Some(RepoFilter::Dirty),
+            Some(RepoFilter::Dirty) => Some(RepoFilter::Clean),
+            _ => None,
+        };
+        self.scroll_offset = 0;
+        self.clean_scroll_offset = 0;
+        self.refresh_order();
+        self.message = Some(format!("Showing {}", state_filter_name(self.state_filter.as_ref())));
+    }
+
+    /// Hides the selected card for the rest of the session, the next card gets selected
+    pub fn dismiss_selected(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        let (name, path) = (repo.name.clone(), repo.path.clone());
+        self.dismissed.push(path);
+        self.refresh_order();
+        self.message = Some(format!("Dismissed {}, u brings it back", name));
+    }
+
+    /// Shows the most recently dismissed repo again and selects it
+    pub fn undo_dismiss(&mut self) {
+        let Some(path) = self.dismissed.pop() else {
+            self.message = Some("Nothing dismissed".to_string());
+            return;
+        };
+        self.refresh_order();
+        if let Some(index) = self.repos_with_changes.iter().position(|r| r.path == path) {
+            self.selected = index;
+        }
+        let name = self.repos.iter().find(|r| r.path == path).map_or(path.clone(), |r| r.name.clone());
+        self.message = Some(format!("{} is back, {} still dismissed", name, self.dismissed.len()));
+    }
+
+    /// Starts typing the selected repo's note, with the current note to edit
+    pub fn open_note_input(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        let buffer = self.notes.text(&repo.path).unwrap_or_default().to_string();
+        self.note_input = Some(NoteInput { repo_path: repo.path.clone(), repo_name: repo.name.clone(), kind: NoteKind::Text, buffer });
     }
 
-    fn sort_repos(&mut self) {
-        self.repos.sort_by(|a, b| {
-            match (a.has_changes(), b.has_changes()) {
-                (true, false) => std::cmp::Ordering::Less,    // repos with changes come first
-                (false, true) => std::cmp::Ordering::Greater, // clean repos come last
-                (true, true) => b.total_changes().cmp(&a.total_changes()), // sort by most changes first
-                (false, false) => a.name.cmp(&b.name), // clean repos sorted alphabetically
+    /// Asks how long to acknowledge the selected repo, or withdraws its acknowledgement
+    pub fn toggle_acknowledged(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        if self.notes.is_acknowledged(repo) {
+            let (name, path) = (repo.name.clone(), repo.path.clone());
+            self.notes.unacknowledge(&path);
+            self.save_notes(format!("{} is no longer acknowledged", name));
+            return;
+        }
+        let (repo_path, repo_name) = (repo.path.clone(), repo.name.clone());
+        self.note_input = Some(NoteInput { repo_path, repo_name, kind: NoteKind::Acknowledge, buffer: String::new() });
+    }
+
+    pub fn submit_note_input(&mut self) {
+        let Some(input) = self.note_input.take() else {
+            return;
+        };
+        match input.kind {
+            NoteKind::Text => {
+                self.notes.set_text(&input.repo_path, &input.buffer);
+                let message = if input.buffer.trim().is_empty() { "Note removed from" } else { "Note saved on" };
+                self.save_notes(format!("{} {}", message, input.repo_name));
             }
+            NoteKind::Acknowledge => {
+                let days = match input.buffer.trim() {
+                    "" => None,
+                    days => match days.parse::<u32>() {
+                        Ok(days) => Some(days),
+                        Err(_) => {
+                            self.message = Some(format!("'{}' is not a number of days", days));
+                            return;
+                        }
+                    },
+                };
+                let Some(repo) = self.repos.iter().find(|r| r.path == input.repo_path) else {
+                    return;
+                };
+                self.notes.acknowledge(repo, days);
+                let until = match days {
+                    Some(_) => self.notes.acknowledged_until(&input.repo_path).map_or(String::new(), Format::date),
+                    None => "it changes".to_string(),
+                };
+                self.save_notes(format!("Acknowledged {} until {}", input.repo_name, until));
+            }
+        }
+    }
+
+    /// Pins the selected repo to the front of the cards, or unpins it
+    pub fn toggle_pinned(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        let (name, path) = (repo.name.clone(), repo.path.clone());
+        let pinned = self.notes.toggle_pinned(&path);
+        // rescans pin it too
+        self.scan_options.pinned = self.notes.pinned();
+        for repo in self.repos.iter_mut().filter(|repo| repo.path == path) {
+            repo.pinned = pinned;
+        }
+        self.save_notes(format!("{} {}", if pinned { "Pinned" } else { "Unpinned" }, name));
+    }
+
+    /// Writes the notes and re-orders the cards, `message` is shown unless writing failed
+    fn save_notes(&mut self, message: String) {
+        self.refresh_order();
+        let saved = match &self.notes_path {
+            Some(path) => self.notes.save(path),
+            None => Err("No home directory to keep notes in".to_string()),
+        };
+        self.message = Some(match saved {
+            Ok(()) => message,
+            Err(e) => e,
         });
     }
-    pub fn new(verbose: bool) -> App {
-        App { 
+
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.scroll_offset = 0;
+        self.clean_scroll_offset = 0;
+        self.refresh_order();
+    }
+
+    pub fn new(options: TuiOptions) -> App {
+        let notes_path = Notes::default_path();
+        let session_path = Session::default_path();
+        let session = session_path.as_deref().filter(|_| options.resume).map(Session::load);
+        let sort_given = options.sort_given;
+        let mut app = App {
             repos: Vec::new(),
             repos_with_changes: Vec::new(),
             clean_repos: Vec::new(),
-            verbose, 
+            verbose: options.verbose, 
             scroll_offset: 0,
             loading: true,
             total_found: 0,
             clean_scroll_offset: 0,
+            selected: 0,
+            prompt: None,
+            message: None,
+            commit_config: options.commit_config,
+            policy_config: options.policy_config,
+            clean_config: options.clean_config,
+            confirm_clean: None,
+            confirm_push: None,
+            resolve: None,
+            launch: None,
+            tools_config: options.tools_config,
+            confirm_step: None,
+            filter: String::new(),
+            filter_input: false,
+            scan_options: options.scan_options,
+            detail: None,
+            detail_activity: Vec::new(),
+            show_submodules: false,
+            detail_branches: None,
+            stats: None,
+            stats_loading: None,
+            rescanning: None,
+            show_help: false,
+            show_skipped: false,
+            shortcuts: options.shortcuts,
+            basic: options.basic,
+            theme: options.theme,
+            running: None,
+            shortcut_output: None,
+            detail_scroll: 0,
+            detail_file: 0,
+            detail_follow: false,
+            watch: options.watch,
+            refreshing: false,
+            rescan_requested: false,
+            seen: HashSet::new(),
+            scan_cancel: CancellationToken::new(),
+            group_by: options.group_by,
+            card_groups: Vec::new(),
+            collapsed: HashSet::new(),
+            dismissed: Vec::new(),
+            session_path,
+            resume_selection: None,
+            notes: notes_path.as_deref().map(Notes::load).unwrap_or_default(),
+            notes_path,
+            remote: options.remote_status.map(RemoteFetcher::spawn),
+            state_filter: options.state_filter,
+            remote_status: HashMap::new(),
+            remote_requested: HashMap::new(),
+            note_input: None,
+            errors: Vec::new(),
+            hitboxes: Hitboxes::default(),
+        };
+        if let Some(session) = session {
+            app.restore(session, sort_given);
+        }
+        app
+    }
+
+    /// Takes up the sort order, filters, dismissed repos, folded groups and selection of a saved session
+    /// The sort order and state filter given on the command line win over the saved ones.
+    fn restore(&mut self, session: Session, sort_given: bool) {
+        if !sort_given && let Some(sort) = session.sort.as_deref().and_then(|name| SortOrder::parse(name).ok()) {
+            self.scan_options.sort = sort;
+        }
+        self.filter = session.filter;
+        if self.state_filter.is_none() {
+            self.state_filter = session.state.and_then(|state| RepoFilter::parse(&format!("state={}", state)).ok());
+        }
+        self.dismissed = session.dismissed;
+        self.collapsed = session.collapsed.into_iter().collect();
+        self.resume_selection = session.selected;
+    }
+
+    /// Saves where the TUI was left, for `--resume`
+    pub fn save_session(&self) -> Result<(), String> {
+        let Some(path) = &self.session_path else {
+            return Ok(());
+        };
+        let mut collapsed: Vec<String> = self.collapsed.iter().cloned().collect();
+        collapsed.sort();
+        let state = match self.state_filter {
+            Some(RepoFilter::Dirty) => Some("dirty".to_string()),
+            Some(RepoFilter::Clean) => Some("clean".to_string()),
+            _ => None,
+        };
+        let session = Session {
+            sort: Some(self.scan_options.sort.name().to_string()),
+            filter: self.filter.clone(),
+            state,
+            dismissed: self.dismissed.clone(),
+            collapsed,
+            // the selected card may not have arrived yet
+            selected: self.selected_repo().map(|repo| repo.path.clone()).or_else(|| self.resume_selection.clone()),
+        };
+        session.save(path)
+    }
+
+    /// Selects the card of a resumed session once it has arrived and scrolls it into view,
+    /// gives up when the scan ends without it
+    pub fn select_resumed(&mut self, cols: usize, available_height: usize) {
+        let Some(path) = &self.resume_selection else {
+            return;
+        };
+        match self.repos_with_changes.iter().position(|repo| &repo.path == path) {
+            Some(index) => {
+                self.selected = index;
+                self.resume_selection = None;
+                self.move_selection(0, cols, available_height);
+            }
+            None if !self.loading => self.resume_selection = None,
+            None => {}
+        }
+    }
+
+    /// The repo of the selected card, none while the selection is on a folded group
+    pub fn selected_repo(&self) -> Option<&RepoInfo> {
+        self.repos_with_changes.get(self.selected).filter(|_| !self.is_folded(self.selected))
+    }
+
+    /// Whether the selected repo is on another machine, saying so since nothing can be changed there from here
+    fn refuse_remote(&mut self) -> bool {
+        let Some(repo) = self.selected_repo() else {
+            return false;
+        };
+        let Some(host) = &repo.host else {
+            return false;
+        };
+        self.message = Some(format!("{} is on {}, only repos on this machine can be changed", repo.name, host));
+        true
+    }
+
+    /// Whether the selection is on the header of a folded group
+    pub fn selection_folded(&self) -> bool {
+        self.is_folded(self.selected)
+    }
+
+    /// Moves the selection by `delta` cards and scrolls the grid so the selected card stays visible.
+    /// A folded group counts as one card.
+    pub fn move_selection(&mut self, delta: isize, cols: usize, available_height: usize) {
+        if self.repos_with_changes.is_empty() {
+            return;
+        }
+        let selectable: Vec<usize> = (0..self.repos_with_changes.len())
+            .filter(|i| !self.is_folded(*i) || *i == 0 || self.card_groups[i - 1] != self.card_groups[*i])
+            .collect();
+        let position = selectable.iter().position(|i| *i >= self.selected).unwrap_or(selectable.len() - 1);
+        self.selected = selectable[position.saturating_add_signed(delta).min(selectable.len() - 1)];
+
+        let rows = self.grid_rows(cols);
+        let row = rows.iter()
+            .position(|row| match row {
+                GridRow::Header { first, collapsed, .. } => *collapsed && *first == self.selected,
+                GridRow::Cards(cards) => cards.contains(&self.selected),
+            })
+            .unwrap_or(0);
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+        } else if row >= self.scroll_offset + self.rows_fitting(&rows, self.scroll_offset, available_height) {
+            // scroll just far enough for the selected row to be the last one shown
+            let mut height = 0;
+            let mut offset = row;
+            while offset > self.scroll_offset {
+                height += self.row_height(&rows[offset]) as usize;
+                if height + self.row_height(&rows[offset - 1]) as usize > available_height {
+                    break;
+                }
+                offset -= 1;
+            }
+            self.scroll_offset = offset;
+        }
+    }
+
+    pub fn open_commit_prompt(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        let template = Committer::template(&repo.path);
+        let (subject, body) = template.split_once('\n').unwrap_or((&template, ""));
+        self.prompt = Some(CommitPrompt {
+            repo_name: repo.name.clone(),
+            repo_path: repo.path.clone(),
+            buffer: subject.to_string(),
+            body: body.trim().to_string(),
+            signoff: self.commit_config.signoff || self.policy_config.requires_signoff(&repo.path),
+            error: None,
+        });
+    }
+
+    /// Commits the prompt's message, keeping the prompt open with an error if validation or git fails
+    pub fn submit_commit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        let message = if prompt.body.is_empty() {
+            prompt.buffer.clone()
+        } else {
+            format!("{}\n\n{}", prompt.buffer, prompt.body)
+        };
+        let result = Committer::prepare_message(&message, &self.commit_config)
+            .and_then(|message| Committer::commit(&prompt.repo_path, &message, prompt.signoff));
+        match result {
+            Ok(summary) => {
+                self.message = Some(format!("Committed to {}: {}", prompt.repo_name, summary));
+                let path = prompt.repo_path.clone();
+                self.prompt = None;
+                self.recheck_repo(&path);
+            }
+            Err(e) => prompt.error = Some(e),
+        }
+    }
+
+    pub fn open_clean_confirm(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        match Cleaner::untracked_files(&repo.path) {
+            Ok(files) if files.is_empty() => self.message = Some(format!("No untracked files in {}", repo.name)),
+            Ok(files) => self.confirm_clean = Some(CleanConfirm {
+                repo_name: repo.name.clone(),
+                repo_path: repo.path.clone(),
+                files: files.len(),
+            }),
+            Err(e) => self.message = Some(e),
+        }
+    }
+
+    /// Quarantines the untracked files of the confirmed repo
+    pub fn clean_confirmed(&mut self) {
+        let Some(confirm) = self.confirm_clean.take() else {
+            return;
+        };
+        let result = Cleaner::new(&self.clean_config)
+            .ok_or("No quarantine directory".to_string())
+            .and_then(|cleaner| cleaner.clean(&confirm.repo_path, &confirm.repo_name));
+        self.recheck_repo(&confirm.repo_path);
+        self.message = Some(match result {
+            Ok(Some(batch)) => format!("Moved {} files of {}, restore with `repolice clean --restore {}`",
+                                       batch.manifest.files.len(), confirm.repo_name, batch.id),
+            Ok(None) => format!("No untracked files in {}", confirm.repo_name),
+            Err(e) => e,
+        });
+    }
+
+    pub fn request_launch(&mut self, tool: Tool) {
+        if let Some(repo) = self.selected_repo() {
+            self.launch = Some((tool, repo.path.clone()));
+        }
+    }
+
+    pub fn request_resolve(&mut self) {
+        match self.selected_repo() {
+            Some(repo) if repo.has_conflicts() => self.resolve = Some(repo.path.clone()),
+            Some(repo) => self.message = Some(format!("{} has no conflicts", repo.name)),
+            None => {}
         }
     }
 
+    /// Reads a repo again and updates its cards
+    pub fn recheck_repo(&mut self, path: &str) {
+        match Reader::read_repo(path, &self.scan_options) {
+            Ok(repos) => repos.into_iter().for_each(|repo| self.add_repo(repo)),
+            Err(e) => {
+                self.message = Some(e.to_string());
+                self.add_error(e);
+            }
+        }
+        self.refresh_detail();
+    }
+
+    /// Shows the selected repo in the detail view, with file lists even when the grid isn't verbose
+    pub fn open_detail(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        // a repo on another machine is shown as it was reported, there is nothing here to read again
+        if repo.host.is_some() {
+            self.detail = Some(repo.clone());
+            self.detail_activity = Vec::new();
+            self.detail_branches = None;
+            self.detail_scroll = 0;
+            self.detail_file = 0;
+            self.detail_follow = false;
+            return;
+        }
+        let mut options = self.scan_options.clone();
+        options.verbose = true;
+        options.filters.clear();
+        let detailed = Reader::read_repo(&repo.path, &options).into_iter().flatten().find(|r| r.path == repo.path);
+        let detailed = detailed.unwrap_or_else(|| repo.clone());
+        self.detail_activity = Reader::head_reflog(&detailed.path, DETAIL_REFLOG_ENTRIES);
+        if self.detail_branches.is_some() {
+            self.detail_branches = self.read_branches(&detailed.path);
+        }
+        self.detail = Some(detailed);
+        self.detail_scroll = 0;
+        self.detail_file = 0;
+        self.detail_follow = false;
+    }
+
+    /// Moves the file cursor of the detail view by `delta` files
+    pub fn move_detail_file(&mut self, delta: isize) {
+        let Some(detail) = &self.detail else {
+            return;
+        };
+        let files = changed_files(detail).len();
+        if files > 0 {
+            self.detail_file = self.detail_file.saturating_add_signed(delta).min(files - 1);
+            self.detail_follow = true;
+        }
+    }
+
+    /// Stages the file under the cursor of the detail view, or unstages it when it is fully staged
+    pub fn toggle_staged(&mut self) {
+        let Some(detail) = &self.detail else {
+            return;
+        };
+        let files = changed_files(detail);
+        let Some((tracker, file)) = files.get(self.detail_file) else {
+            return;
+        };
+        if detail.conflicted_files.files.iter().flatten().any(|conflicted| conflicted == *file) {
+            self.message = Some(format!("Resolve the conflicts in {} first, m opens the merge tool", file));
+            return;
+        }
+        // renames and copies are listed as "old -> new", both sides go into the index together
+        let paths: Vec<&str> = file.split(" -> ").collect();
+        let result = match tracker.staging(file) {
+            Some((true, false)) => Committer::unstage(&detail.path, &paths, detail.last_commit.is_none()),
+            _ => Committer::stage(&detail.path, &paths),
+        };
+        let path = detail.path.clone();
+        if let Err(e) = result {
+            self.message = Some(e);
+        }
+        self.detail_follow = true;
+        self.recheck_repo(&path);
+    }
+
+    /// Scrolls the detail view by `delta` lines, leaving the file cursor where it is
+    pub fn scroll_detail(&mut self, delta: i16) {
+        self.detail_scroll = self.detail_scroll.saturating_add_signed(delta);
+        self.detail_follow = false;
+    }
+
+    /// Shows or hides the branch list of the repo in the detail view
+    pub fn toggle_branches(&mut self) {
+        self.detail_branches = match (&self.detail_branches, &self.detail) {
+            (None, Some(detail)) => self.read_branches(&detail.path.clone()),
+            _ => None,
+        };
+    }
+
+    /// The branches of the repo at `path`, or nothing with the reason in the message line
+    fn read_branches(&mut self, path: &str) -> Option<Vec<Branch>> {
+        Reader::branches(path).map_err(|e| self.message = Some(e.to_string())).ok()
+    }
+
+    /// Re-reads the repo in the detail view, closing it when the repo is no longer the selected card
+    fn refresh_detail(&mut self) {
+        let Some(detail) = &self.detail else {
+            return;
+        };
+        if self.selected_repo().is_some_and(|r| r.path == detail.path) {
+            let cursor_file = changed_files(detail).get(self.detail_file).map(|(_, file)| file.to_string());
+            let (scroll, file, follow) = (self.detail_scroll, self.detail_file, self.detail_follow);
+            self.open_detail();
+            // the cursor follows its file into another list, like from Modified to Added when staged,
+            // and stays at the same place when the file is gone
+            let files = self.detail.as_ref().map(changed_files).unwrap_or_default();
+            self.detail_file = files.iter().position(|(_, file)| Some(file.as_str()) == cursor_file.as_deref())
+                .unwrap_or(file.min(files.len().saturating_sub(1)));
+            self.detail_scroll = scroll;
+            self.detail_follow = follow;
+        } else {
+            self.detail = None;
+        }
+    }
+
+    /// Fast-forwards the selected repo if it is clean and only behind its upstream
+    pub fn pull_selected(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        if !Pull::is_candidate(repo) {
+            self.message = Some(format!("{} is not clean and behind its upstream", repo.name));
+            return;
+        }
+        let (name, path) = (repo.name.clone(), repo.path.clone());
+        let result = Pull::run(&path);
+        self.recheck_repo(&path);
+        self.message = Some(match result {
+            Ok(summary) => format!("Pulled {}: {}", name, summary),
+            Err(e) => format!("Could not pull {}: {}", name, e),
+        });
+    }
+
+    pub fn request_push(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            return;
+        };
+        match &repo.upstream {
+            Some(upstream) if Push::is_candidate(repo) => self.confirm_push = Some(PushConfirm {
+                repo_name: repo.name.clone(),
+                repo_path: repo.path.clone(),
+                branch: repo.branch.clone(),
+                upstream: upstream.name.clone(),
+                commits: upstream.ahead,
+            }),
+            Some(upstream) if upstream.ahead > 0 => self.message = Some(format!("{} is behind its upstream too, pull first", repo.name)),
+            _ => self.message = Some(format!("{} has nothing to push", repo.name)),
+        }
+    }
+
+    /// Pushes the confirmed repo's branch and re-checks it
+    pub fn push_confirmed(&mut self) {
+        let Some(confirm) = self.confirm_push.take() else {
+            return;
+        };
+        let result = Push::run(&confirm.repo_path, &confirm.branch);
+        self.recheck_repo(&confirm.repo_path);
+        self.message = Some(match result {
+            Ok(summary) => format!("{}: {}", confirm.repo_name, summary),
+            Err(e) => format!("Could not push {}: {}", confirm.repo_name, e),
+        });
+    }
+
     pub fn scroll_down(&mut self, cols: usize, available_height: usize) {
-        let total_rows = (self.repos_with_changes.len() + cols - 1) / cols;
-        
-        let estimated_visible_rows = (available_height / 6).max(1); // estimate
-        
-        if self.scroll_offset + estimated_visible_rows < total_rows {
+        let rows = self.grid_rows(cols);
+        if self.scroll_offset + self.rows_fitting(&rows, self.scroll_offset, available_height) < rows.len() {
             self.scroll_offset += 1;
         }
     }
@@ -107,9 +1250,12 @@ impl App {
     }
 }
 
-pub async fn run_streaming_tui<S>(repo_stream: S, verbose: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+/// Runs the TUI, `scan` starts a scan that stops once its token is cancelled.
+/// It is called again for every refresh in watch mode.
+pub async fn run_streaming_tui<F, S>(scan: F, options: TuiOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
 where
-    S: Stream<Item = RepoInfo> + Unpin,
+    F: Fn(CancellationToken) -> S,
+    S: Stream<Item = Result<RepoInfo, RepoliceError>> + Unpin,
 {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -117,8 +1263,8 @@ where
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = App::new(verbose);
-    let res = run_streaming_app_loop(&mut terminal, app, repo_stream).await;
+    let app = App::new(options);
+    let res = run_streaming_app_loop(&mut terminal, app, scan).await;
 
     disable_raw_mode()?;
     execute!(
@@ -128,96 +1274,407 @@ where
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = res {
-        println!("{err:?}");
+    match res {
+        Ok(app) => {
+            if let Err(e) = app.save_session() {
+                eprintln!("{}", e);
+            }
+        }
+        Err(err) => println!("{err:?}"),
     }
 
     Ok(())
 }
 
-async fn run_streaming_app_loop<B: Backend, S>(
+async fn run_streaming_app_loop<B: Backend, F, S>(
     terminal: &mut Terminal<B>,
     mut app: App,
-    mut repo_stream: S,
-) -> io::Result<()>
+    scan: F,
+) -> io::Result<App>
 where
-    S: Stream<Item = RepoInfo> + Unpin,
+    F: Fn(CancellationToken) -> S,
+    S: Stream<Item = Result<RepoInfo, RepoliceError>> + Unpin,
 {
+    let mut repo_stream = scan(app.scan_cancel.clone());
+    let mut scan_finished: Option<Instant> = None;
     let mut last_render = std::time::Instant::now();
     let render_interval = Duration::from_millis(100); // Render at most 10 times per second
     
     loop {
+        if let Some(path) = app.resolve.take() {
+            run_resolver(terminal, &mut app, &path)?;
+        }
+        if let Some((tool, path)) = app.launch.take() {
+            run_tool(terminal, &mut app, tool, &path)?;
+        }
+        app.poll_shortcut();
+        app.poll_rescan();
+        app.poll_stats();
+        app.poll_remote_status();
+
         let size = terminal.size()?;
-        let cols = 4;
-        let available_height = size.height.saturating_sub(10); // More space for dynamic content
+        let cols = grid_columns(size.width);
+        let available_height = size.height.saturating_sub(11); // More space for dynamic content
         
         // Check for new repos from the stream (non-blocking)
         match tokio::time::timeout(Duration::from_millis(10), repo_stream.next()).await {
-            Ok(Some(repo_info)) => {
+            Ok(Some(Ok(repo_info))) => {
                 app.add_repo(repo_info);
             }
+            Ok(Some(Err(error))) => {
+                app.add_error(error);
+            }
             Ok(None) => {
                 // Stream is exhausted
-                app.set_loading_complete();
+                if scan_finished.is_none() {
+                    app.set_loading_complete();
+                    scan_finished = Some(Instant::now());
+                }
             }
             Err(_) => {
                 // Timeout - no new repos in this cycle, continue
             }
         }
+        app.select_resumed(cols, available_height as usize);
+
+        // in watch mode, start the next scan once the interval has passed since the last one finished,
+        // and whenever a rescan was asked for, replacing a scan still running
+        let watch_due = matches!((app.watch, scan_finished), (Some(interval), Some(finished)) if finished.elapsed() >= interval);
+        if watch_due || std::mem::take(&mut app.rescan_requested) {
+            app.scan_cancel.cancel();
+            app.scan_cancel = CancellationToken::new();
+            repo_stream = scan(app.scan_cancel.clone());
+            scan_finished = None;
+            app.start_refresh();
+        }
         
         // Throttle rendering to avoid excessive redraws
         if last_render.elapsed() >= render_interval {
-            terminal.draw(|f| ui(f, &app, cols, available_height))?;
+            terminal.draw(|f| draw(f, &mut app, cols, available_height))?;
             last_render = std::time::Instant::now();
         }
 
         // Check for user input (non-blocking)
-        if let Ok(true) = event::poll(Duration::from_millis(50)) {
-            if let Event::Key(key) = event::read()? {
-                let visible_clean_repos = (size.width / 12).max(1) as usize; // Estimate how many clean repos fit
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Down => app.scroll_down(cols, available_height as usize),
-                    KeyCode::Up => app.scroll_up(),
-                    KeyCode::Left => app.scroll_clean_left(),
-                    KeyCode::Right => app.scroll_clean_right(visible_clean_repos),
-                    _ => {}
-                }
-            }
+        if let Ok(true) = event::poll(Duration::from_millis(50))
+            && handle_event(&mut app, event::read()?, cols, available_height as usize, size.width) {
+            app.cancel_all();
+            return Ok(app);
         }
         
         // break if loading is complete and stream is exhausted
         if !app.loading {
-            if let Ok(true) = event::poll(Duration::from_millis(100)) {
-                if let Event::Key(key) = event::read()? {
-                    let visible_clean_repos = (size.width / 12).max(1) as usize;
-                    match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Down => app.scroll_down(cols, available_height as usize),
-                        KeyCode::Up => app.scroll_up(),
-                        KeyCode::Left => app.scroll_clean_left(),
-                        KeyCode::Right => app.scroll_clean_right(visible_clean_repos),
-                        _ => {}
-                    }
+            if let Ok(true) = event::poll(Duration::from_millis(100))
+                && handle_event(&mut app, event::read()?, cols, available_height as usize, size.width) {
+                app.cancel_all();
+                return Ok(app);
+            }
+            terminal.draw(|f| draw(f, &mut app, cols, available_height))?;
+        }
+    }
+}
+
+/// Leaves the TUI while the merge tool or editor runs on the repo's conflicts, then re-checks the repo
+fn run_resolver<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, path: &str) -> io::Result<()> {
+    let mut command = match Resolver::command(path) {
+        Ok(command) => command,
+        Err(e) => {
+            app.message = Some(e);
+            return Ok(());
+        }
+    };
+
+    let marked = Resolver::files_with_markers(path);
+    let status = suspended(terminal, || command.status())?;
+    if status.is_ok() {
+        Resolver::stage_resolved(path, &marked);
+    }
+
+    app.recheck_repo(path);
+    let name = app.repos.iter().find(|r| r.path == path).map_or(path.to_string(), |r| r.name.clone());
+    let remaining = Resolver::conflicted_files(path).len();
+    app.message = Some(match status {
+        Err(e) => format!("Could not start the merge tool: {}", e),
+        Ok(_) if remaining == 0 => format!("No conflicts left in {}", name),
+        Ok(_) => format!("{} still has {} conflicted files", name, remaining),
+    });
+    Ok(())
+}
+
+/// Leaves the TUI while the editor or git tool runs in the repo, then re-checks the repo
+fn run_tool<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, tool: Tool, path: &str) -> io::Result<()> {
+    let mut command = match Launcher::command(tool, &app.tools_config, path) {
+        Ok(command) => command,
+        Err(e) => {
+            app.message = Some(e);
+            return Ok(());
+        }
+    };
+    let status = suspended(terminal, || command.status())?;
+    app.recheck_repo(path);
+    match status {
+        Err(e) => app.message = Some(format!("Could not start {:?}: {}", command.get_program(), e)),
+        Ok(status) if !status.success() => app.message = Some(format!("{:?} exited with {}", command.get_program(), status)),
+        Ok(_) => {}
+    }
+    Ok(())
+}
+
+/// Hands the terminal to `run` and takes it back afterwards, whatever `run` returned
+fn suspended<B: Backend, T>(terminal: &mut Terminal<B>, run: impl FnOnce() -> T) -> io::Result<T> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    let result = run();
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// Cards that fit side by side in a terminal `width` columns wide, at least one
+fn grid_columns(width: u16) -> usize {
+    // the grid has a margin of one column on either side
+    (width.saturating_sub(2) / MIN_CARD_WIDTH).max(1) as usize
+}
+
+/// Applies a key press or mouse action to the app, returns true when the user wants to quit
+fn handle_event(app: &mut App, event: Event, cols: usize, available_height: usize, width: u16) -> bool {
+    match event {
+        Event::Resize(width, height) => {
+            // the selected card may have moved to another row, or out of sight
+            app.move_selection(0, grid_columns(width), height.saturating_sub(11) as usize);
+            false
+        }
+        Event::Key(key) => handle_key(app, key, cols, available_height, width),
+        Event::Mouse(mouse) => {
+            handle_mouse(app, mouse, cols, available_height, width);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// The wheel scrolls whatever is under the pointer, a click selects a card like Tab does.
+/// Clicking the selected card opens its details and clicking a group header folds or unfolds it.
+fn handle_mouse(app: &mut App, mouse: MouseEvent, cols: usize, available_height: usize, width: u16) {
+    // prompts and confirmations wait for their keys
+    if app.prompt.is_some() || app.filter_input || app.note_input.is_some() || app.confirm_clean.is_some() || app.confirm_push.is_some() || app.confirm_step.is_some()
+        || app.stats.is_some() || app.stats_loading.is_some() || app.show_help || app.show_skipped {
+        return;
+    }
+    let at = |area: Rect| area.contains(Position::new(mouse.column, mouse.row));
+    if app.detail.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => app.scroll_detail(-1),
+            MouseEventKind::ScrollDown => app.scroll_detail(1),
+            _ => {}
+        }
+        return;
+    }
+
+    let visible_clean_repos = (width / 12).max(1) as usize;
+    let in_footer = at(app.hitboxes.footer);
+    match mouse.kind {
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollLeft if in_footer => app.scroll_clean_left(),
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollRight if in_footer => app.scroll_clean_right(visible_clean_repos),
+        MouseEventKind::ScrollUp if at(app.hitboxes.grid) => app.scroll_up(),
+        MouseEventKind::ScrollDown if at(app.hitboxes.grid) => app.scroll_down(cols, available_height),
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some((_, index)) = app.hitboxes.cards.iter().find(|(area, _)| at(*area)) {
+                app.message = None;
+                if *index == app.selected {
+                    app.open_detail();
+                } else {
+                    app.selected = *index;
                 }
+            } else if let Some((_, first)) = app.hitboxes.headers.iter().find(|(area, _)| at(*area)) {
+                app.message = None;
+                app.selected = *first;
+                app.toggle_group(cols, available_height);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Applies a key press to the app, returns true when the user wants to quit
+fn handle_key(app: &mut App, key: KeyEvent, cols: usize, available_height: usize, width: u16) -> bool {
+    if let Some(prompt) = app.prompt.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.prompt = None,
+            KeyCode::Enter => app.submit_commit_prompt(),
+            KeyCode::Backspace => { prompt.buffer.pop(); }
+            KeyCode::Char(c) => prompt.buffer.push(c),
+            _ => {}
+        }
+        return false;
+    }
+
+    if let Some(input) = app.note_input.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.note_input = None,
+            KeyCode::Enter => app.submit_note_input(),
+            KeyCode::Backspace => { input.buffer.pop(); }
+            KeyCode::Char(c) => input.buffer.push(c),
+            _ => {}
+        }
+        return false;
+    }
+
+    if app.filter_input {
+        match key.code {
+            KeyCode::Esc => {
+                app.filter_input = false;
+                app.set_filter(String::new());
             }
-            terminal.draw(|f| ui(f, &app, cols, available_height))?;
+            KeyCode::Enter => app.filter_input = false,
+            KeyCode::Backspace => {
+                let mut filter = app.filter.clone();
+                filter.pop();
+                app.set_filter(filter);
+            }
+            KeyCode::Char(c) => app.set_filter(format!("{}{}", app.filter, c)),
+            _ => {}
+        }
+        return false;
+    }
+
+    if app.confirm_clean.is_some() {
+        match key.code {
+            KeyCode::Char('y') => app.clean_confirmed(),
+            _ => app.confirm_clean = None,
+        }
+        return false;
+    }
+
+    if app.confirm_push.is_some() {
+        match key.code {
+            KeyCode::Char('y') => app.push_confirmed(),
+            _ => app.confirm_push = None,
+        }
+        return false;
+    }
+
+    if app.confirm_step.is_some() {
+        match key.code {
+            KeyCode::Char('y') => app.step_confirmed(),
+            _ => app.confirm_step = None,
+        }
+        return false;
+    }
+
+    app.message = None;
+    if app.show_help {
+        match key.code {
+            KeyCode::Char('q') => return true,
+            _ => app.show_help = false,
+        }
+        return false;
+    }
+    if app.show_skipped {
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('K') => app.show_skipped = false,
+            _ => {}
+        }
+        return false;
+    }
+    if app.stats.is_some() || app.stats_loading.is_some() {
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('i') => app.close_stats(),
+            _ => {}
+        }
+        return false;
+    }
+    // committing, cleaning, pulling and the tools all run here, on the local checkout
+    let changes_repo = matches!(key.code, KeyCode::Char('c' | 'x' | 'P' | 'm' | 'e' | 'g' | 'a' | 'C' | 'r'))
+        || (app.detail.is_some() && matches!(key.code, KeyCode::Char(' ' | 'p' | 'b') | KeyCode::F(_)));
+    if changes_repo && app.refuse_remote() {
+        return false;
+    }
+    if app.detail.is_some() {
+        match key.code {
+            // actions on the selected repo work the same as from the grid
+            KeyCode::Char('q' | 'c' | 'm' | 'x' | 'a' | 'C' | 'e' | 'g' | 'P' | 'f' | 'r' | 'R' | '?') => {}
+            KeyCode::Esc | KeyCode::Enter => app.detail = None,
+            KeyCode::Up => app.scroll_detail(-1),
+            KeyCode::Down => app.scroll_detail(1),
+            KeyCode::PageUp => app.scroll_detail(-(available_height as i16)),
+            KeyCode::PageDown => app.scroll_detail(available_height as i16),
+            KeyCode::Home => app.scroll_detail(i16::MIN),
+            KeyCode::End => app.scroll_detail(i16::MAX),
+            KeyCode::Tab => app.move_detail_file(1),
+            KeyCode::BackTab => app.move_detail_file(-1),
+            KeyCode::Char(' ') => app.toggle_staged(),
+            KeyCode::Char('p') => app.pull_selected(),
+            KeyCode::F(number) => app.run_shortcut(number),
+            KeyCode::Char('y') => app.copy_selected(false),
+            KeyCode::Char('Y') => app.copy_selected(true),
+            KeyCode::Char('S') => app.show_submodules = !app.show_submodules,
+            KeyCode::Char('b') => app.toggle_branches(),
+            _ => {}
+        }
+        if !matches!(key.code, KeyCode::Char('q' | 'c' | 'm' | 'x' | 'a' | 'C' | 'e' | 'g' | 'P' | 'n' | 'A' | 'f' | 'r' | 'R' | '?')) {
+            return false;
         }
     }
+
+    let visible_clean_repos = (width / 12).max(1) as usize; // Estimate how many clean repos fit
+    match key.code {
+        KeyCode::Char('q') => return true,
+        KeyCode::Down => app.scroll_down(cols, available_height),
+        KeyCode::Up => app.scroll_up(),
+        KeyCode::Left => app.scroll_clean_left(),
+        KeyCode::Right => app.scroll_clean_right(visible_clean_repos),
+        KeyCode::Tab => app.move_selection(1, cols, available_height),
+        KeyCode::BackTab => app.move_selection(-1, cols, available_height),
+        KeyCode::Char('c') => app.open_commit_prompt(),
+        KeyCode::Char('x') => app.open_clean_confirm(),
+        KeyCode::Char('P') => app.request_push(),
+        KeyCode::Char('m') => app.request_resolve(),
+        KeyCode::Char('e') => app.request_launch(Tool::Editor),
+        KeyCode::Char('g') => app.request_launch(Tool::Git),
+        KeyCode::Char('a') => app.request_step(Step::Abort),
+        KeyCode::Char('C') => app.request_step(Step::Continue),
+        KeyCode::Char('/') => app.filter_input = true,
+        KeyCode::Char('s') => app.cycle_sort(),
+        KeyCode::Char('o') => app.cycle_state_filter(),
+        KeyCode::Char('i') => app.open_stats(),
+        KeyCode::Char('K') => app.show_skipped = !app.scan_options.progress.skipped().is_empty(),
+        KeyCode::Char('?') => app.show_help = true,
+        KeyCode::Char('y') => app.copy_selected(false),
+        KeyCode::Char('Y') => app.copy_selected(true),
+        KeyCode::Char('d') => app.dismiss_selected(),
+        KeyCode::Char('u') => app.undo_dismiss(),
+        KeyCode::Char('n') => app.open_note_input(),
+        KeyCode::Char('A') => app.toggle_acknowledged(),
+        KeyCode::Char('f') => app.toggle_pinned(),
+        KeyCode::Char('r') => app.rescan_selected(),
+        KeyCode::Char('R') => app.request_rescan(),
+        KeyCode::Enter if app.selection_folded() => app.toggle_group(cols, available_height),
+        KeyCode::Enter => app.open_detail(),
+        KeyCode::Char('z') => app.toggle_group(cols, available_height),
+        KeyCode::Char('Z') => app.toggle_all_groups(cols, available_height),
+        KeyCode::Esc if !app.filter.is_empty() => app.set_filter(String::new()),
+        KeyCode::Esc if app.loading => app.stop_scan(),
+        _ => {}
+    }
+    false
 }
 
-fn ui(f: &mut Frame, app: &App, cols: usize, available_height: u16) {
+fn ui(f: &mut Frame, app: &mut App, cols: usize, available_height: u16) {
     let size = f.area();
+    let mut hitboxes = Hitboxes::default();
 
     // Use cached repo separation
     let repos_with_changes = &app.repos_with_changes;
     let clean_repos = &app.clean_repos;
 
-    // create main layout with title, main content, clean repos footer, and instructions
+    // create main layout with title and totals, main content, clean repos footer, and instructions
     let constraints = if clean_repos.is_empty() {
-        vec![Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]
+        vec![Constraint::Length(4), Constraint::Min(0), Constraint::Length(1)]
     } else {
-        vec![Constraint::Length(3), Constraint::Min(0), Constraint::Length(3), Constraint::Length(1)]
+        vec![Constraint::Length(4), Constraint::Min(0), Constraint::Length(3), Constraint::Length(1)]
     };
     
     let chunks = Layout::default()
@@ -227,11 +1684,16 @@ fn ui(f: &mut Frame, app: &App, cols: usize, available_height: u16) {
         .split(size);
 
     // title with scroll status and loading indicator
-    let total_rows = (repos_with_changes.len() + cols - 1) / cols;
-    let estimated_visible_rows = (available_height / 6).max(1) as usize;
+    let rows = app.grid_rows(cols);
+    let total_rows = rows.len();
+    let scrollable = app.scroll_offset > 0 || app.scroll_offset + app.rows_fitting(&rows, app.scroll_offset, available_height as usize) < total_rows;
     let title_text = if app.loading {
-        format!("Repolice - Loading repositories... ({} found)", app.total_found)
-    } else if total_rows > estimated_visible_rows {
+        format!("Repolice - Loading repositories... ({} found, Esc to stop)", app.total_found)
+    } else if let Some(interval) = app.watch {
+        format!("Repolice ({} with changes, {} clean) - watching every {}s{}",
+                repos_with_changes.len(), clean_repos.len(), interval.as_secs(),
+                if app.refreshing { ", refreshing..." } else { "" })
+    } else if scrollable {
         format!("Repolice - Repos with changes (Scroll: {}/{})", 
                 app.scroll_offset + 1, 
                 total_rows)
@@ -239,89 +1701,90 @@ fn ui(f: &mut Frame, app: &App, cols: usize, available_height: u16) {
         format!("Repolice ({} with changes, {} clean)", repos_with_changes.len(), clean_repos.len())
     };
     
-    let title = Paragraph::new(title_text)
-        .style(Style::default().fg(Color::Cyan))
+    let title_text = if app.filter.is_empty() {
+        title_text
+    } else {
+        format!("{} - filter: {}", title_text, app.filter)
+    };
+    let title_text = if app.dismissed.is_empty() {
+        title_text
+    } else {
+        format!("{} - {} dismissed", title_text, app.dismissed.len())
+    };
+    let title_text = match &app.state_filter {
+        Some(state) => format!("{} - {}", title_text, state_filter_name(Some(state))),
+        None => title_text,
+    };
+    let title_text = if app.scan_options.sort == SortOrder::Changes {
+        title_text
+    } else {
+        format!("{} - sorted by {}", title_text, app.scan_options.sort.name())
+    };
+    // totals cover every scanned repo, the text filter only narrows the cards
+    let mut totals = vec![Span::raw(Totals::of(&app.repos).describe())];
+    if !app.errors.is_empty() {
+        let errors: Vec<String> = app.errors.iter()
+            .map(|e| format!("{} ({})", Reader::repo_name(e.path()), e.reason()))
+            .collect();
+        totals.push(Span::styled(format!(" - {} could not be read: {}", app.errors.len(), errors.join(", ")), Style::default().fg(Color::Red)));
+    }
+    let skipped = app.scan_options.progress.skipped();
+    if !skipped.is_empty() {
+        totals.push(Span::styled(format!(" - {} directories skipped (K lists them)", skipped.len()), Style::default().fg(Color::Red)));
+    }
+    let title = Paragraph::new(vec![
+        Line::from(Span::styled(title_text, Style::default().fg(Color::Cyan))),
+        Line::from(totals),
+    ])
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
     // create grid layout for visible repos with changes only
     if !repos_with_changes.is_empty() {
-        // calculate how many repos can fit in the available height
+        // as many rows from the scroll offset on as fit in the available height
         let mut current_height = 0u16;
-        let mut visible_repos = Vec::new();
-        let mut row_idx = app.scroll_offset;
-        
-        while current_height < available_height && row_idx * cols < repos_with_changes.len() {
-            let mut max_height_in_row = 0u16;
-            let mut repos_in_row = Vec::new();
-            
-            for col_idx in 0..cols {
-                let repo_idx = row_idx * cols + col_idx;
-                if repo_idx < repos_with_changes.len() {
-                    let repo = &repos_with_changes[repo_idx];
-                    let repo_height = calculate_repo_height(repo, app.verbose);
-                    max_height_in_row = max_height_in_row.max(repo_height);
-                    repos_in_row.push(repo);
-                }
-            }
-            
-            if current_height + max_height_in_row <= available_height {
-                visible_repos.extend(repos_in_row);
-                current_height += max_height_in_row;
-                row_idx += 1;
-            } else {
+        let mut visible_rows = Vec::new();
+        for row in rows.iter().skip(app.scroll_offset) {
+            let height = app.row_height(row);
+            if current_height + height > available_height {
                 break;
             }
+            current_height += height;
+            visible_rows.push((row, height));
         }
-        
-        let actual_visible_rows = (visible_repos.len() + cols - 1) / cols;
-
-        if actual_visible_rows > 0 {
-            // calculate dynamic heights for each row based on content
-            let mut row_heights = Vec::new();
-            for row_idx in 0..actual_visible_rows {
-                let mut max_height_in_row = 3; // minimum height (name + branch + border)
-                
-                for col_idx in 0..cols {
-                    let repo_idx = row_idx * cols + col_idx;
-                    if repo_idx < visible_repos.len() {
-                        let repo = visible_repos[repo_idx];
-                        let repo_height = calculate_repo_height(repo, app.verbose);
-                        max_height_in_row = max_height_in_row.max(repo_height);
-                    }
+
+        let row_constraints: Vec<Constraint> = visible_rows.iter()
+            .map(|(_, height)| Constraint::Length(*height))
+            .collect();
+        let row_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(row_constraints)
+            .split(chunks[1]);
+
+        hitboxes.grid = chunks[1];
+        for ((row, _), row_chunk) in visible_rows.iter().zip(row_chunks.iter()) {
+            match row {
+                GridRow::Header { dir, cards, first, collapsed } => {
+                    let selected = *collapsed && *first == app.selected;
+                    render_group_header(f, *row_chunk, dir, *cards, *collapsed, selected);
+                    hitboxes.headers.push((*row_chunk, *first));
                 }
-                row_heights.push(max_height_in_row);
-            }
-            
-            // create constraints based on calculated heights
-            let row_constraints: Vec<Constraint> = row_heights
-                .iter()
-                .map(|&height| Constraint::Length(height))
-                .collect();
-
-            let row_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(row_constraints)
-                .split(chunks[1]);
-
-            for (row_idx, row_chunk) in row_chunks.iter().enumerate() {
-                let col_constraints = vec![Constraint::Percentage(25); 4];
-                let col_chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints(col_constraints)
-                    .split(*row_chunk);
-
-                for col_idx in 0..cols {
-                    let repo_idx = row_idx * cols + col_idx;
-                    if repo_idx < visible_repos.len() {
-                        let repo = visible_repos[repo_idx];
-                        render_repo_widget(f, col_chunks[col_idx], repo, app.verbose);
+                GridRow::Cards(cards) => {
+                    let col_constraints = vec![Constraint::Ratio(1, cols as u32); cols];
+                    let col_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(col_constraints)
+                        .split(*row_chunk);
+                    for (col_chunk, repo_idx) in col_chunks.iter().zip(cards.clone()) {
+                        let repo = &repos_with_changes[repo_idx];
+                        render_repo_widget(f, *col_chunk, app, repo, repo_idx == app.selected);
+                        hitboxes.cards.push((*col_chunk, repo_idx));
                     }
                 }
             }
         }
     } else {
-        let no_repos = Paragraph::new("No repositories with changes")
+        let no_repos = Paragraph::new(if app.filter.is_empty() { "No repositories with changes" } else { "No repositories with changes match the filter" })
             .style(Style::default().fg(Color::Yellow))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(no_repos, chunks[1]);
@@ -330,41 +1793,621 @@ fn ui(f: &mut Frame, app: &App, cols: usize, available_height: u16) {
     // only render clean repos footer if there are any
     if !clean_repos.is_empty() {
         render_clean_repos_footer(f, chunks[2], clean_repos, app.clean_scroll_offset, size.width);
+        hitboxes.footer = chunks[2];
     }
 
-    let instruction_text = if clean_repos.is_empty() {
-        if total_rows > estimated_visible_rows {
-            "Press ↑/↓ to scroll, 'q' to quit"
-        } else {
-            "Press 'q' to quit"
+    // every key doesn't fit a line, `?` lists them all
+    let keys = "Tab select, Enter details, c commit, / filter, s sort, o dirty/clean, i stats, ? help, q quit".to_string();
+    let instruction_text = match (clean_repos.is_empty(), scrollable) {
+        (true, true) => format!("↑/↓ scroll, {}", keys),
+        (true, false) => keys,
+        (false, true) => format!("↑/↓ scroll repos, ←/→ scroll clean repos, {}", keys),
+        (false, false) => format!("←/→ scroll clean repos, {}", keys),
+    };
+    
+    let instruction_text = match &app.detail {
+        _ if app.show_help => "any key closes the help, q quit".to_string(),
+        _ if app.stats.is_some() || app.stats_loading.is_some() || app.show_skipped => "Esc close, q quit".to_string(),
+        Some(repo) if repo.repo_state.in_progress() =>
+            "Esc close, ↑/↓ scroll, Tab file, space stage/unstage, m merge conflicts, C continue, a abort, ? help, q quit".to_string(),
+        Some(_) => "Esc close, ↑/↓ scroll, Tab file, space stage/unstage, c commit, p/P pull/push, ? help, q quit".to_string(),
+        None => instruction_text,
+    };
+    let confirm_step = app.confirm_step.zip(app.selected_repo()).map(|(step, repo)| {
+        let operation = repo.repo_state.describe().trim_end_matches(" in progress");
+        Paragraph::new(format!("{} the {} of {}? (y/n)", capitalize(step.verb()), operation, repo.name))
+            .style(Style::default().fg(Color::Yellow))
+    });
+    let instructions = match (&app.confirm_clean, &app.message) {
+        _ if app.filter_input => Paragraph::new(format!("/{}_", app.filter))
+            .style(Style::default().fg(Color::Yellow)),
+        _ if let Some(input) = &app.note_input => {
+            let question = match input.kind {
+                NoteKind::Text => format!("Note on {} (empty removes it)", input.repo_name),
+                NoteKind::Acknowledge => format!("Acknowledge {} for how many days? (empty: until it changes)", input.repo_name),
+            };
+            Paragraph::new(format!("{}: {}_", question, input.buffer)).style(Style::default().fg(Color::Yellow))
         }
-    } else {
-        if total_rows > estimated_visible_rows {
-            "Press ↑/↓ to scroll repos, ←/→ to scroll clean repos, 'q' to quit"
-        } else {
-            "Press ←/→ to scroll clean repos, 'q' to quit"
+        _ if let Some(confirm) = confirm_step => confirm,
+        _ if let Some(confirm) = &app.confirm_push => {
+            let commits = if confirm.commits == 1 { "1 commit".to_string() } else { format!("{} commits", confirm.commits) };
+            Paragraph::new(format!("Push {} of {} [{}] to {}? (y/n)", commits, confirm.repo_name, confirm.branch, confirm.upstream))
+                .style(Style::default().fg(Color::Yellow))
         }
+        (Some(confirm), _) => Paragraph::new(format!("Move {} untracked files of {} to the quarantine? (y/n)", confirm.files, confirm.repo_name))
+            .style(Style::default().fg(Color::Yellow)),
+        (None, Some(message)) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Green)),
+        (None, None) => Paragraph::new(instruction_text).style(Style::default().fg(Color::Gray)),
     };
-    
-    let instructions = Paragraph::new(instruction_text)
-        .style(Style::default().fg(Color::Gray));
     let instruction_chunk = if clean_repos.is_empty() { chunks[2] } else { chunks[3] };
     f.render_widget(instructions, instruction_chunk);
+
+    if let Some(scroll) = app.detail.as_ref().map(|repo| render_detail(f, chunks[1], app, repo)) {
+        app.detail_scroll = scroll;
+    }
+    if app.stats.is_some() || app.stats_loading.is_some() {
+        render_stats(f, chunks[1], app.stats.as_ref());
+    }
+    if app.show_skipped {
+        render_skipped(f, chunks[1], &skipped);
+    }
+    if app.show_help {
+        render_help(f, chunks[1], app);
+    }
+    if let Some(prompt) = &app.prompt {
+        render_commit_prompt(f, size, prompt, &app.commit_config);
+    }
+    app.hitboxes = hitboxes;
+}
+
+fn draw(f: &mut Frame, app: &mut App, cols: usize, available_height: u16) {
+    ui(f, app, cols, available_height);
+    app.theme.apply(f.buffer_mut());
+    if app.basic {
+        to_basic(f.buffer_mut());
+    }
+}
+
+/// Rewrites a rendered frame for terminals that garble anything fancy: ASCII borders and arrows,
+/// only the 8 base colors and no dim, italic or blinking text. Thick borders become '#' and '=',
+/// so the selected card still stands out.
+fn to_basic(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        let symbol = match cell.symbol() {
+            "─" | "═" => Some("-"),
+            "━" => Some("="),
+            "│" | "║" => Some("|"),
+            "┃" | "┏" | "┓" | "┗" | "┛" => Some("#"),
+            "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "┼" | "├" | "┤" | "┬" | "┴" => Some("+"),
+            "↑" => Some("^"),
+            "▸" => Some(">"),
+            "▾" => Some("v"),
+            "↓" => Some("v"),
+            "←" => Some("<"),
+            "→" => Some(">"),
+            _ => None,
+        };
+        if let Some(symbol) = symbol {
+            cell.set_symbol(symbol);
+        }
+        cell.fg = basic_color(cell.fg);
+        cell.bg = basic_color(cell.bg);
+        cell.modifier.remove(Modifier::DIM | Modifier::ITALIC | Modifier::SLOW_BLINK | Modifier::RAPID_BLINK);
+    }
+}
+
+/// Nearest of the 8 base colors, blue becomes cyan and grays become white for contrast on dark backgrounds
+fn basic_color(color: Color) -> Color {
+    match color {
+        Color::Reset | Color::Black | Color::Red | Color::Green | Color::Yellow | Color::Magenta | Color::Cyan => color,
+        Color::Blue | Color::LightBlue | Color::LightCyan => Color::Cyan,
+        Color::LightRed => Color::Red,
+        Color::LightGreen => Color::Green,
+        Color::LightYellow => Color::Yellow,
+        Color::LightMagenta => Color::Magenta,
+        Color::Gray | Color::DarkGray | Color::White | Color::Rgb(..) | Color::Indexed(_) => Color::Gray,
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// A group's header line with its directory and number of cards, folded groups can be selected
+fn render_group_header(f: &mut Frame, area: Rect, dir: &str, cards: usize, collapsed: bool, selected: bool) {
+    let marker = if collapsed { "▸" } else { "▾" };
+    let mut style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    if selected {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    let text = format!("{} {}/ ({} with changes)", marker, Printer::tilde(dir), cards);
+    f.render_widget(Paragraph::new(Span::styled(text, style)), area);
+}
+
+fn state_filter_name(state: Option<&RepoFilter>) -> &'static str {
+    match state {
+        Some(RepoFilter::Dirty) => "only dirty repos",
+        Some(RepoFilter::Clean) => "only clean repos",
+        _ => "all repos",
+    }
+}
+
+/// A row of the render module in the TUI's styles
+fn row_spans(row: Row) -> Vec<Span<'static>> {
+    row.into_iter()
+        .map(|segment| {
+            let style = segment.color.map_or(Style::default(), |color| Style::default().fg(color));
+            Span::styled(segment.text, if segment.bold { style.add_modifier(Modifier::BOLD) } else { style })
+        })
+        .collect()
+}
+
+fn row_line(row: Row) -> Line<'static> {
+    Line::from(row_spans(row))
+}
+
+fn ci_color(ci: CiState) -> Color {
+    match ci {
+        CiState::Success => Color::Green,
+        CiState::Failure => Color::Red,
+        CiState::Pending => Color::Yellow,
+    }
+}
+
+/// Draws the detail view, returning how far it is scrolled, which follows the file cursor when asked to
+fn render_detail(f: &mut Frame, area: Rect, app: &App, repo: &RepoInfo) -> u16 {
+    let (activity, mut scroll) = (&app.detail_activity, app.detail_scroll);
+    let mut lines = vec![
+        row_line(render::title(repo)),
+        Line::from(Span::styled(&repo.path, Style::default().fg(Color::DarkGray))),
+    ];
+
+    let mut branch_spans = row_spans(vec![render::branch(repo)]);
+    if let Some(upstream) = &repo.upstream {
+        branch_spans.push(Span::raw(format!(" tracking {}", upstream.name)));
+        branch_spans.push(Span::styled(Printer::ahead_behind(upstream), Style::default().fg(Color::Cyan)));
+    }
+    lines.push(Line::from(branch_spans));
+    if repo.repo_state != RepoState::Normal {
+        lines.push(state_warning(repo.repo_state));
+    }
+    if let Some(url) = &repo.remote_url {
+        lines.push(Line::from(Span::styled(format!("origin {}", url), Style::default().fg(Color::Blue))));
+    }
+    if repo.local_only {
+        lines.push(Line::from(Span::styled("local only: no remote, nothing here is backed up elsewhere", Style::default().fg(Color::Red))));
+    }
+    match app.remote_status.get(&repo.path) {
+        Some(Ok(status)) => {
+            match &status.pull_request {
+                Some(pr) => lines.push(Line::from(vec![
+                    Span::styled(format!("{}{} ", if pr.draft { "Draft " } else { "" }, pr.reference), Style::default().fg(Color::Cyan)),
+                    Span::raw(pr.title.clone()),
+                    Span::styled(format!(" {}", pr.url), Style::default().fg(Color::DarkGray)),
+                ])),
+                None => lines.push(Line::from(Span::styled("No open pull request for this branch", Style::default().fg(Color::DarkGray)))),
+            }
+            if let Some(ci) = status.ci {
+                lines.push(Line::from(Span::styled(format!("CI {}", ci.describe()), Style::default().fg(ci_color(ci)))));
+            }
+        }
+        Some(Err(reason)) => {
+            lines.push(Line::from(Span::styled(format!("remote status unavailable: {}", reason), Style::default().fg(Color::Red))));
+        }
+        None => {}
+    }
+    if let Some(note) = app.notes.text(&repo.path) {
+        lines.push(Line::from(Span::styled(format!("Note: {}", note), Style::default().add_modifier(Modifier::ITALIC))));
+    }
+    if app.notes.is_acknowledged(repo) {
+        let until = app.notes.acknowledged_until(&repo.path).map_or("it changes".to_string(), Format::date);
+        lines.push(Line::from(Span::styled(format!("Acknowledged until {} (A withdraws)", until), Style::default().fg(Color::DarkGray))));
+    }
+    if let Some(RemoteCheck::Unreachable(reason)) = &repo.remote_check {
+        lines.push(Line::from(Span::styled(format!("origin unreachable: {}", reason), Style::default().fg(Color::Red))));
+    }
+    if let Some(commit) = &repo.last_commit {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", commit.short_id), Style::default().fg(Color::DarkGray)),
+            Span::raw(commit.subject.clone()),
+            Span::styled(format!(" ({}, {})", commit.author, Format::current().time(commit.time)), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    if let Some(attention) = &repo.attention {
+        lines.push(Line::from(Span::styled(format!("Needs attention, {}", attention.describe()), Style::default().fg(Printer::level_color(attention.level)).add_modifier(Modifier::BOLD))));
+    }
+    if let Some(default) = &repo.default_branch {
+        let color = if default.behind > 0 { Color::Yellow } else { Color::DarkGray };
+        lines.push(Line::from(Span::styled(
+            format!("{} commits off {}, which moved on by {} since", default.ahead, default.name, default.behind),
+            Style::default().fg(color),
+        )));
+    }
+    if repo.shallow {
+        lines.push(Line::from(Span::styled("Shallow clone: history is cut off, so commits ahead and behind may be off", Style::default().fg(Color::Yellow))));
+    }
+    if repo.partial {
+        lines.push(Line::from(Span::styled("Partial clone: left out objects are fetched from the promisor remote when needed", Style::default().fg(Color::Yellow))));
+    }
+    if let Some(parent) = &repo.nested_in {
+        lines.push(Line::from(Span::styled(format!("Nested inside {}, which doesn't register it as a submodule", Printer::tilde(parent)), Style::default().fg(Color::Yellow))));
+    }
+    if !repo.gone_branches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("Upstream gone, --prune-gone deletes the merged ones: {}", repo.gone_branches.join(", ")),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if !repo.nested_repos.is_empty() {
+        lines.push(Line::from(Span::styled(format!("Holds repos that aren't submodules: {}", repo.nested_repos.join(", ")), Style::default().fg(Color::Yellow))));
+    }
+    if let (Some(since), Some(age)) = (repo.dirty_since, repo.dirty_for()) {
+        lines.push(Line::from(Span::styled(format!("Dirty for {}, the oldest changed file dates from {}", age, Format::date(since)), Style::default().fg(Color::DarkGray))));
+    }
+    if let Some(sync) = repo.dep_sync {
+        lines.push(Line::from(sync.describe()));
+    }
+    if let Some(stashes) = repo.stashes.filter(|n| *n > 0) {
+        lines.push(Line::from(Span::styled(format!("{} stash entries", stashes), Style::default().fg(Color::Yellow))));
+    }
+    if let Some(ignored) = repo.ignored.as_ref().filter(|ignored| ignored.total > 0) {
+        lines.push(Line::from(vec![
+            Span::raw(format!("Ignored files: {} ", Format::current().size(ignored.total))),
+            Span::styled(format!("({})", Printer::largest_ignored(ignored)), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    if repo.has_changes() {
+        lines.push(Line::from(Printer::staging_summary(repo)));
+    }
+    if !repo.submodules.is_empty() {
+        let color = if repo.submodules_dirty() > 0 { Color::Red } else { Color::DarkGray };
+        let summary = format!("Submodules: {}, {} changed", repo.submodules.len(), repo.submodules_dirty());
+        let hint = if app.show_submodules { "S hide" } else { "S show" };
+        lines.push(Line::from(vec![
+            Span::styled(summary, Style::default().fg(color)),
+            Span::styled(format!(" ({})", hint), Style::default().fg(Color::DarkGray)),
+        ]));
+        if app.show_submodules {
+            for submodule in &repo.submodules {
+                let color = if submodule.needs_attention() { Color::Red } else { Color::DarkGray };
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {} ", submodule.path)),
+                    Span::styled(submodule.describe(), Style::default().fg(color)),
+                ]));
+            }
+        }
+    }
+    if !repo.worktrees.is_empty() {
+        let color = if repo.worktrees_dirty() > 0 { Color::Red } else { Color::DarkGray };
+        lines.push(Line::from(Span::styled(
+            format!("Worktrees: {}, {} dirty", repo.worktrees.len(), repo.worktrees_dirty()),
+            Style::default().fg(color),
+        )));
+        for worktree in &repo.worktrees {
+            let color = if worktree.is_dirty() || worktree.missing { Color::Red } else { Color::DarkGray };
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {} ", Printer::tilde(&worktree.path))),
+                Span::styled(worktree.describe(), Style::default().fg(color)),
+            ]));
+        }
+    }
+
+    match &app.detail_branches {
+        Some(branches) => {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Branches (b hide)", Style::default().add_modifier(Modifier::BOLD))));
+            for branch in branches {
+                // only commits that are on no remote at all can be lost with the clone
+                let color = match &branch.upstream {
+                    None if branch.unpushed > 0 => Color::Red,
+                    Some(upstream) if upstream.ahead > 0 => Color::Yellow,
+                    _ => Color::DarkGray,
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{} {} ", if branch.current { "*" } else { " " }, branch.name), Style::default().fg(Color::Green)),
+                    Span::styled(Printer::branch_state(branch), Style::default().fg(color)),
+                    Span::styled(format!(" ({})", Format::current().time(branch.time)), Style::default().fg(Color::DarkGray)),
+                ]));
+            }
+        }
+        None if repo.host.is_none() => lines.push(Line::from(Span::styled("b list branches", Style::default().fg(Color::DarkGray)))),
+        // a repo on another machine has no branches or shortcuts here
+        None => {}
+    }
+
+    let shortcuts: Vec<String> = app.shortcuts.iter()
+        .filter(|s| s.function_key().is_some() && s.applies_to(repo) && repo.host.is_none())
+        .map(|s| format!("{} {}", s.key.to_uppercase(), s.command))
+        .collect();
+    if !shortcuts.is_empty() {
+        lines.push(Line::from(Span::styled(format!("Shortcuts: {}", shortcuts.join(", ")), Style::default().fg(Color::DarkGray))));
+    }
+    if let Some((command, path, _)) = &app.running && *path == repo.path {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!("Running `{}`...", command), Style::default().fg(Color::Yellow))));
+    } else if let Some(output) = app.shortcut_output.as_ref().filter(|o| o.path == repo.path) {
+        let color = if output.result.success { Color::Green } else { Color::Red };
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!("Output of `{}`", output.command), Style::default().fg(color).add_modifier(Modifier::BOLD))));
+        for line in output.result.output.lines() {
+            lines.push(Line::from(format!("  {}", line)));
+        }
+    }
+
+    if !activity.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Recent activity", Style::default().add_modifier(Modifier::BOLD))));
+        for entry in activity {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {} ", entry.short_id), Style::default().fg(Color::DarkGray)),
+                Span::raw(entry.message.clone()),
+                Span::styled(format!(" ({})", Format::current().time(entry.time)), Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+    }
+
+    let mut file_index = 0;
+    let mut cursor_line = None;
+    for (tracker, color) in repo.trackers().into_iter().zip(render::TRACKER_COLORS) {
+        if tracker.amount == 0 {
+            continue;
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("{} ({})", tracker.status, tracker.amount),
+            Style::default().fg(color).add_modifier(Modifier::BOLD))));
+        for file in tracker.files.iter().flatten() {
+            let style = if file_index == app.detail_file {
+                cursor_line = Some(lines.len() as u16);
+                Style::default().fg(color).add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default().fg(color)
+            };
+            file_index += 1;
+            let mut spans = vec![Span::raw("  "), Span::styled(file.as_str(), style)];
+            match tracker.staging(file) {
+                Some((true, true)) => spans.push(Span::styled(" partly staged", Style::default().fg(Color::DarkGray))),
+                Some((true, false)) => spans.push(Span::styled(" staged", Style::default().fg(Color::Green))),
+                _ => {}
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+    if let Some(hygiene) = repo.hygiene.as_ref().filter(|hygiene| hygiene.warnings() > 0) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!("Hygiene ({})", hygiene.describe()), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+        for (files, why) in [(&hygiene.tracked_ignored, "tracked but ignored"), (&hygiene.unignored, "not ignored")] {
+            for file in files {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {}", file), Style::default().fg(Color::Yellow)),
+                    Span::styled(format!(" {}", why), Style::default().fg(Color::DarkGray)),
+                ]));
+            }
+        }
+    }
+    if !repo.has_changes() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Nothing new here!", Style::default().fg(Color::LightCyan).add_modifier(Modifier::ITALIC))));
+    }
+
+    // inside the borders
+    let height = area.height.saturating_sub(2).max(1);
+    if app.detail_follow && let Some(line) = cursor_line {
+        scroll = scroll.clamp((line + 1).saturating_sub(height), line);
+    }
+    // the last page stays full, however far it is scrolled
+    scroll = scroll.min(u16::try_from(lines.len()).unwrap_or(u16::MAX).saturating_sub(height));
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan)))
+        .scroll((scroll, 0));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    scroll
+}
+
+/// Every changed file of `repo` with its tracker, in the order the detail view lists them
+fn changed_files(repo: &RepoInfo) -> Vec<(&FileTracker, &String)> {
+    repo.trackers().into_iter()
+        .filter(|tracker| tracker.amount > 0)
+        .flat_map(|tracker| tracker.files.iter().flatten().map(move |file| (tracker, file)))
+        .collect()
 }
 
-fn calculate_repo_height(repo: &RepoInfo, verbose: bool) -> u16 {
+/// Stats over all repos with bar charts for the hosts and languages
+fn render_stats(f: &mut Frame, area: Rect, stats: Option<&Stats>) {
+    let mut lines = vec![Line::from(Span::styled("Workspace stats", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)))];
+    match stats {
+        None => lines.push(Line::from(Span::styled("Gathering stats...", Style::default().fg(Color::DarkGray)))),
+        Some(stats) => {
+            lines.push(Line::from(format!("{} repos, {} with uncommitted changes ({}%)", stats.repos, stats.dirty, stats.dirty_percent())));
+            lines.push(Line::from(format!("{} unpushed commits", stats.unpushed)));
+            if let Some((repo, file, time)) = &stats.oldest_change {
+                lines.push(Line::from(format!("oldest uncommitted change: {} in {}, {}", file, repo, Format::date(*time))));
+            }
+            if let Some((repo, size)) = &stats.largest {
+                lines.push(Line::from(format!("largest repo: {} ({})", repo, Format::current().size(*size))));
+            }
+            let width = (area.width as usize).saturating_sub(30).clamp(10, 40);
+            for (title, counts) in [("Hosts", &stats.hosts), ("Languages", &stats.languages)] {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(title, Style::default().add_modifier(Modifier::BOLD))));
+                for bar in Stats::bars(counts, width) {
+                    lines.push(Line::from(Span::styled(format!("  {}", bar), Style::default().fg(Color::Cyan))));
+                }
+            }
+        }
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Directories the search for repos couldn't list and why, as many as fit
+fn render_skipped(f: &mut Frame, area: Rect, skipped: &[SkippedDir]) {
+    let mut lines = vec![
+        Line::from(Span::styled("Skipped directories", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled("repos below these were not found", Style::default().fg(Color::DarkGray))),
+    ];
+    let room = (area.height as usize).saturating_sub(4);
+    let shown = if skipped.len() > room { room.saturating_sub(1) } else { skipped.len() };
+    for dir in &skipped[..shown] {
+        lines.push(Line::from(vec![
+            Span::raw(Printer::tilde(&dir.path)),
+            Span::styled(format!(" {}", dir.reason), Style::default().fg(Color::Red)),
+        ]));
+    }
+    if skipped.len() > shown {
+        lines.push(Line::from(Span::styled(format!("+{} more, --no-tui prints them all", skipped.len() - shown), Style::default().fg(Color::DarkGray))));
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Files listed per category on a verbose card, the detail view lists them all
+const CARD_FILES: usize = 3;
+
+/// Keys of the grid, with what they do
+const GRID_KEYS: &[(&str, &str)] = &[
+    ("↑/↓", "scroll the cards"),
+    ("←/→", "scroll the clean repos"),
+    ("Tab/S-Tab", "select the next/previous card"),
+    ("Enter", "details of the selected repo, unfolds a folded group"),
+    ("c", "commit the staged changes"),
+    ("x", "move untracked files to the quarantine"),
+    ("P", "push a branch that is ahead of its upstream"),
+    ("m", "resolve conflicts"),
+    ("C/a", "continue/abort a merge, rebase or similar"),
+    ("e/g", "open in the editor/git tool"),
+    ("y/Y", "copy the path/a cd command"),
+    ("d/u", "dismiss the card/undo"),
+    ("n", "note on the repo"),
+    ("A", "acknowledge the repo"),
+    ("f", "pin the repo to the front"),
+    ("r/R", "rescan the repo/every repo"),
+    ("/", "filter by name, branch or path"),
+    ("s", "cycle the sort order"),
+    ("o", "cycle all/dirty/clean repos"),
+    ("i", "workspace stats"),
+    ("K", "directories skipped by the search"),
+    ("z/Z", "fold the group/all groups"),
+    ("Esc", "clear the filter, stop the scan"),
+    ("?", "this help"),
+    ("q", "quit"),
+];
+
+/// Keys of the detail view, on top of the repo actions of the grid
+const DETAIL_KEYS: &[(&str, &str)] = &[
+    ("Esc/Enter", "back to the grid"),
+    ("↑/↓", "scroll"),
+    ("PgUp/PgDn", "scroll a page, Home/End to the top/bottom"),
+    ("Tab/S-Tab", "next/previous changed file"),
+    ("space", "stage/unstage the file"),
+    ("p", "pull"),
+    ("b", "local branches"),
+    ("S", "list every submodule"),
+    ("F1-F12", "configured shortcuts"),
+];
+
+/// Every key with what it does, and the settings the grid is shown with
+fn render_help(f: &mut Frame, area: Rect, app: &App) {
+    let heading = |text: &'static str| Line::from(Span::styled(text, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    let key_lines = |keys: &[(&'static str, &'static str)]| -> Vec<Line<'static>> {
+        keys.iter()
+            .map(|(key, what)| Line::from(vec![Span::styled(format!("  {:<10}", key), Style::default().fg(Color::Cyan)), Span::raw(*what)]))
+            .collect()
+    };
+    let setting = |name: &'static str, value: String| Line::from(vec![Span::styled(format!("  {:<10}", name), Style::default().fg(Color::Cyan)), Span::raw(value)]);
+
+    let mut left = vec![heading("Keys")];
+    left.extend(key_lines(GRID_KEYS));
+
+    let mut right = vec![heading("Detail view")];
+    right.extend(key_lines(DETAIL_KEYS));
+    right.push(Line::from(""));
+    right.push(heading("Settings"));
+    right.push(setting("sort", app.scan_options.sort.name().to_string()));
+    right.push(setting("showing", state_filter_name(app.state_filter.as_ref()).to_string()));
+    right.push(setting("filter", if app.filter.is_empty() { "none".to_string() } else { app.filter.clone() }));
+    right.push(setting("verbose", if app.verbose { "on" } else { "off" }.to_string()));
+    right.push(setting("grouping", if app.group_by.is_some() { "by directory" } else { "none" }.to_string()));
+    right.push(setting("watch", app.watch.map_or("off".to_string(), |interval| format!("every {}s", interval.as_secs()))));
+    right.push(setting("theme", app.theme.to_possible_value().map_or(String::new(), |value| value.get_name().to_string())));
+
+    let block = Block::default().borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(inner);
+    f.render_widget(Paragraph::new(left), columns[0]);
+    f.render_widget(Paragraph::new(right), columns[1]);
+}
+
+fn render_commit_prompt(f: &mut Frame, size: Rect, prompt: &CommitPrompt, config: &CommitConfig) {
+    let width = size.width.saturating_sub(4).min(80);
+    let area = Rect::new((size.width - width) / 2, size.height.saturating_sub(8) / 2, width, 8.min(size.height));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(&prompt.buffer, Style::default().fg(Color::White)),
+            Span::styled("_", Style::default().fg(Color::Gray).add_modifier(Modifier::SLOW_BLINK)),
+        ]),
+    ];
+    if !prompt.body.is_empty() {
+        lines.push(Line::from(Span::styled("(+ commit template body)", Style::default().fg(Color::Gray))));
+    }
+    if !config.co_authors.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("(+ {} Co-authored-by trailers)", config.co_authors.len()),
+            Style::default().fg(Color::Gray))));
+    }
+    if prompt.signoff {
+        lines.push(Line::from(Span::styled("(+ Signed-off-by)", Style::default().fg(Color::Gray))));
+    }
+    if let Some(error) = &prompt.error {
+        lines.push(Line::from(Span::styled(error.as_str(), Style::default().fg(Color::Red))));
+    }
+    lines.push(Line::from(Span::styled("Enter to commit staged changes, Esc to cancel", Style::default().fg(Color::Gray))));
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Commit to {}", prompt.repo_name)))
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Detached HEADs are worth a look, an unfinished merge or rebase needs attention
+fn state_warning(state: RepoState) -> Line<'static> {
+    let style = if state.in_progress() {
+        Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+    };
+    Line::from(Span::styled(format!(" {} ", state.describe().to_uppercase()), style))
+}
+
+fn calculate_repo_height(repo: &RepoInfo, verbose: bool, note: Option<&str>) -> u16 {
     let mut height = 4; // base height: name + branch + borders
+    if note.is_some() { height += 1; }
+    if repo.last_commit.is_some() { height += 1; }
+    if repo.repo_state != RepoState::Normal { height += 1; }
+    height += render::notes(repo).len() as u16;
     
     if repo.has_changes() {
+        height += 1; // staged/unstaged line
         if verbose {
-            // in verbose mode, each file type gets its own line
-            if repo.new_files.amount > 0 { height += 1; }
-            if repo.added_files.amount > 0 { height += 1; }
-            if repo.modified_files.amount > 0 { height += 1; }
-            if repo.deleted_files.amount > 0 { height += 1; }
+            // in verbose mode, each file type gets its own lines
+            height += repo.trackers().into_iter().map(|tracker| render::files_height(tracker, CARD_FILES) as u16).sum::<u16>();
         } else {
-            // in simple mode, all changes fit on one line
+            // in simple mode, all changes fit on one line, conflicts get a line of their own
             height += 1;
+            if repo.has_conflicts() { height += 1; }
         }
     } else {
         height += 1;
@@ -373,6 +2416,11 @@ fn calculate_repo_height(repo: &RepoInfo, verbose: bool) -> u16 {
     height
 }
 
+/// A category on a verbose card: its count, then its first files cut to `width` from the left, and how many more there are
+fn card_file_lines(tracker: &FileTracker, color: Color, width: usize) -> Vec<Line<'static>> {
+    render::files(tracker, color, CARD_FILES, width).into_iter().map(row_line).collect()
+}
+
 fn render_clean_repos_footer(f: &mut Frame, area: Rect, clean_repos: &[RepoInfo], scroll_offset: usize, terminal_width: u16) {
     let repo_width = 12; // each clean repo takes 12 characters
     let visible_count = (terminal_width / repo_width).max(1) as usize;
@@ -385,9 +2433,13 @@ fn render_clean_repos_footer(f: &mut Frame, area: Rect, clean_repos: &[RepoInfo]
         if i > 0 {
             spans.push(Span::raw(" "));
         }
+        // clean but local only repos are still at risk
         spans.push(Span::styled(
-            format!("[{}]", &repo.name),
-            Style::default().fg(Color::Green)
+            match &repo.host {
+                Some(host) => format!("[{}:{}]", host, repo.name),
+                None => format!("[{}]", repo.name),
+            },
+            Style::default().fg(if repo.local_only { Color::Red } else { Color::Green })
         ));
     }
     
@@ -406,53 +2458,31 @@ fn render_clean_repos_footer(f: &mut Frame, area: Rect, clean_repos: &[RepoInfo]
     f.render_widget(paragraph, area);
 }
 
-fn render_repo_widget(f: &mut Frame, area: Rect, repo: &RepoInfo, verbose: bool) {
-    let title = Line::from(vec![
-        Span::styled(&repo.name, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-    ]);
-    let branch = Line::from(vec![
-        Span::styled(format!("[{}]", &repo.branch), Style::default().fg(Color::Green)),
-    ]);
+/// An acknowledged card is dimmed, a note is shown below the branch
+fn render_repo_widget(f: &mut Frame, area: Rect, app: &App, repo: &RepoInfo, selected: bool) {
+    let (verbose, note, acknowledged) = (app.verbose, app.notes.text(&repo.path), app.notes.is_acknowledged(repo));
+    let remote = app.remote_status.get(&repo.path).and_then(|status| status.as_ref().ok());
+    let title = row_line(render::title(repo));
+    let mut branch_spans = row_spans(render::branch_row(repo));
+    if let Some(pr) = remote.and_then(|status| status.pull_request.as_ref()) {
+        let style = if pr.draft { Style::default().fg(Color::DarkGray) } else { Style::default().fg(Color::Cyan) };
+        branch_spans.push(Span::styled(format!(" PR{}", pr.reference), style));
+    }
+    if let Some(ci) = remote.and_then(|status| status.ci) {
+        branch_spans.push(Span::styled(format!(" CI:{}", ci.describe()), Style::default().fg(ci_color(ci))));
+    }
+    let branch = Line::from(branch_spans);
+    // file names keep their end, where the file itself is
+    let name_width = (area.width as usize).saturating_sub(4);
     let changes = |repo: &RepoInfo| -> Vec<Line> {
         if repo.has_changes() {
             if verbose {
-                vec![
-                    Line::from(vec![Span::styled(
-                        format!("{}: {}", &repo.new_files.status, &repo.new_files.amount), 
-                        Style::default().fg(Color::Blue))]),
-                    Line::from(vec![Span::styled(
-                        format!("{}: {}", &repo.new_files.status, &repo.new_files.amount), 
-                        Style::default().fg(Color::Blue))]),
-                        //TODO: for each of the files, make a new Line with the file name and color
-
-                    Line::from(vec![Span::styled(
-                        format!("{}: {}", &repo.added_files.status, &repo.added_files.amount), 
-                        Style::default().fg(Color::Green))]),
-                        //TODO: for each of the files, make a new Line with the file name and color
-                    Line::from(vec![Span::styled(
-                        format!("{}: {}", &repo.modified_files.status, &repo.modified_files.amount), 
-                        Style::default().fg(Color::Yellow))]),
-                        //TODO: for each of the files, make a new Line with the file name and color
-                    Line::from(vec![Span::styled(
-                        format!("{}: {}", &repo.deleted_files.status, &repo.deleted_files.amount),
-                        Style::default().fg(Color::Red))]),
-                        //TODO: for each of the files, make a new Line with the file name and color
-                ]
+                repo.trackers().into_iter().zip(render::TRACKER_COLORS).skip(1)
+                    .filter(|(tracker, _)| tracker.amount > 0)
+                    .flat_map(|(tracker, color)| card_file_lines(tracker, color, name_width))
+                    .collect()
             } else {
-                vec![Line::from(vec![
-                    Span::styled(
-                        format!("{}:{} ", &repo.new_files.status, &repo.new_files.amount), 
-                        Style::default().fg(Color::Blue)),
-                    Span::styled(
-                        format!("{}:{} ", &repo.added_files.status, &repo.added_files.amount), 
-                        Style::default().fg(Color::Green)),
-                    Span::styled(
-                        format!("{}:{} ", &repo.modified_files.status, &repo.modified_files.amount), 
-                        Style::default().fg(Color::Yellow)),
-                    Span::styled(
-                        format!("{}:{} ", &repo.deleted_files.status, &repo.deleted_files.amount),
-                        Style::default().fg(Color::Red)),
-                ])]
+                vec![row_line(render::counts(repo))]
             }
         } else {
             vec![Line::from(
@@ -461,10 +2491,43 @@ fn render_repo_widget(f: &mut Frame, area: Rect, repo: &RepoInfo, verbose: bool)
         }
     };
 
-    let content: Vec<Line> = vec![title, branch, changes(repo).into_iter().flatten().collect()];
+    let mut content: Vec<Line> = vec![title, branch];
+    if let Some(note) = note {
+        content.push(Line::from(Span::styled(note.to_string(), Style::default().add_modifier(Modifier::ITALIC))));
+    }
+    if repo.repo_state != RepoState::Normal {
+        content.push(state_warning(repo.repo_state));
+    }
+    content.extend(render::notes(repo).into_iter().map(|note| row_line(vec![note])));
+    content.extend(render::last_commit(repo).map(row_line));
+    if repo.has_conflicts() {
+        // conflicts get their own line above everything else
+        let mut lines = card_file_lines(&repo.conflicted_files, Color::Red, name_width);
+        if !verbose {
+            lines.truncate(1);
+        }
+        lines[0].style = Style::default().add_modifier(Modifier::BOLD);
+        content.extend(lines);
+    }
+    content.extend(render::staging(repo).map(row_line));
+    content.extend(changes(repo));
 
+    let block = if selected {
+        Block::default().borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan))
+    } else if let Some(attention) = repo.attention.as_ref().filter(|attention| attention.level == Level::Critical) {
+        Block::default().borders(Borders::ALL).border_style(Style::default().fg(Printer::level_color(attention.level)))
+    } else if repo.edits_protected_branch() {
+        Block::default().borders(Borders::ALL).border_style(Style::default().fg(render::PROTECTED_COLOR))
+    } else if let Some(attention) = &repo.attention {
+        Block::default().borders(Borders::ALL).border_style(Style::default().fg(Printer::level_color(attention.level)))
+    } else {
+        Block::default().borders(Borders::ALL)
+    };
+    let block = if acknowledged { block.title(Span::styled("ok", Style::default().fg(Color::DarkGray))) } else { block };
+    let style = if acknowledged { Style::default().add_modifier(Modifier::DIM) } else { Style::default() };
     let paragraph = Paragraph::new(content)
-        .block(Block::default().borders(Borders::ALL))
+        .block(block)
+        .style(style)
         .wrap(Wrap { trim: true });
 
     f.render_widget(paragraph, area);
@@ -0,0 +1,210 @@
+use crate::printer::Printer;
+use crate::theme::Theme;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crossterm::{
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+
+/// A subdirectory of the directory being browsed
+struct Entry {
+    name: String,
+    path: PathBuf,
+    /// has a `.git` of its own
+    repo: bool,
+}
+
+/// Browses directories to pick where to scan, for when repolice starts somewhere without repos
+struct Picker {
+    /// where the scan would have looked
+    start: PathBuf,
+    dir: PathBuf,
+    /// subdirectories of `dir`, ".." first unless `dir` is the root
+    entries: Vec<Entry>,
+    selected: usize,
+    scroll: usize,
+    show_hidden: bool,
+    theme: Theme,
+    basic: bool,
+}
+
+impl Picker {
+    fn new(start: &Path, theme: Theme, basic: bool) -> Picker {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        let mut picker = Picker { start: start.clone(), dir: start, entries: Vec::new(), selected: 0, scroll: 0, show_hidden: false, theme, basic };
+        picker.read_dir();
+        picker
+    }
+
+    /// Lists the subdirectories of `dir`, hidden ones only when asked for
+    fn read_dir(&mut self) {
+        let mut entries: Vec<Entry> = fs::read_dir(&self.dir).into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+            .map(|entry| {
+                let path = entry.path();
+                Entry { name: entry.file_name().to_string_lossy().to_string(), repo: path.join(".git").exists(), path }
+            })
+            .filter(|entry| self.show_hidden || !entry.name.starts_with('.'))
+            .collect();
+        entries.sort_by_cached_key(|entry| entry.name.to_lowercase());
+        if let Some(parent) = self.dir.parent() {
+            entries.insert(0, Entry { name: "..".to_string(), path: parent.to_path_buf(), repo: false });
+        }
+        self.entries = entries;
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    /// Browses into `dir`, selecting the directory we came from when going up
+    fn open(&mut self, dir: PathBuf) {
+        let previous = self.dir.clone();
+        self.dir = dir;
+        self.read_dir();
+        if let Some(index) = self.entries.iter().position(|entry| entry.path == previous) {
+            self.selected = index;
+        }
+    }
+
+    fn up(&mut self) {
+        if let Some(parent) = self.dir.parent() {
+            self.open(parent.to_path_buf());
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if !self.entries.is_empty() {
+            self.selected = self.selected.saturating_add_signed(delta).min(self.entries.len() - 1);
+        }
+    }
+
+    /// Handles a key, returning the directory to scan once one is picked
+    fn handle_key(&mut self, code: KeyCode) -> Option<PathBuf> {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.entries.len().saturating_sub(1),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(entry) = self.entries.get(self.selected) {
+                    self.open(entry.path.clone());
+                }
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.up(),
+            KeyCode::Char('.') => {
+                self.show_hidden = !self.show_hidden;
+                let selected = self.entries.get(self.selected).map(|entry| entry.path.clone());
+                self.read_dir();
+                self.selected = selected.and_then(|path| self.entries.iter().position(|entry| entry.path == path)).unwrap_or(0);
+            }
+            KeyCode::Char('s') => return Some(self.dir.clone()),
+            KeyCode::Char(' ') => return self.entries.get(self.selected).map(|entry| entry.path.clone()),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Min(3), Constraint::Length(3)])
+            .split(f.area());
+
+        let header = vec![
+            Line::from(Span::styled(format!("No repos below {}", Printer::tilde(&self.start.to_string_lossy())), Style::default().fg(Color::Yellow))),
+            Line::from(vec![
+                Span::raw("Pick a directory to scan: "),
+                Span::styled(Printer::tilde(&self.dir.to_string_lossy()), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            ]),
+        ];
+        f.render_widget(Paragraph::new(header).block(Block::default().borders(Borders::ALL).title("Repolice")), chunks[0]);
+
+        // keep the selected entry in view
+        let rows = chunks[1].height.saturating_sub(2) as usize;
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if rows > 0 && self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+        let lines: Vec<Line> = if self.entries.is_empty() {
+            vec![Line::from(Span::styled("No subdirectories", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)))]
+        } else {
+            self.entries.iter().enumerate().skip(self.scroll).take(rows)
+                .map(|(index, entry)| {
+                    let mut spans = vec![Span::raw(format!("{}/", entry.name))];
+                    if entry.repo {
+                        spans.push(Span::styled(" git", Style::default().fg(Color::Green)));
+                    }
+                    let line = Line::from(spans);
+                    if index == self.selected { line.style(Style::default().add_modifier(Modifier::REVERSED)) } else { line }
+                })
+                .collect()
+        };
+        let title = if self.show_hidden { "Directories (hidden shown)" } else { "Directories" };
+        f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), chunks[1]);
+
+        let help = "↑↓ select, Enter open, Backspace up, s scan this directory, space scan the selected one, . hidden, q quit";
+        f.render_widget(Paragraph::new(help).style(Style::default().fg(Color::Gray)).block(Block::default().borders(Borders::ALL)), chunks[2]);
+
+        self.theme.apply(f.buffer_mut());
+        if self.basic {
+            super::to_basic(f.buffer_mut());
+        }
+    }
+}
+
+/// Lets the user pick a directory to scan, starting at `start`. None when they quit instead.
+pub fn pick(start: &Path, theme: Theme, basic: bool) -> io::Result<Option<PathBuf>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    // the shell is left with a usable terminal when it can't be taken over
+    if let Err(e) = execute!(stdout, EnterAlternateScreen) {
+        let _ = disable_raw_mode();
+        return Err(e);
+    }
+    let mut terminal = match Terminal::new(CrosstermBackend::new(stdout)) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen);
+            return Err(e);
+        }
+    };
+
+    let picked = run(&mut terminal, Picker::new(start, theme, basic));
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    picked
+}
+
+fn run<B: Backend>(terminal: &mut Terminal<B>, mut picker: Picker) -> io::Result<Option<PathBuf>> {
+    loop {
+        terminal.draw(|f| picker.render(f))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+            return Ok(None);
+        }
+        if let Some(dir) = picker.handle_key(key.code) {
+            return Ok(Some(dir));
+        }
+    }
+}